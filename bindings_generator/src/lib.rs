@@ -41,6 +41,7 @@ pub type GeneratorResult<T = ()> = Result<T, io::Error>;
 pub struct BindingResult {
     pub class_bindings: HashMap<String, TokenStream>,
     pub icalls: TokenStream,
+    pub global_type_tags: TokenStream,
 }
 
 pub fn generate_bindings(api: &Api, docs: Option<&GodotXmlDocs>) -> BindingResult {
@@ -62,9 +63,12 @@ pub fn generate_bindings(api: &Api, docs: Option<&GodotXmlDocs>) -> BindingResul
         .map(|(name, sig)| generate_icall(name, sig))
         .collect();
 
+    let global_type_tags = generate_global_type_tags(api);
+
     BindingResult {
         class_bindings,
         icalls,
+        global_type_tags,
     }
 }
 
@@ -76,6 +80,45 @@ pub fn generate_imports() -> TokenStream {
     }
 }
 
+/// Generates a `global_type_tags` module with a static `GlobalClass` for each class, used as
+/// its global type tag, and an `ALL` slice of them for registration.
+fn generate_global_type_tags(api: &Api) -> TokenStream {
+    let classes = api.classes.iter().map(|class| {
+        let name = &class.name;
+        let ident = format_ident!("{}", class.name);
+
+        let base = if class.base_class.is_empty() {
+            quote! { None }
+        } else {
+            let base = format_ident!("{}", class.base_class);
+            quote! { Some(&#base) }
+        };
+
+        quote! {
+            pub(crate) static #ident: GlobalClass = GlobalClass {
+                name: #name,
+                base: #base,
+            };
+        }
+    });
+
+    let idents = api
+        .classes
+        .iter()
+        .map(|class| format_ident!("{}", class.name));
+
+    quote! {
+        #[allow(non_upper_case_globals)]
+        pub(crate) mod global_type_tags {
+            use gdnative_core::private::GlobalClass;
+
+            #(#classes)*
+
+            pub(crate) static ALL: &[&GlobalClass] = &[#(&#idents),*];
+        }
+    }
+}
+
 fn generate_class_bindings(
     api: &Api,
     class: &GodotClass,
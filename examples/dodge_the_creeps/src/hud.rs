@@ -12,6 +12,7 @@ impl Hud {
         builder.add_signal(Signal {
             name: "start_game",
             args: &[],
+            num_default_args: 0,
         });
     }
 
@@ -19,6 +19,7 @@ impl Player {
         builder.add_signal(Signal {
             name: "hit",
             args: &[],
+            num_default_args: 0,
         });
     }
 
@@ -1,6 +1,8 @@
 use gdnative::api::NetworkedMultiplayerENet;
 use gdnative::prelude::*;
 
+use crate::server::Server;
+
 const ADDRESS: &str = "127.0.0.1";
 const PORT: i64 = 9876;
 const IN_BANDWIDTH: i64 = 1000;
@@ -46,7 +48,7 @@ impl ServerPuppet {
 
     #[export]
     fn on_connected_to_server(&mut self, owner: TRef<Node>) {
-        owner.rpc("greet_server", &[Variant::from_str("hello")]);
+        Server::rpc_greet_server(&owner, "hello".into());
     }
 
     #[export(rpc = "puppet")]
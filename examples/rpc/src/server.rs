@@ -1,6 +1,8 @@
 use gdnative::api::NetworkedMultiplayerENet;
 use gdnative::prelude::*;
 
+use crate::client::ServerPuppet;
+
 const PORT: i64 = 9876;
 const MAX_CLIENTS: i64 = 1;
 const IN_BANDWIDTH: i64 = 1000;
@@ -35,10 +37,6 @@ impl Server {
         let tree = owner.get_tree().expect("could not retreive Scene Tree");
         let tree = unsafe { tree.assume_safe() };
 
-        owner.rpc_id(
-            tree.get_rpc_sender_id(),
-            "return_greeting",
-            &[Variant::from_str("hello")],
-        );
+        ServerPuppet::rpc_id_return_greeting(owner, tree.get_rpc_sender_id(), "hello".into());
     }
 }
@@ -15,6 +15,7 @@ impl SignalEmitter {
         builder.add_signal(Signal {
             name: "tick",
             args: &[],
+            num_default_args: 0,
         });
 
         builder.add_signal(Signal {
@@ -26,6 +27,7 @@ impl SignalEmitter {
                 export_info: ExportInfo::new(VariantType::I64),
                 usage: PropertyUsage::DEFAULT,
             }],
+            num_default_args: 0,
         });
     }
 
@@ -22,6 +22,8 @@ fn main() {
         let mut output = BufWriter::new(File::create(&generated_rs).unwrap());
 
         generate(&out_path, &mut output, &binding_res);
+
+        write!(&mut output, "{}", binding_res.global_type_tags).unwrap();
     }
 
     {
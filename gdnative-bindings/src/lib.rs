@@ -10,4 +10,13 @@ pub use generated::*;
 
 pub mod utils;
 
+/// Registers global type tags for all generated classes, which speed up casts between them.
+///
+/// This is intended to be an internal interface.
+#[doc(hidden)]
+#[inline]
+pub fn register_global_type_tags() {
+    gdnative_core::private::register_global_type_tags(generated::global_type_tags::ALL);
+}
+
 pub(crate) mod icalls;
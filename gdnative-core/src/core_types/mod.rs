@@ -22,10 +22,18 @@ mod vector2;
 mod vector2_array;
 mod vector3_array;
 
+#[cfg(feature = "serde")]
+mod serialize;
+
 pub mod dictionary;
 pub mod error;
+pub mod typed_dictionary;
+pub mod typed_variant_array;
 pub mod vector3;
 
+#[cfg(feature = "serde")]
+pub mod variant_serde;
+
 pub use geom::*;
 
 pub use access::*;
@@ -44,6 +52,8 @@ pub use string::*;
 pub use string_array::*;
 pub use transform2d::*;
 pub use typed_array::TypedArray;
+pub use typed_dictionary::TypedDictionary;
+pub use typed_variant_array::TypedVariantArray;
 pub use variant::*;
 pub use variant_array::*;
 pub use vector2::*;
@@ -2,6 +2,7 @@ use super::IsEqualApprox;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Quat {
     pub x: f32,
     pub y: f32,
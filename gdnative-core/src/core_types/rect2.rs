@@ -1,6 +1,7 @@
 use super::Vector2;
 
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect2 {
     pub position: Vector2,
     pub size: Vector2,
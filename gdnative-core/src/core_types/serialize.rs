@@ -0,0 +1,308 @@
+//! Implementations of the `serde` traits for core types.
+//!
+//! `Variant`s are serialized as externally tagged enums, with the names of `VariantType` as
+//! tags, so the type of each value is preserved. Variants containing objects or `Rid`s can't
+//! be serialized. `variant_serde` passes `Variant`s through unchanged instead.
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess};
+use serde::ser::{self, Serialize, Serializer};
+
+use super::typed_array::Element;
+use super::*;
+use crate::sys;
+use crate::thread_access::{Shared, ThreadAccess, ThreadLocal, Unique};
+
+impl Serialize for GodotString {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for GodotString {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(GodotString::from_str)
+    }
+}
+
+impl Serialize for NodePath {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for NodePath {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(|path| NodePath::from_str(&path))
+    }
+}
+
+impl<T: Element + Serialize> Serialize for TypedArray<T> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.read().iter())
+    }
+}
+
+impl<'de, T: Element + Deserialize<'de>> Deserialize<'de> for TypedArray<T> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<T>::deserialize(deserializer).map(TypedArray::from_vec)
+    }
+}
+
+impl<Access: ThreadAccess> Serialize for VariantArray<Access> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+struct VariantArrayVisitor;
+
+impl<'de> de::Visitor<'de> for VariantArrayVisitor {
+    type Value = VariantArray<Unique>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a sequence of variants")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let array = VariantArray::new();
+        while let Some(element) = seq.next_element::<Variant>()? {
+            array.push(element);
+        }
+        Ok(array)
+    }
+}
+
+impl<'de> Deserialize<'de> for VariantArray<Unique> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(VariantArrayVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for VariantArray<Shared> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        VariantArray::<Unique>::deserialize(deserializer).map(VariantArray::into_shared)
+    }
+}
+
+impl<'de> Deserialize<'de> for VariantArray<ThreadLocal> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        VariantArray::<Unique>::deserialize(deserializer).map(VariantArray::into_thread_local)
+    }
+}
+
+impl<Access: ThreadAccess> Serialize for Dictionary<Access> {
+    #[inline]
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+struct DictionaryVisitor;
+
+impl<'de> de::Visitor<'de> for DictionaryVisitor {
+    type Value = Dictionary<Unique>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map of variants")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let dict = Dictionary::new();
+        while let Some((key, value)) = map.next_entry::<Variant, Variant>()? {
+            dict.insert(key, value);
+        }
+        Ok(dict)
+    }
+}
+
+impl<'de> Deserialize<'de> for Dictionary<Unique> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(DictionaryVisitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Dictionary<Shared> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Dictionary::<Unique>::deserialize(deserializer).map(Dictionary::into_shared)
+    }
+}
+
+impl<'de> Deserialize<'de> for Dictionary<ThreadLocal> {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Dictionary::<Unique>::deserialize(deserializer).map(Dictionary::into_thread_local)
+    }
+}
+
+impl Serialize for Variant {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let ty = self.get_type();
+        let index = ty as u32;
+        let name = VariantType::NAMES[index as usize];
+
+        macro_rules! newtype {
+            ($value:expr) => {
+                serializer.serialize_newtype_variant("Variant", index, name, $value)
+            };
+        }
+
+        match self.dispatch() {
+            VariantDispatch::Nil => serializer.serialize_unit_variant("Variant", index, name),
+            VariantDispatch::Bool(v) => newtype!(&v),
+            VariantDispatch::I64(v) => newtype!(&v),
+            VariantDispatch::F64(v) => newtype!(&v),
+            VariantDispatch::GodotString(v) => newtype!(&v),
+            VariantDispatch::Vector2(v) => newtype!(&v),
+            VariantDispatch::Rect2(v) => newtype!(&v),
+            VariantDispatch::Vector3(v) => newtype!(&v),
+            VariantDispatch::Transform2D(v) => newtype!(&v),
+            VariantDispatch::Plane(v) => newtype!(&v),
+            VariantDispatch::Quat(v) => newtype!(&v),
+            VariantDispatch::Aabb(v) => newtype!(&v),
+            VariantDispatch::Basis(v) => newtype!(&v),
+            VariantDispatch::Transform(v) => newtype!(&v),
+            VariantDispatch::Color(v) => newtype!(&v),
+            VariantDispatch::NodePath(v) => newtype!(&v),
+            VariantDispatch::Dictionary(v) => newtype!(&v),
+            VariantDispatch::VariantArray(v) => newtype!(&v),
+            VariantDispatch::ByteArray(v) => newtype!(&v),
+            VariantDispatch::Int32Array(v) => newtype!(&v),
+            VariantDispatch::Float32Array(v) => newtype!(&v),
+            VariantDispatch::StringArray(v) => newtype!(&v),
+            VariantDispatch::Vector2Array(v) => newtype!(&v),
+            VariantDispatch::Vector3Array(v) => newtype!(&v),
+            VariantDispatch::ColorArray(v) => newtype!(&v),
+            VariantDispatch::Rid(_) | VariantDispatch::Object(_) => Err(ser::Error::custom(
+                format_args!("cannot serialize a Variant of type {:?}", ty),
+            )),
+        }
+    }
+}
+
+/// Identifier of a `Variant` enum variant, accepting either the name or the index of a
+/// `VariantType`.
+struct VariantTypeIdent(VariantType);
+
+impl<'de> Deserialize<'de> for VariantTypeIdent {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct IdentVisitor;
+
+        impl<'de> de::Visitor<'de> for IdentVisitor {
+            type Value = VariantTypeIdent;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a variant type")
+            }
+
+            fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+                if v < VariantType::NAMES.len() as u64 {
+                    Ok(VariantTypeIdent(VariantType::from_sys(
+                        v as sys::godot_variant_type,
+                    )))
+                } else {
+                    Err(E::invalid_value(
+                        de::Unexpected::Unsigned(v),
+                        &"a variant type index",
+                    ))
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                match VariantType::NAMES.iter().position(|&name| name == v) {
+                    Some(index) => self.visit_u64(index as u64),
+                    None => Err(E::unknown_variant(v, VariantType::NAMES)),
+                }
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                match std::str::from_utf8(v) {
+                    Ok(v) => self.visit_str(v),
+                    Err(_) => Err(E::invalid_value(de::Unexpected::Bytes(v), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_identifier(IdentVisitor)
+    }
+}
+
+struct VariantVisitor;
+
+impl<'de> de::Visitor<'de> for VariantVisitor {
+    type Value = Variant;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a Variant")
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let (VariantTypeIdent(ty), variant) = data.variant()?;
+
+        macro_rules! newtype {
+            ($type:ty) => {
+                variant.newtype_variant::<$type>()?.owned_to_variant()
+            };
+        }
+
+        let value = match ty {
+            VariantType::Nil => {
+                variant.unit_variant()?;
+                Variant::new()
+            }
+            VariantType::Bool => newtype!(bool),
+            VariantType::I64 => newtype!(i64),
+            VariantType::F64 => newtype!(f64),
+            VariantType::GodotString => newtype!(GodotString),
+            VariantType::Vector2 => newtype!(Vector2),
+            VariantType::Rect2 => newtype!(Rect2),
+            VariantType::Vector3 => newtype!(Vector3),
+            VariantType::Transform2D => newtype!(Transform2D),
+            VariantType::Plane => newtype!(Plane),
+            VariantType::Quat => newtype!(Quat),
+            VariantType::Aabb => newtype!(Aabb),
+            VariantType::Basis => newtype!(Basis),
+            VariantType::Transform => newtype!(Transform),
+            VariantType::Color => newtype!(Color),
+            VariantType::NodePath => newtype!(NodePath),
+            VariantType::Dictionary => newtype!(Dictionary<Unique>),
+            VariantType::VariantArray => newtype!(VariantArray<Unique>),
+            VariantType::ByteArray => newtype!(ByteArray),
+            VariantType::Int32Array => newtype!(Int32Array),
+            VariantType::Float32Array => newtype!(Float32Array),
+            VariantType::StringArray => newtype!(StringArray),
+            VariantType::Vector2Array => newtype!(Vector2Array),
+            VariantType::Vector3Array => newtype!(Vector3Array),
+            VariantType::ColorArray => newtype!(ColorArray),
+            VariantType::Rid | VariantType::Object => {
+                return Err(de::Error::custom(format_args!(
+                    "cannot deserialize a Variant of type {:?}",
+                    ty
+                )))
+            }
+        };
+
+        Ok(value)
+    }
+}
+
+impl<'de> Deserialize<'de> for Variant {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum("Variant", VariantType::NAMES, VariantVisitor)
+    }
+}
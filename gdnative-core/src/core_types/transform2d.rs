@@ -1,6 +1,7 @@
 use super::Vector2;
 
 #[repr(C)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Transform2D {
     pub x: Vector2,
     pub y: Vector2,
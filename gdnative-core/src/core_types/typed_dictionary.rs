@@ -0,0 +1,448 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+use gdnative_impl_proc_macros::doc_variant_collection_safety;
+
+use crate::core_types::dictionary;
+use crate::core_types::Dictionary;
+use crate::core_types::FromVariant;
+use crate::core_types::FromVariantError;
+use crate::core_types::OwnedToVariant;
+use crate::core_types::ToVariant;
+use crate::core_types::ToVariantEq;
+use crate::core_types::Variant;
+use crate::NewRef;
+
+use crate::thread_access::*;
+
+/// A `Dictionary` whose keys are known to be convertible to `K`, and whose values are known
+/// to be convertible to `V`.
+///
+/// This is a zero-cost wrapper around [`Dictionary`](../dictionary/struct.Dictionary.html) with
+/// the same memory layout and the same *typestate* `Access`. The entries are validated once,
+/// when the wrapper is created through `TryFrom` or `FromVariant`. Typed accessors then convert
+/// entries on the fly.
+///
+/// Since the underlying dictionary is reference-counted, entries can still be replaced through
+/// other references, e.g. from GDScript. Accessors that return `V` panic if a value is no
+/// longer convertible. Use `try_get` and `try_iter` where this needs to be handled.
+#[repr(transparent)]
+pub struct TypedDictionary<K, V, Access: ThreadAccess = Shared> {
+    dict: Dictionary<Access>,
+
+    /// Marker for the entry types. Thread safety is determined by `Access` alone.
+    _marker: PhantomData<fn() -> (K, V)>,
+}
+
+/// Operations allowed on all typed dictionaries at any point in time.
+impl<K, V, Access: ThreadAccess> TypedDictionary<K, V, Access> {
+    /// Wraps a dictionary without validating its entries.
+    #[inline]
+    fn from_untyped_unchecked(dict: Dictionary<Access>) -> Self {
+        TypedDictionary {
+            dict,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns `true` if the dictionary contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.dict.is_empty()
+    }
+
+    /// Returns the number of elements in the dictionary.
+    #[inline]
+    pub fn len(&self) -> i32 {
+        self.dict.len()
+    }
+
+    /// Returns a reference to the underlying `Dictionary`.
+    #[inline]
+    pub fn as_untyped(&self) -> &Dictionary<Access> {
+        &self.dict
+    }
+
+    /// Unwraps the underlying `Dictionary`.
+    #[inline]
+    pub fn into_untyped(self) -> Dictionary<Access> {
+        self.dict
+    }
+
+    /// Create a copy of the dictionary.
+    ///
+    /// This creates a new dictionary and is **not** a cheap reference count
+    /// increment.
+    #[inline]
+    pub fn duplicate(&self) -> TypedDictionary<K, V, Unique> {
+        TypedDictionary::from_untyped_unchecked(self.dict.duplicate())
+    }
+}
+
+impl<K: ToVariant + ToVariantEq, V, Access: ThreadAccess> TypedDictionary<K, V, Access> {
+    /// Returns true if the dictionary contains the specified key.
+    #[inline]
+    pub fn contains(&self, key: &K) -> bool {
+        self.dict.contains(key)
+    }
+}
+
+impl<K: ToVariant + ToVariantEq, V: FromVariant, Access: ThreadAccess>
+    TypedDictionary<K, V, Access>
+{
+    /// Returns a copy of the value corresponding to the key, converted to `V`, if it exists.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value can't be converted to `V`, which is the case when it has been
+    /// replaced through another reference.
+    #[inline]
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.try_get(key).map(|value| match value {
+            Ok(value) => value,
+            Err(err) => panic!("value of TypedDictionary is invalid: {}", err),
+        })
+    }
+
+    /// Returns a copy of the value corresponding to the key if it exists, or an error if it
+    /// can't be converted to `V`.
+    #[inline]
+    pub fn try_get(&self, key: &K) -> Option<Result<V, FromVariantError>> {
+        self.dict.get(key).map(|value| V::from_variant(&value))
+    }
+}
+
+impl<K: FromVariant, V: FromVariant, Access: ThreadAccess> TypedDictionary<K, V, Access> {
+    /// Returns an iterator through all key-value pairs in the dictionary, converted to
+    /// `(K, V)`.
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if it encounters an entry that can't be converted. Use `try_iter`
+    /// where this needs to be handled.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, K, V, Access> {
+        Iter {
+            iter: self.try_iter(),
+        }
+    }
+
+    /// Returns an iterator through all key-value pairs in the dictionary, converted to
+    /// `(K, V)`, or an error if they can't be converted.
+    ///
+    /// A key that can't be converted is reported as `InvalidKey`, and a value that can't be
+    /// converted as `InvalidEntry`, both with the key of the entry.
+    #[inline]
+    pub fn try_iter(&self) -> TryIter<'_, K, V, Access> {
+        TryIter {
+            iter: self.dict.iter(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Operations allowed on typed dictionaries that can only be referenced to from the current
+/// thread.
+impl<K, V, Access> TypedDictionary<K, V, Access>
+where
+    K: OwnedToVariant + ToVariantEq,
+    V: OwnedToVariant,
+    Access: LocalThreadAccess,
+{
+    /// Inserts or updates the value of the element corresponding to the key.
+    #[inline]
+    pub fn insert(&self, key: K, val: V) {
+        self.dict.insert(key, val)
+    }
+}
+
+impl<K: ToVariant + ToVariantEq, V, Access: LocalThreadAccess> TypedDictionary<K, V, Access> {
+    /// Erase a key-value pair in the dictionary by the specified key.
+    #[inline]
+    pub fn erase(&self, key: &K) {
+        self.dict.erase(key)
+    }
+}
+
+impl<K, V, Access: LocalThreadAccess> TypedDictionary<K, V, Access> {
+    /// Clears the dictionary, removing all key-value pairs.
+    #[inline]
+    pub fn clear(&self) {
+        self.dict.clear()
+    }
+}
+
+/// Operations allowed on typed dictionaries that might be shared between different threads.
+impl<K, V> TypedDictionary<K, V, Shared>
+where
+    K: OwnedToVariant + ToVariantEq,
+    V: OwnedToVariant,
+{
+    /// Inserts or updates the value of the element corresponding to the key.
+    ///
+    #[doc_variant_collection_safety]
+    #[inline]
+    pub unsafe fn insert(&self, key: K, val: V) {
+        self.dict.insert(key, val)
+    }
+}
+
+/// Operations allowed on typed dictionaries that are not unique.
+impl<K, V, Access: NonUniqueThreadAccess> TypedDictionary<K, V, Access> {
+    /// Assume that this is the only reference to this dictionary, on which
+    /// operations that change the container size can be safely performed.
+    ///
+    /// # Safety
+    ///
+    /// It isn't thread-safe to perform operations that change the container
+    /// size from multiple threads at the same time.
+    /// Creating multiple `Unique` references to the same collections, or
+    /// violating the thread-safety guidelines in non-Rust code will cause
+    /// undefined behavior.
+    #[inline]
+    pub unsafe fn assume_unique(self) -> TypedDictionary<K, V, Unique> {
+        TypedDictionary::from_untyped_unchecked(self.dict.assume_unique())
+    }
+}
+
+/// Operations allowed on unique typed dictionaries.
+impl<K, V> TypedDictionary<K, V, Unique> {
+    /// Creates an empty typed dictionary.
+    #[inline]
+    pub fn new() -> Self {
+        Self::from_untyped_unchecked(Dictionary::new())
+    }
+
+    /// Put this dictionary under the "shared" access type.
+    #[inline]
+    pub fn into_shared(self) -> TypedDictionary<K, V, Shared> {
+        TypedDictionary::from_untyped_unchecked(self.dict.into_shared())
+    }
+
+    /// Put this dictionary under the "thread-local" access type.
+    #[inline]
+    pub fn into_thread_local(self) -> TypedDictionary<K, V, ThreadLocal> {
+        TypedDictionary::from_untyped_unchecked(self.dict.into_thread_local())
+    }
+}
+
+/// Checks that all entries of the dictionary can be converted to `(K, V)`.
+///
+/// A key that can't be converted is reported as `InvalidKey`, and a value that can't be
+/// converted as `InvalidEntry`, both with the key of the entry.
+impl<K, V, Access> TryFrom<Dictionary<Access>> for TypedDictionary<K, V, Access>
+where
+    K: FromVariant,
+    V: FromVariant,
+    Access: ThreadAccess,
+{
+    type Error = FromVariantError;
+
+    #[inline]
+    fn try_from(dict: Dictionary<Access>) -> Result<Self, Self::Error> {
+        for (key, value) in dict.iter() {
+            K::from_variant(&key).map_err(|err| FromVariantError::InvalidKey {
+                key: key.to_string(),
+                error: Box::new(err),
+            })?;
+            V::from_variant(&value).map_err(|err| FromVariantError::InvalidEntry {
+                key: key.to_string(),
+                error: Box::new(err),
+            })?;
+        }
+
+        Ok(Self::from_untyped_unchecked(dict))
+    }
+}
+
+impl<K, V> Default for TypedDictionary<K, V, Unique> {
+    #[inline]
+    fn default() -> Self {
+        TypedDictionary::new()
+    }
+}
+
+impl<K, V, Access: NonUniqueThreadAccess> NewRef for TypedDictionary<K, V, Access> {
+    /// Creates a new reference to the same dictionary.
+    #[inline]
+    fn new_ref(&self) -> Self {
+        Self::from_untyped_unchecked(self.dict.new_ref())
+    }
+}
+
+impl<K, V> From<TypedDictionary<K, V, Unique>> for TypedDictionary<K, V, Shared> {
+    #[inline]
+    fn from(dict: TypedDictionary<K, V, Unique>) -> Self {
+        dict.into_shared()
+    }
+}
+
+impl<K, V, Access: ThreadAccess> From<TypedDictionary<K, V, Access>> for Dictionary<Access> {
+    #[inline]
+    fn from(dict: TypedDictionary<K, V, Access>) -> Self {
+        dict.into_untyped()
+    }
+}
+
+impl<K, V, Access: ThreadAccess> fmt::Debug for TypedDictionary<K, V, Access> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.dict.fmt(f)
+    }
+}
+
+impl<K, V> ToVariant for TypedDictionary<K, V, Shared> {
+    #[inline]
+    fn to_variant(&self) -> Variant {
+        self.dict.to_variant()
+    }
+}
+
+impl<K, V> OwnedToVariant for TypedDictionary<K, V, Unique> {
+    #[inline]
+    fn owned_to_variant(self) -> Variant {
+        self.into_shared().to_variant()
+    }
+}
+
+impl<K: FromVariant, V: FromVariant> FromVariant for TypedDictionary<K, V, Shared> {
+    #[inline]
+    fn from_variant(variant: &Variant) -> Result<Self, FromVariantError> {
+        Dictionary::from_variant(variant).and_then(Self::try_from)
+    }
+}
+
+/// Iterator through all key-value pairs in a typed dictionary, converted to `(K, V)`.
+///
+/// This struct is created by the `iter` method on `TypedDictionary`.
+pub struct Iter<'a, K, V, Access: ThreadAccess> {
+    iter: TryIter<'a, K, V, Access>,
+}
+
+impl<'a, K: FromVariant, V: FromVariant, Access: ThreadAccess> Iterator for Iter<'a, K, V, Access> {
+    type Item = (K, V);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|entry| match entry {
+            Ok(entry) => entry,
+            Err(err) => panic!("entry of TypedDictionary is invalid: {}", err),
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Iterator through all key-value pairs in a typed dictionary, converted to `(K, V)`, or to
+/// an error if they can't be converted.
+///
+/// This struct is created by the `try_iter` method on `TypedDictionary`.
+pub struct TryIter<'a, K, V, Access: ThreadAccess> {
+    iter: dictionary::Iter<'a, Access>,
+    _marker: PhantomData<fn() -> (K, V)>,
+}
+
+impl<'a, K, V, Access> Iterator for TryIter<'a, K, V, Access>
+where
+    K: FromVariant,
+    V: FromVariant,
+    Access: ThreadAccess,
+{
+    type Item = Result<(K, V), FromVariantError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(key, value)| {
+            let typed_key = K::from_variant(&key).map_err(|err| FromVariantError::InvalidKey {
+                key: key.to_string(),
+                error: Box::new(err),
+            })?;
+            let typed_value =
+                V::from_variant(&value).map_err(|err| FromVariantError::InvalidEntry {
+                    key: key.to_string(),
+                    error: Box::new(err),
+                })?;
+            Ok((typed_key, typed_value))
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V, Access> IntoIterator for &'a TypedDictionary<K, V, Access>
+where
+    K: FromVariant,
+    V: FromVariant,
+    Access: ThreadAccess,
+{
+    type Item = (K, V);
+    type IntoIter = Iter<'a, K, V, Access>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for TypedDictionary<K, V, Unique>
+where
+    K: OwnedToVariant + ToVariantEq,
+    V: OwnedToVariant,
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let dict = Self::new();
+        for (key, value) in iter {
+            dict.insert(key, value);
+        }
+        dict
+    }
+}
+
+godot_test!(test_typed_dictionary {
+    let dict = TypedDictionary::<String, i64, Unique>::new();
+    assert!(dict.is_empty());
+
+    dict.insert("foo".to_string(), 1);
+    dict.insert("bar".to_string(), 2);
+    assert_eq!(2, dict.len());
+    assert!(dict.contains(&"foo".to_string()));
+    assert_eq!(Some(2), dict.get(&"bar".to_string()));
+    assert_eq!(None, dict.get(&"nope".to_string()));
+
+    let mut entries = dict.iter().collect::<Vec<_>>();
+    entries.sort();
+    assert_eq!(vec![("bar".to_string(), 2), ("foo".to_string(), 1)], entries);
+
+    let invalid = dict.duplicate();
+    invalid.as_untyped().insert("baz", "nope");
+    let errors = invalid
+        .try_iter()
+        .filter_map(|entry| entry.err())
+        .collect::<Vec<_>>();
+    match errors.as_slice() {
+        [FromVariantError::InvalidEntry { key, .. }] => assert_eq!("baz", key),
+        other => panic!("unexpected errors: {:?}", other),
+    }
+
+    let variant = dict.into_shared().to_variant();
+    let back = TypedDictionary::<String, i64>::from_variant(&variant).unwrap();
+    assert_eq!(Some(1), back.get(&"foo".to_string()));
+
+    let mixed = Dictionary::new();
+    mixed.insert("foo", 1);
+    mixed.insert("bar", "nope");
+    match TypedDictionary::<String, i64, Unique>::try_from(mixed) {
+        Err(FromVariantError::InvalidEntry { key, .. }) => assert_eq!("bar", key),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    assert!(TypedDictionary::<String, String>::from_variant(&variant).is_err());
+});
@@ -0,0 +1,417 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::{Enumerate, FromIterator};
+use std::marker::PhantomData;
+
+use gdnative_impl_proc_macros::doc_variant_collection_safety;
+
+use crate::core_types::variant_array;
+use crate::core_types::FromVariant;
+use crate::core_types::FromVariantError;
+use crate::core_types::OwnedToVariant;
+use crate::core_types::ToVariant;
+use crate::core_types::Variant;
+use crate::core_types::VariantArray;
+use crate::NewRef;
+
+use crate::thread_access::*;
+
+/// A `VariantArray` whose elements are known to be convertible to `T`.
+///
+/// This is a zero-cost wrapper around [`VariantArray`](../struct.VariantArray.html) with the
+/// same memory layout and the same *typestate* `Access`. The elements are validated once, when
+/// the wrapper is created through `TryFrom` or `FromVariant`. Typed accessors then convert
+/// elements to `T` on the fly.
+///
+/// Since the underlying array is reference-counted, elements can still be replaced through
+/// other references, e.g. from GDScript. Accessors that return `T` panic if an element is no
+/// longer convertible. Use `try_get` and `try_iter` where this needs to be handled.
+///
+/// When exported as a property, the element type is reported to the editor using an
+/// [`ArrayHint`](../../nativescript/init/property/hint/struct.ArrayHint.html).
+#[repr(transparent)]
+pub struct TypedVariantArray<T, Access: ThreadAccess = Shared> {
+    array: VariantArray<Access>,
+
+    /// Marker for the element type. Thread safety is determined by `Access` alone.
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// Operations allowed on all typed arrays at any point in time.
+impl<T, Access: ThreadAccess> TypedVariantArray<T, Access> {
+    /// Wraps an array without validating its elements.
+    #[inline]
+    fn from_untyped_unchecked(array: VariantArray<Access>) -> Self {
+        TypedVariantArray {
+            array,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns `true` if the array contains no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.array.is_empty()
+    }
+
+    /// Returns the number of elements in the array.
+    #[inline]
+    pub fn len(&self) -> i32 {
+        self.array.len()
+    }
+
+    /// Returns a reference to the underlying `VariantArray`.
+    #[inline]
+    pub fn as_untyped(&self) -> &VariantArray<Access> {
+        &self.array
+    }
+
+    /// Unwraps the underlying `VariantArray`.
+    #[inline]
+    pub fn into_untyped(self) -> VariantArray<Access> {
+        self.array
+    }
+
+    /// Create a copy of the array.
+    ///
+    /// This creates a new array and is **not** a cheap reference count
+    /// increment.
+    #[inline]
+    pub fn duplicate(&self) -> TypedVariantArray<T, Unique> {
+        TypedVariantArray::from_untyped_unchecked(self.array.duplicate())
+    }
+}
+
+impl<T: FromVariant, Access: ThreadAccess> TypedVariantArray<T, Access> {
+    /// Returns a copy of the element at the given offset, converted to `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the element at `idx` can't be converted to `T`, which is the case when `idx`
+    /// is out of bounds, or when the element has been replaced through another reference.
+    #[inline]
+    pub fn get(&self, idx: i32) -> T {
+        match self.try_get(idx) {
+            Ok(value) => value,
+            Err(err) => panic!("element {} of TypedVariantArray is invalid: {}", idx, err),
+        }
+    }
+
+    /// Returns a copy of the element at the given offset, or an error if it can't be
+    /// converted to `T`.
+    #[inline]
+    pub fn try_get(&self, idx: i32) -> Result<T, FromVariantError> {
+        T::from_variant(&self.array.get(idx))
+    }
+
+    /// Returns an iterator over the elements of the array, converted to `T`.
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if it encounters an element that can't be converted to `T`. Use
+    /// `try_iter` where this needs to be handled.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T, Access> {
+        Iter {
+            iter: self.try_iter(),
+        }
+    }
+
+    /// Returns an iterator over the elements of the array, converted to `T`, or an error if
+    /// they can't be converted.
+    ///
+    /// Elements that can't be converted are reported as `InvalidItem`, with their indices.
+    #[inline]
+    pub fn try_iter(&self) -> TryIter<'_, T, Access> {
+        TryIter {
+            iter: self.array.iter().enumerate(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: OwnedToVariant, Access: ThreadAccess> TypedVariantArray<T, Access> {
+    /// Sets the value of the element at the given offset.
+    #[inline]
+    pub fn set(&self, idx: i32, val: T) {
+        self.array.set(idx, val)
+    }
+}
+
+/// Operations allowed on typed arrays that can only be referenced to from the current thread.
+impl<T: OwnedToVariant, Access: LocalThreadAccess> TypedVariantArray<T, Access> {
+    /// Appends an element at the end of the array.
+    #[inline]
+    pub fn push(&self, val: T) {
+        self.array.push(val)
+    }
+
+    /// Insert a new element at a given position in the array.
+    #[inline]
+    pub fn insert(&self, at: i32, val: T) {
+        self.array.insert(at, val)
+    }
+}
+
+impl<T, Access: LocalThreadAccess> TypedVariantArray<T, Access> {
+    /// Clear the array.
+    #[inline]
+    pub fn clear(&self) {
+        self.array.clear()
+    }
+
+    /// Remove the element at the given offset.
+    #[inline]
+    pub fn remove(&self, idx: i32) {
+        self.array.remove(idx)
+    }
+}
+
+/// Operations allowed on typed arrays that might be shared between different threads.
+impl<T: OwnedToVariant> TypedVariantArray<T, Shared> {
+    /// Appends an element at the end of the array.
+    ///
+    #[doc_variant_collection_safety]
+    #[inline]
+    pub unsafe fn push(&self, val: T) {
+        self.array.push(val)
+    }
+
+    /// Insert a new element at a given position in the array.
+    ///
+    #[doc_variant_collection_safety]
+    #[inline]
+    pub unsafe fn insert(&self, at: i32, val: T) {
+        self.array.insert(at, val)
+    }
+}
+
+/// Operations allowed on typed arrays that are not unique.
+impl<T, Access: NonUniqueThreadAccess> TypedVariantArray<T, Access> {
+    /// Assume that this is the only reference to this array, on which
+    /// operations that change the container size can be safely performed.
+    ///
+    /// # Safety
+    ///
+    /// It isn't thread-safe to perform operations that change the container
+    /// size from multiple threads at the same time.
+    /// Creating multiple `Unique` references to the same collections, or
+    /// violating the thread-safety guidelines in non-Rust code will cause
+    /// undefined behavior.
+    #[inline]
+    pub unsafe fn assume_unique(self) -> TypedVariantArray<T, Unique> {
+        TypedVariantArray::from_untyped_unchecked(self.array.assume_unique())
+    }
+}
+
+/// Operations allowed on unique typed arrays.
+impl<T> TypedVariantArray<T, Unique> {
+    /// Creates an empty typed array.
+    #[inline]
+    pub fn new() -> Self {
+        Self::from_untyped_unchecked(VariantArray::new())
+    }
+
+    /// Put this array under the "shared" access type.
+    #[inline]
+    pub fn into_shared(self) -> TypedVariantArray<T, Shared> {
+        TypedVariantArray::from_untyped_unchecked(self.array.into_shared())
+    }
+
+    /// Put this array under the "thread-local" access type.
+    #[inline]
+    pub fn into_thread_local(self) -> TypedVariantArray<T, ThreadLocal> {
+        TypedVariantArray::from_untyped_unchecked(self.array.into_thread_local())
+    }
+}
+
+/// Checks that all elements of the array can be converted to `T`.
+///
+/// Elements that can't be converted are reported as `InvalidItem`, with the index of the
+/// first invalid element.
+impl<T: FromVariant, Access: ThreadAccess> TryFrom<VariantArray<Access>>
+    for TypedVariantArray<T, Access>
+{
+    type Error = FromVariantError;
+
+    #[inline]
+    fn try_from(array: VariantArray<Access>) -> Result<Self, Self::Error> {
+        for (index, item) in array.iter().enumerate() {
+            T::from_variant(&item).map_err(|err| FromVariantError::InvalidItem {
+                index,
+                error: Box::new(err),
+            })?;
+        }
+
+        Ok(Self::from_untyped_unchecked(array))
+    }
+}
+
+impl<T> Default for TypedVariantArray<T, Unique> {
+    #[inline]
+    fn default() -> Self {
+        TypedVariantArray::new()
+    }
+}
+
+impl<T, Access: NonUniqueThreadAccess> NewRef for TypedVariantArray<T, Access> {
+    /// Creates a new reference to the same array.
+    #[inline]
+    fn new_ref(&self) -> Self {
+        Self::from_untyped_unchecked(self.array.new_ref())
+    }
+}
+
+impl<T> From<TypedVariantArray<T, Unique>> for TypedVariantArray<T, Shared> {
+    #[inline]
+    fn from(array: TypedVariantArray<T, Unique>) -> Self {
+        array.into_shared()
+    }
+}
+
+impl<T, Access: ThreadAccess> From<TypedVariantArray<T, Access>> for VariantArray<Access> {
+    #[inline]
+    fn from(array: TypedVariantArray<T, Access>) -> Self {
+        array.into_untyped()
+    }
+}
+
+impl<T, Access: ThreadAccess> fmt::Debug for TypedVariantArray<T, Access> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.array.fmt(f)
+    }
+}
+
+impl<T> ToVariant for TypedVariantArray<T, Shared> {
+    #[inline]
+    fn to_variant(&self) -> Variant {
+        self.array.to_variant()
+    }
+}
+
+impl<T> OwnedToVariant for TypedVariantArray<T, Unique> {
+    #[inline]
+    fn owned_to_variant(self) -> Variant {
+        self.into_shared().to_variant()
+    }
+}
+
+impl<T: FromVariant> FromVariant for TypedVariantArray<T, Shared> {
+    #[inline]
+    fn from_variant(variant: &Variant) -> Result<Self, FromVariantError> {
+        VariantArray::from_variant(variant).and_then(Self::try_from)
+    }
+}
+
+/// Iterator through all elements of a typed array, converted to `T`.
+///
+/// This struct is created by the `iter` method on `TypedVariantArray`.
+pub struct Iter<'a, T, Access: ThreadAccess> {
+    iter: TryIter<'a, T, Access>,
+}
+
+impl<'a, T: FromVariant, Access: ThreadAccess> Iterator for Iter<'a, T, Access> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|item| match item {
+            Ok(value) => value,
+            Err(err) => panic!("element of TypedVariantArray is invalid: {}", err),
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+/// Iterator through all elements of a typed array, converted to `T`, or to an error if they
+/// can't be converted.
+///
+/// This struct is created by the `try_iter` method on `TypedVariantArray`.
+pub struct TryIter<'a, T, Access: ThreadAccess> {
+    iter: Enumerate<variant_array::Iter<'a, Access>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: FromVariant, Access: ThreadAccess> Iterator for TryIter<'a, T, Access> {
+    type Item = Result<T, FromVariantError>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(index, item)| {
+            T::from_variant(&item).map_err(|err| FromVariantError::InvalidItem {
+                index,
+                error: Box::new(err),
+            })
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T: FromVariant, Access: ThreadAccess> IntoIterator for &'a TypedVariantArray<T, Access> {
+    type Item = T;
+    type IntoIter = Iter<'a, T, Access>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: OwnedToVariant> FromIterator<T> for TypedVariantArray<T, Unique> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let array = Self::new();
+        for elem in iter {
+            array.push(elem);
+        }
+        array
+    }
+}
+
+godot_test!(test_typed_variant_array {
+    let array = TypedVariantArray::<i64, Unique>::new();
+    assert!(array.is_empty());
+
+    array.push(1);
+    array.push(3);
+    array.insert(1, 2);
+    assert_eq!(3, array.len());
+    assert_eq!(2, array.get(1));
+    assert_eq!(vec![1, 2, 3], array.iter().collect::<Vec<_>>());
+
+    let shared = array.into_shared();
+    let variant = shared.to_variant();
+    let back = TypedVariantArray::<i64>::from_variant(&variant).unwrap();
+    assert_eq!(vec![1, 2, 3], back.iter().collect::<Vec<_>>());
+
+    let strings: TypedVariantArray<String, Unique> =
+        vec!["foo".to_string(), "bar".to_string()].into_iter().collect();
+    assert_eq!("bar", strings.get(1));
+    assert!(strings.try_get(5).is_err());
+
+    strings.as_untyped().push(42);
+    assert_eq!(3, strings.try_iter().count());
+    assert!(strings.try_iter().take(2).all(|item| item.is_ok()));
+    match strings.try_iter().nth(2) {
+        Some(Err(FromVariantError::InvalidItem { index, .. })) => assert_eq!(2, index),
+        other => panic!("unexpected item: {:?}", other),
+    }
+
+    let mixed = VariantArray::new();
+    mixed.push(42);
+    mixed.push("nope");
+    match TypedVariantArray::<i64, Unique>::try_from(mixed) {
+        Err(FromVariantError::InvalidItem { index, .. }) => assert_eq!(1, index),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    assert!(TypedVariantArray::<String>::from_variant(&variant).is_err());
+});
@@ -1,8 +1,11 @@
 use crate::*;
+use std::borrow::Cow;
 use std::default::Default;
 use std::fmt;
 use std::mem::{forget, transmute};
 use std::ptr;
+use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::core_types::*;
 use crate::object::*;
@@ -103,12 +106,23 @@ macro_rules! decl_variant_type {
     ) => {
         #[repr(u32)]
         #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub enum VariantType {
             $(
                 $variant = $c_const as u32,
             )*
         }
 
+        impl VariantType {
+            /// Names of all variant types, indexed by their values.
+            #[cfg(feature = "serde")]
+            pub(crate) const NAMES: &'static [&'static str] = &[
+                $(
+                    stringify!($variant),
+                )*
+            ];
+        }
+
         /// Rust enum associating each primitive variant type to its value.
         ///
         /// For `Variant`s containing objects, the original `Variant` is returned unchanged, due to
@@ -977,6 +991,44 @@ godot_test!(
 ///
 /// Behavior of the derive macros can be customized using attributes:
 ///
+/// ### Container attributes
+///
+/// - `#[variant(repr = "int")]`
+///
+/// Represent a fieldless enum as the integer value of its discriminant. This is the
+/// representation expected by `#[derive(Export)]`, which shows the enum as a drop-down
+/// in the inspector.
+///
+/// - `#[variant(tag = "type")]`
+///
+/// Represent the enum as an internally tagged `Dictionary`, with the variant name stored
+/// under the given key along with the fields (`{ "type": "Variant", "a": a, "b": b }`).
+/// Only valid for enums whose variants are all units or structs.
+///
+/// - `#[variant(tag = "t", content = "c")]`
+///
+/// Represent the enum as an adjacently tagged `Dictionary`, with the variant name and the
+/// payload stored under separate keys (`{ "t": "Variant", "c": [a, b, c] }`). The content
+/// key is omitted for unit variants.
+///
+/// - `#[variant(untagged)]`
+///
+/// Represent the enum as the payload alone, without any indication of the variant. Unit
+/// variants are represented as `Nil`. When converting from a `Variant`, each variant is
+/// tried in order, and the first one that succeeds is returned.
+///
+/// - `#[variant(rename_all = "camelCase")]`
+///
+/// Rename all fields of a struct, or all variants of an enum, according to the given case
+/// convention. Accepted values are `lowercase`, `UPPERCASE`, `PascalCase`, `camelCase`,
+/// `snake_case`, `SCREAMING_SNAKE_CASE`, `kebab-case` and `SCREAMING-KEBAB-CASE`.
+///
+/// - `#[variant(deny_unknown_fields)]`
+///
+/// Return `FromVariantError::UnknownField` when converting from a `Dictionary` containing keys
+/// that are not fields of the struct, instead of ignoring them. Cannot be used together with
+/// `#[variant(flatten)]`.
+///
 /// ### Field attributes
 ///
 /// - `#[variant(to_variant_with = "path::to::func")]`
@@ -1007,6 +1059,29 @@ godot_test!(
 /// - `#[variant(skip)]`
 ///
 /// Convenience attribute that sets `skip_to_variant` and `skip_from_variant`.
+///
+/// - `#[variant(default)]`
+///
+/// Use `Default::default()` when the field's key is missing from the `Dictionary`. Also
+/// applies to fields skipped with `skip_from_variant`.
+///
+/// - `#[variant(default = "path::to::func")]`
+///
+/// Same as `#[variant(default)]`, but obtains the value by calling the given function, with
+/// the signature `fn() -> T`.
+///
+/// - `#[variant(rename = "name")]`
+///
+/// Use the given name as the field's key instead of its Rust name.
+///
+/// - `#[variant(flatten)]`
+///
+/// Merge the keys of the field, which must be represented as a `Dictionary`, into the
+/// `Dictionary` of the containing struct. When converting from a `Variant`, the field is
+/// converted from the entire containing `Dictionary`. Types that never convert to a
+/// `Dictionary`, like numbers, strings and arrays, are rejected at compile time. Other values
+/// that turn out not to be a `Dictionary`, like a `Variant` holding a number, are inserted
+/// under the field's name instead.
 pub trait ToVariant {
     fn to_variant(&self) -> Variant;
 }
@@ -1066,6 +1141,13 @@ pub trait ToVariantEq: Eq {}
 /// manually handle potentially heterogeneous values e.g. for error reporting, use `VariantArray`
 /// directly or compose with an appropriate wrapper: `Vec<Option<T>>` or `Vec<MaybeNot<T>>`.
 ///
+/// ## Maps and sets
+///
+/// `HashMap`, `BTreeMap` and `IndexMap` are converted from `Dictionary`s. A key that can't
+/// be converted is reported as `InvalidKey`, and a value that can't be converted as
+/// `InvalidEntry`, both with the key of the entry. `HashSet`, `BTreeSet` and `IndexSet` are
+/// converted from `VariantArray`s, in the same way as `Vec<T>`.
+///
 /// ## Deriving `FromVariant`
 ///
 /// The derive macro provides implementation consistent with derived `ToVariant`. See `ToVariant`
@@ -1097,6 +1179,16 @@ pub enum FromVariantError {
         expected: VariantEnumRepr,
         error: Box<FromVariantError>,
     },
+    /// The tag of an internally or adjacently tagged enum is missing.
+    MissingEnumTag { tag: &'static str },
+    /// The content of an adjacently tagged enum is missing.
+    MissingEnumContent { content: &'static str },
+    /// None of the variants of an untagged enum could be converted from the value.
+    ///
+    /// `errors` contains the name of each variant, along with the error it failed with.
+    NoMatchingEnumVariant {
+        errors: Vec<(&'static str, FromVariantError)>,
+    },
     /// Invalid struct representation.
     InvalidStructRepr {
         expected: VariantStructRepr,
@@ -1130,6 +1222,14 @@ pub enum FromVariantError {
 
     /// Given object is not an instance of the expected NativeClass.
     InvalidInstance { expected: &'static str },
+    /// Dictionary contains a key that is not a field of the struct. Only returned by
+    /// implementations derived with `#[variant(deny_unknown_fields)]`.
+    UnknownField {
+        /// The unknown key
+        field_name: String,
+        /// Names of all expected fields known at compile time
+        expected: &'static [&'static str],
+    },
     /// Collection contains an invalid field.
     InvalidField {
         field_name: &'static str,
@@ -1140,11 +1240,27 @@ pub enum FromVariantError {
         index: usize,
         error: Box<FromVariantError>,
     },
+    /// Map contains a key that can't be converted.
+    InvalidKey {
+        /// The invalid key, converted to a string
+        key: String,
+        error: Box<FromVariantError>,
+    },
+    /// Map contains an invalid value.
+    InvalidEntry {
+        /// Key of the invalid value, converted to a string
+        key: String,
+        error: Box<FromVariantError>,
+    },
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum VariantEnumRepr {
     ExternallyTagged,
+    InternallyTagged,
+    AdjacentlyTagged,
+    Untagged,
+    Int,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
@@ -1190,6 +1306,15 @@ impl fmt::Display for FromVariantError {
                 "invalid enum representation: expected {:?}, {}",
                 expected, error
             ),
+            E::MissingEnumTag { tag } => write!(f, "missing enum tag {}", tag),
+            E::MissingEnumContent { content } => write!(f, "missing enum content {}", content),
+            E::NoMatchingEnumVariant { errors } => {
+                write!(f, "value does not match any enum variant")?;
+                for (variant, error) in errors {
+                    write!(f, "; {}: {}", variant, error)?;
+                }
+                Ok(())
+            }
             E::InvalidStructRepr { expected, error } => write!(
                 f,
                 "invalid struct representation: expected {:?}, {}",
@@ -1218,30 +1343,61 @@ impl fmt::Display for FromVariantError {
             E::InvalidInstance { expected } => {
                 write!(f, "object is not an instance of `NativeClass` {}", expected)
             }
-            E::InvalidField { field_name, error } => {
-                write!(f, "invalid value for field {}", field_name)?;
-
-                let mut next_error = error.as_ref();
-                loop {
-                    match next_error {
-                        E::InvalidField { field_name, error } => {
-                            write!(f, ".{}", field_name)?;
-                            next_error = error.as_ref();
-                        }
-                        E::InvalidItem { index, error } => {
-                            write!(f, "[{}]", index)?;
-                            next_error = error.as_ref();
-                        }
-                        _ => {
-                            write!(f, ": {}", next_error)?;
-                            return Ok(());
-                        }
+            E::UnknownField {
+                field_name,
+                expected,
+            } => {
+                write!(f, "unknown field {}, expected fields are: ", field_name)?;
+                let mut first = true;
+                for v in *expected {
+                    if first {
+                        first = false;
+                    } else {
+                        write!(f, ", ")?;
                     }
+                    write!(f, "{}", v)?;
                 }
+                Ok(())
+            }
+            E::InvalidField { field_name, error } => {
+                write!(f, "invalid value for field {}", field_name)?;
+                write_error_path(f, error)
             }
             E::InvalidItem { index, error } => {
                 write!(f, "invalid value for item at index {}: {}", index, error)
             }
+            E::InvalidKey { key, error } => write!(f, "invalid key {}: {}", key, error),
+            E::InvalidEntry { key, error } => {
+                write!(f, "invalid value for key {}", key)?;
+                write_error_path(f, error)
+            }
+        }
+    }
+}
+
+/// Writes the path to the value that caused `error` in nested collections, followed by the
+/// innermost error.
+fn write_error_path(f: &mut fmt::Formatter, mut error: &FromVariantError) -> fmt::Result {
+    use FromVariantError as E;
+
+    loop {
+        match error {
+            E::InvalidField {
+                field_name,
+                error: next,
+            } => {
+                write!(f, ".{}", field_name)?;
+                error = next.as_ref();
+            }
+            E::InvalidItem { index, error: next } => {
+                write!(f, "[{}]", index)?;
+                error = next.as_ref();
+            }
+            E::InvalidEntry { key, error: next } => {
+                write!(f, "[{:?}]", key)?;
+                error = next.as_ref();
+            }
+            _ => return write!(f, ": {}", error),
         }
     }
 }
@@ -1881,6 +2037,321 @@ macro_rules! impl_variant_for_tuples {
 
 impl_variant_for_tuples!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12,);
 
+macro_rules! replace_expr {
+    ($_t:tt $sub:expr) => {
+        $sub
+    };
+}
+
+macro_rules! impl_variant_for_arrays_next {
+    ($_x:ident, $($xs:ident,)*) => {
+        impl_variant_for_arrays!($($xs,)*);
+    }
+}
+
+// Implemented for each length up to 32, since const generics aren't available with the
+// minimum supported Rust version.
+macro_rules! impl_variant_for_arrays {
+    () => {};
+    ( $($name:ident,)+ ) => {
+        impl<T: ToVariant> ToVariant for [T; tuple_length!($($name,)+)] {
+            #[inline]
+            fn to_variant(&self) -> Variant {
+                (&self[..]).to_variant()
+            }
+        }
+
+        impl<T: FromVariant> FromVariant for [T; tuple_length!($($name,)+)] {
+            #[inline]
+            fn from_variant(v: &Variant) -> Result<Self, FromVariantError> {
+                let array = VariantArray::from_variant(v)?;
+                let expected = tuple_length!($($name,)+);
+                let len = array.len() as usize;
+                if len != expected {
+                    return Err(FromVariantError::InvalidLength { expected, len });
+                }
+
+                let mut items = array.iter().enumerate().map(|(index, item)| {
+                    T::from_variant(&item).map_err(|err| FromVariantError::InvalidItem {
+                        index,
+                        error: Box::new(err),
+                    })
+                });
+
+                Ok([$( replace_expr!($name items.next().unwrap()?), )+])
+            }
+        }
+
+        impl_variant_for_arrays_next!($($name,)+);
+    };
+}
+
+impl_variant_for_arrays!(
+    T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T, T,
+);
+
+impl<T: ToVariant> ToVariant for [T; 0] {
+    #[inline]
+    fn to_variant(&self) -> Variant {
+        (&self[..]).to_variant()
+    }
+}
+
+impl<T: FromVariant> FromVariant for [T; 0] {
+    #[inline]
+    fn from_variant(v: &Variant) -> Result<Self, FromVariantError> {
+        let array = VariantArray::from_variant(v)?;
+        let len = array.len() as usize;
+        if len != 0 {
+            return Err(FromVariantError::InvalidLength { expected: 0, len });
+        }
+
+        Ok([])
+    }
+}
+
+fn items_to_variant<'a, T: ToVariant + 'a>(items: impl Iterator<Item = &'a T>) -> Variant {
+    let array = VariantArray::new();
+    for item in items {
+        array.push(item.to_variant());
+    }
+    array.into_shared().to_variant()
+}
+
+fn items_from_variant<T: FromVariant, C: std::iter::FromIterator<T>>(
+    variant: &Variant,
+) -> Result<C, FromVariantError> {
+    let array = VariantArray::from_variant(variant)?;
+    array
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            T::from_variant(&item).map_err(|err| FromVariantError::InvalidItem {
+                index,
+                error: Box::new(err),
+            })
+        })
+        .collect()
+}
+
+fn entries_to_variant<'a, K, V>(entries: impl Iterator<Item = (&'a K, &'a V)>) -> Variant
+where
+    K: ToVariant + ToVariantEq + 'a,
+    V: ToVariant + 'a,
+{
+    let dict = Dictionary::new();
+    for (key, value) in entries {
+        dict.insert(key, value.to_variant());
+    }
+    dict.into_shared().to_variant()
+}
+
+fn entries_from_variant<K, V, C>(variant: &Variant) -> Result<C, FromVariantError>
+where
+    K: FromVariant,
+    V: FromVariant,
+    C: std::iter::FromIterator<(K, V)>,
+{
+    let dict = Dictionary::from_variant(variant)?;
+    dict.iter()
+        .map(|(key, value)| {
+            let k = K::from_variant(&key).map_err(|err| FromVariantError::InvalidKey {
+                key: key.to_string(),
+                error: Box::new(err),
+            })?;
+            let v = V::from_variant(&value).map_err(|err| FromVariantError::InvalidEntry {
+                key: key.to_string(),
+                error: Box::new(err),
+            })?;
+            Ok((k, v))
+        })
+        .collect()
+}
+
+impl<K, V, S> ToVariant for std::collections::HashMap<K, V, S>
+where
+    K: ToVariant + ToVariantEq,
+    V: ToVariant,
+{
+    #[inline]
+    fn to_variant(&self) -> Variant {
+        entries_to_variant(self.iter())
+    }
+}
+
+impl<K, V, S> FromVariant for std::collections::HashMap<K, V, S>
+where
+    K: FromVariant + Eq + std::hash::Hash,
+    V: FromVariant,
+    S: std::hash::BuildHasher + Default,
+{
+    #[inline]
+    fn from_variant(variant: &Variant) -> Result<Self, FromVariantError> {
+        entries_from_variant(variant)
+    }
+}
+
+impl<K, V> ToVariant for std::collections::BTreeMap<K, V>
+where
+    K: ToVariant + ToVariantEq,
+    V: ToVariant,
+{
+    #[inline]
+    fn to_variant(&self) -> Variant {
+        entries_to_variant(self.iter())
+    }
+}
+
+impl<K, V> FromVariant for std::collections::BTreeMap<K, V>
+where
+    K: FromVariant + Ord,
+    V: FromVariant,
+{
+    #[inline]
+    fn from_variant(variant: &Variant) -> Result<Self, FromVariantError> {
+        entries_from_variant(variant)
+    }
+}
+
+impl<K, V, S> ToVariant for indexmap::IndexMap<K, V, S>
+where
+    K: ToVariant + ToVariantEq,
+    V: ToVariant,
+{
+    #[inline]
+    fn to_variant(&self) -> Variant {
+        entries_to_variant(self.iter())
+    }
+}
+
+impl<K, V, S> FromVariant for indexmap::IndexMap<K, V, S>
+where
+    K: FromVariant + Eq + std::hash::Hash,
+    V: FromVariant,
+    S: std::hash::BuildHasher + Default,
+{
+    #[inline]
+    fn from_variant(variant: &Variant) -> Result<Self, FromVariantError> {
+        entries_from_variant(variant)
+    }
+}
+
+impl<T: ToVariant, S> ToVariant for std::collections::HashSet<T, S> {
+    #[inline]
+    fn to_variant(&self) -> Variant {
+        items_to_variant(self.iter())
+    }
+}
+
+impl<T, S> FromVariant for std::collections::HashSet<T, S>
+where
+    T: FromVariant + Eq + std::hash::Hash,
+    S: std::hash::BuildHasher + Default,
+{
+    #[inline]
+    fn from_variant(variant: &Variant) -> Result<Self, FromVariantError> {
+        items_from_variant(variant)
+    }
+}
+
+impl<T: ToVariant> ToVariant for std::collections::BTreeSet<T> {
+    #[inline]
+    fn to_variant(&self) -> Variant {
+        items_to_variant(self.iter())
+    }
+}
+
+impl<T: FromVariant + Ord> FromVariant for std::collections::BTreeSet<T> {
+    #[inline]
+    fn from_variant(variant: &Variant) -> Result<Self, FromVariantError> {
+        items_from_variant(variant)
+    }
+}
+
+impl<T: ToVariant, S> ToVariant for indexmap::IndexSet<T, S> {
+    #[inline]
+    fn to_variant(&self) -> Variant {
+        items_to_variant(self.iter())
+    }
+}
+
+impl<T, S> FromVariant for indexmap::IndexSet<T, S>
+where
+    T: FromVariant + Eq + std::hash::Hash,
+    S: std::hash::BuildHasher + Default,
+{
+    #[inline]
+    fn from_variant(variant: &Variant) -> Result<Self, FromVariantError> {
+        items_from_variant(variant)
+    }
+}
+
+macro_rules! impl_variant_for_pointers {
+    ( $($ptr:ident),* ) => {
+        $(
+            impl<T: ToVariant + ?Sized> ToVariant for $ptr<T> {
+                #[inline]
+                fn to_variant(&self) -> Variant {
+                    T::to_variant(self)
+                }
+            }
+            impl<T: ToVariantEq + ?Sized> ToVariantEq for $ptr<T> {}
+
+            impl<T: FromVariant> FromVariant for $ptr<T> {
+                #[inline]
+                fn from_variant(variant: &Variant) -> Result<Self, FromVariantError> {
+                    T::from_variant(variant).map($ptr::new)
+                }
+            }
+        )*
+    };
+}
+
+impl_variant_for_pointers!(Box, Rc, Arc);
+
+impl<'a, T: ToVariant + ToOwned + ?Sized> ToVariant for Cow<'a, T> {
+    #[inline]
+    fn to_variant(&self) -> Variant {
+        T::to_variant(self)
+    }
+}
+impl<'a, T: ToVariantEq + ToOwned + ?Sized> ToVariantEq for Cow<'a, T> {}
+
+impl<'a, T> FromVariant for Cow<'a, T>
+where
+    T: ToOwned + ?Sized,
+    T::Owned: FromVariant,
+{
+    #[inline]
+    fn from_variant(variant: &Variant) -> Result<Self, FromVariantError> {
+        T::Owned::from_variant(variant).map(Cow::Owned)
+    }
+}
+
+impl ToVariant for char {
+    #[inline]
+    fn to_variant(&self) -> Variant {
+        let mut buf = [0; 4];
+        Variant::from_str(self.encode_utf8(&mut buf))
+    }
+}
+impl ToVariantEq for char {}
+
+impl FromVariant for char {
+    #[inline]
+    fn from_variant(variant: &Variant) -> Result<Self, FromVariantError> {
+        let s = String::from_variant(variant)?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => Err(FromVariantError::InvalidLength {
+                len: s.chars().count(),
+                expected: 1,
+            }),
+        }
+    }
+}
+
 godot_test!(
     test_variant_option {
         use std::marker::PhantomData;
@@ -1998,6 +2469,98 @@ godot_test!(
         assert_eq!(Ok((42, 54)), tuple);
     }
 
+    test_variant_collections {
+        use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+        let mut map = HashMap::new();
+        map.insert("foo".to_string(), 42_i64);
+        map.insert("bar".to_string(), 54_i64);
+
+        let variant = map.to_variant();
+        let dict = variant.try_to_dictionary().expect("should be dict");
+        assert_eq!(2, dict.len());
+        assert_eq!(Some(42), dict.get("foo").and_then(|v| v.try_to_i64()));
+
+        assert_eq!(Ok(map.clone()), HashMap::from_variant(&variant));
+        assert_eq!(
+            Ok(map.clone().into_iter().collect::<BTreeMap<_, _>>()),
+            BTreeMap::from_variant(&variant),
+        );
+        assert_eq!(
+            Ok(map.clone().into_iter().collect::<indexmap::IndexMap<_, _>>()),
+            indexmap::IndexMap::from_variant(&variant),
+        );
+
+        let dict = Dictionary::new();
+        dict.insert("foo", 42);
+        dict.insert(54, 54);
+        let variant = dict.into_shared().to_variant();
+        assert_eq!(
+            Err(FromVariantError::InvalidKey {
+                key: "54".into(),
+                error: Box::new(FromVariantError::InvalidVariantType {
+                    expected: VariantType::GodotString,
+                    variant_type: VariantType::I64,
+                }),
+            }),
+            HashMap::<String, i64>::from_variant(&variant),
+        );
+        assert_eq!(
+            Err(FromVariantError::InvalidEntry {
+                key: "foo".into(),
+                error: Box::new(FromVariantError::InvalidVariantType {
+                    expected: VariantType::Bool,
+                    variant_type: VariantType::I64,
+                }),
+            }),
+            HashMap::<String, bool>::from_variant(&variant),
+        );
+
+        let set: BTreeSet<i64> = vec![3, 1, 2].into_iter().collect();
+        let variant = set.to_variant();
+        let array = variant.try_to_array().expect("should be array");
+        assert_eq!(Some(1), array.get(0).try_to_i64());
+        assert_eq!(Ok(set.clone()), BTreeSet::from_variant(&variant));
+        assert_eq!(
+            Ok(set.iter().copied().collect::<HashSet<_>>()),
+            HashSet::from_variant(&variant),
+        );
+
+        let variant = [1_i64, 2, 3].to_variant();
+        assert_eq!(Ok([1, 2, 3]), <[i64; 3]>::from_variant(&variant));
+        assert_eq!(
+            Err(FromVariantError::InvalidLength { expected: 2, len: 3 }),
+            <[i64; 2]>::from_variant(&variant),
+        );
+    }
+
+    test_variant_wrappers {
+        use std::borrow::Cow;
+        use std::rc::Rc;
+        use std::sync::Arc;
+
+        assert_eq!(Some(42), Box::new(42_i64).to_variant().try_to_i64());
+        assert_eq!(Some(42), Rc::new(42_i64).to_variant().try_to_i64());
+        assert_eq!(Some(42), Arc::new(42_i64).to_variant().try_to_i64());
+        assert_eq!(Ok(Box::new(42)), Box::<i64>::from_variant(&42.to_variant()));
+        assert_eq!(Ok(Rc::new(42)), Rc::<i64>::from_variant(&42.to_variant()));
+        assert_eq!(Ok(Arc::new(42)), Arc::<i64>::from_variant(&42.to_variant()));
+
+        let boxed: Box<str> = "boxed".into();
+        assert_eq!(Some("boxed".into()), boxed.to_variant().try_to_string());
+
+        let cow = Cow::Borrowed("cow");
+        assert_eq!(Some("cow".into()), cow.to_variant().try_to_string());
+        assert_eq!(Ok(cow), Cow::<str>::from_variant(&"cow".to_variant()));
+
+        assert_eq!(Some("é".into()), 'é'.to_variant().try_to_string());
+        assert_eq!(Ok('é'), char::from_variant(&"é".to_variant()));
+        assert_eq!(
+            Err(FromVariantError::InvalidLength { expected: 1, len: 2 }),
+            char::from_variant(&"ab".to_variant()),
+        );
+    }
+
     test_variant_dispatch {
         let variant = 42i64.to_variant();
         if let VariantDispatch::I64(i) = variant.dispatch() {
@@ -0,0 +1,846 @@
+//! Conversion between `Variant` and any type implementing the `serde` traits.
+//!
+//! [`to_variant`] and [`from_variant`] convert values directly, without going through an
+//! intermediate format like JSON. Values are mapped to the closest Godot types:
+//!
+//! - Booleans, integers and floats become `bool`, `int` and `float` variants. Integers
+//!   outside of the range of `i64` can't be converted.
+//! - Strings and `char`s become `String`s, and byte slices become `PoolByteArray`s.
+//! - `None`, `()` and unit structs become `Nil`, and `Some` and newtype structs become their
+//!   contents.
+//! - Sequences and tuples become `Array`s, and maps and structs become `Dictionary`s.
+//! - Enums are externally tagged: unit variants become their names, and other variants become
+//!   a `Dictionary` with the name of the variant as the only key.
+//!
+//! `Variant`s and core types like `Vector2` are passed through as they are, instead of using
+//! their `Serialize` implementations, so `to_variant(&variant)` returns `variant` unchanged.
+//! Core types can also be read back from their `Serialize` representation, like a
+//! `Dictionary` with the keys `x` and `y` for a `Vector2`. Variants containing objects or
+//! `Rid`s can't be converted.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct SaveData {
+//!     level: String,
+//!     position: Vector2,
+//!     items: Vec<u32>,
+//! }
+//!
+//! let variant = variant_serde::to_variant(&save)?;
+//! assert_eq!(VariantType::Dictionary, variant.get_type());
+//!
+//! let save: SaveData = variant_serde::from_variant(&variant)?;
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt::{self, Display};
+
+use serde::de::value::{MapDeserializer, SeqDeserializer, StringDeserializer};
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use serde::ser::{self, Serialize};
+
+use super::*;
+use crate::sys;
+use crate::thread_access::Unique;
+
+/// Converts `value` to a `Variant`.
+#[inline]
+pub fn to_variant<T: Serialize + ?Sized>(value: &T) -> Result<Variant, Error> {
+    value.serialize(VariantSerializer)
+}
+
+/// Converts `variant` to a `T`.
+#[inline]
+pub fn from_variant<T: DeserializeOwned>(variant: &Variant) -> Result<T, Error> {
+    T::deserialize(VariantDeserializer::new(variant.clone()))
+}
+
+/// Error during conversion between `Variant` and other types.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Error reported by a `Serialize` or `Deserialize` implementation.
+    Custom(String),
+    /// Variants of this type can't be converted.
+    UnsupportedType(VariantType),
+    /// An integer is outside of the range of `i64`.
+    IntegerOutOfRange,
+}
+
+impl Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Custom(message) => f.write_str(message),
+            Error::UnsupportedType(ty) => write!(f, "variants of type {:?} are not supported", ty),
+            Error::IntegerOutOfRange => write!(f, "integer is outside of the range of i64"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    #[inline]
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    #[inline]
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Custom(msg.to_string())
+    }
+}
+
+/// `Serializer` producing `Variant`s.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct VariantSerializer;
+
+#[inline]
+fn tagged(name: &str, value: Variant) -> Variant {
+    let dict = Dictionary::new();
+    dict.insert(name, value);
+    dict.owned_to_variant()
+}
+
+/// Core types converted to variants of their own type, with the number of their fields.
+const NATIVE_STRUCTS: &[(&str, usize, VariantType)] = &[
+    ("Vector2", 2, VariantType::Vector2),
+    ("Rect2", 2, VariantType::Rect2),
+    ("Vector3", 3, VariantType::Vector3),
+    ("Transform2D", 3, VariantType::Transform2D),
+    ("Plane", 2, VariantType::Plane),
+    ("Quat", 4, VariantType::Quat),
+    ("Aabb", 2, VariantType::Aabb),
+    ("Basis", 1, VariantType::Basis),
+    ("Transform", 2, VariantType::Transform),
+    ("Color", 4, VariantType::Color),
+];
+
+/// Returns the type of a variant of the `Variant` enum, as serialized by its `Serialize`
+/// implementation.
+#[inline]
+fn variant_type(name: &str, variant_index: u32, variant: &str) -> Option<VariantType> {
+    if name != "Variant" || VariantType::NAMES.get(variant_index as usize) != Some(&variant) {
+        return None;
+    }
+
+    Some(VariantType::from_sys(
+        variant_index as sys::godot_variant_type,
+    ))
+}
+
+/// Converts `value` to a variant of type `ty`, if it isn't one already.
+fn to_native(ty: VariantType, value: Variant) -> Result<Variant, Error> {
+    if value.get_type() == ty {
+        return Ok(value);
+    }
+
+    macro_rules! native {
+        ($type:ty) => {
+            from_variant::<$type>(&value)?.to_variant()
+        };
+    }
+
+    let native = match ty {
+        VariantType::Vector2 => native!(Vector2),
+        VariantType::Rect2 => native!(Rect2),
+        VariantType::Vector3 => native!(Vector3),
+        VariantType::Transform2D => native!(Transform2D),
+        VariantType::Plane => native!(Plane),
+        VariantType::Quat => native!(Quat),
+        VariantType::Aabb => native!(Aabb),
+        VariantType::Basis => native!(Basis),
+        VariantType::Transform => native!(Transform),
+        VariantType::Color => native!(Color),
+        VariantType::NodePath => native!(NodePath),
+        VariantType::ByteArray => native!(ByteArray),
+        VariantType::Int32Array => native!(Int32Array),
+        VariantType::Float32Array => native!(Float32Array),
+        VariantType::StringArray => native!(StringArray),
+        VariantType::Vector2Array => native!(Vector2Array),
+        VariantType::Vector3Array => native!(Vector3Array),
+        VariantType::ColorArray => native!(ColorArray),
+        _ => {
+            return Err(Error::Custom(format!(
+                "expected a variant of type {:?}, found {:?}",
+                ty,
+                value.get_type(),
+            )))
+        }
+    };
+
+    Ok(native)
+}
+
+impl ser::Serializer for VariantSerializer {
+    type Ok = Variant;
+    type Error = Error;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeTagged<SerializeArray>;
+    type SerializeMap = SerializeDictionary;
+    type SerializeStruct = SerializeDictionary;
+    type SerializeStructVariant = SerializeTagged<SerializeDictionary>;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<Variant, Error> {
+        Ok(v.to_variant())
+    }
+
+    #[inline]
+    fn serialize_i8(self, v: i8) -> Result<Variant, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    #[inline]
+    fn serialize_i16(self, v: i16) -> Result<Variant, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    #[inline]
+    fn serialize_i32(self, v: i32) -> Result<Variant, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    #[inline]
+    fn serialize_i64(self, v: i64) -> Result<Variant, Error> {
+        Ok(v.to_variant())
+    }
+
+    #[inline]
+    fn serialize_u8(self, v: u8) -> Result<Variant, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> Result<Variant, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> Result<Variant, Error> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> Result<Variant, Error> {
+        let v = i64::try_from(v).map_err(|_| Error::IntegerOutOfRange)?;
+        self.serialize_i64(v)
+    }
+
+    #[inline]
+    fn serialize_f32(self, v: f32) -> Result<Variant, Error> {
+        self.serialize_f64(f64::from(v))
+    }
+
+    #[inline]
+    fn serialize_f64(self, v: f64) -> Result<Variant, Error> {
+        Ok(v.to_variant())
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<Variant, Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<Variant, Error> {
+        Ok(Variant::from_str(v))
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> Result<Variant, Error> {
+        Ok(ByteArray::from_slice(v).to_variant())
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Variant, Error> {
+        Ok(Variant::new())
+    }
+
+    #[inline]
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Variant, Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Variant, Error> {
+        Ok(Variant::new())
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Variant, Error> {
+        Ok(Variant::new())
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Variant, Error> {
+        match variant_type(name, variant_index, variant) {
+            Some(VariantType::Nil) => Ok(Variant::new()),
+            _ => self.serialize_str(variant),
+        }
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Variant, Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Variant, Error> {
+        // `Variant`s are passed through instead of being tagged.
+        match variant_type(name, variant_index, variant) {
+            Some(ty) => to_native(ty, value.serialize(self)?),
+            None => Ok(tagged(variant, value.serialize(self)?)),
+        }
+    }
+
+    #[inline]
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray {
+            array: VariantArray::new(),
+        })
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeTagged<SerializeArray>, Error> {
+        Ok(SerializeTagged {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    #[inline]
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeDictionary, Error> {
+        Ok(SerializeDictionary {
+            dict: Dictionary::new(),
+            key: None,
+            native: None,
+        })
+    }
+
+    #[inline]
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeDictionary, Error> {
+        let mut serializer = self.serialize_map(Some(len))?;
+        serializer.native = NATIVE_STRUCTS
+            .iter()
+            .find(|&&(native_name, _, _)| native_name == name)
+            .map(|&(_, fields, ty)| (fields, ty));
+        Ok(serializer)
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeTagged<SerializeDictionary>, Error> {
+        Ok(SerializeTagged {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+/// Serializer for sequences and tuples, producing a `VariantArray`.
+#[derive(Debug)]
+pub struct SerializeArray {
+    array: VariantArray<Unique>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Variant;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.array.push(value.serialize(VariantSerializer)?);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Variant, Error> {
+        Ok(self.array.owned_to_variant())
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Variant;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Variant, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Variant;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Variant, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Serializer for maps and structs, producing a `Dictionary`, or a variant of a core type for
+/// the structs of the core types.
+#[derive(Debug)]
+pub struct SerializeDictionary {
+    dict: Dictionary<Unique>,
+    key: Option<Variant>,
+    native: Option<(usize, VariantType)>,
+}
+
+impl ser::SerializeMap for SerializeDictionary {
+    type Ok = Variant;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(VariantSerializer)?);
+        Ok(())
+    }
+
+    #[inline]
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Custom("map value serialized before its key".into()))?;
+        self.dict.insert(key, value.serialize(VariantSerializer)?);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Variant, Error> {
+        Ok(self.dict.owned_to_variant())
+    }
+}
+
+impl ser::SerializeStruct for SerializeDictionary {
+    type Ok = Variant;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.dict.insert(key, value.serialize(VariantSerializer)?);
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Variant, Error> {
+        // Other structs with the same name as a core type are kept as dictionaries.
+        let native = match self.native {
+            Some((fields, ty)) if self.dict.len() == fields as i32 => Some(ty),
+            _ => None,
+        };
+
+        let dict = ser::SerializeMap::end(self)?;
+        match native {
+            Some(ty) => Ok(to_native(ty, dict.clone()).unwrap_or(dict)),
+            None => Ok(dict),
+        }
+    }
+}
+
+/// Serializer for tuple and struct enum variants, producing a `Dictionary` with the name of
+/// the variant as the only key.
+#[derive(Debug)]
+pub struct SerializeTagged<S> {
+    variant: &'static str,
+    inner: S,
+}
+
+impl ser::SerializeTupleVariant for SerializeTagged<SerializeArray> {
+    type Ok = Variant;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        ser::SerializeSeq::serialize_element(&mut self.inner, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Variant, Error> {
+        Ok(tagged(self.variant, ser::SerializeSeq::end(self.inner)?))
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeTagged<SerializeDictionary> {
+    type Ok = Variant;
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Variant, Error> {
+        Ok(tagged(self.variant, ser::SerializeMap::end(self.inner)?))
+    }
+}
+
+/// `Deserializer` reading from a `Variant`.
+#[derive(Debug)]
+pub struct VariantDeserializer {
+    variant: Variant,
+}
+
+impl VariantDeserializer {
+    /// Creates a deserializer reading from `variant`.
+    #[inline]
+    pub fn new(variant: Variant) -> Self {
+        VariantDeserializer { variant }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Variant {
+    type Deserializer = VariantDeserializer;
+
+    #[inline]
+    fn into_deserializer(self) -> VariantDeserializer {
+        VariantDeserializer::new(self)
+    }
+}
+
+fn visit_array<'de, V, I>(visitor: V, iter: I) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+    I: Iterator<Item = Variant>,
+{
+    let mut seq = SeqDeserializer::<_, Error>::new(iter);
+    let value = visitor.visit_seq(&mut seq)?;
+    seq.end()?;
+    Ok(value)
+}
+
+/// Visits the fields of a core type, so it can be read like any other struct.
+fn visit_fields<'de, V>(visitor: V, fields: Vec<(&'static str, Variant)>) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let mut map = MapDeserializer::<_, Error>::new(fields.into_iter());
+    let value = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(value)
+}
+
+fn visit_pool_array<'de, V, T>(visitor: V, array: &TypedArray<T>) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+    T: super::typed_array::Element + ToVariant,
+{
+    let elements = array
+        .read()
+        .iter()
+        .map(ToVariant::to_variant)
+        .collect::<Vec<_>>();
+
+    visit_array(visitor, elements.into_iter())
+}
+
+impl<'de> de::Deserializer<'de> for VariantDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.variant.dispatch() {
+            VariantDispatch::Nil => visitor.visit_unit(),
+            VariantDispatch::Bool(v) => visitor.visit_bool(v),
+            VariantDispatch::I64(v) => visitor.visit_i64(v),
+            VariantDispatch::F64(v) => visitor.visit_f64(v),
+            VariantDispatch::GodotString(v) => visitor.visit_string(v.to_string()),
+            VariantDispatch::NodePath(v) => visitor.visit_string(v.to_string()),
+            VariantDispatch::Vector2(v) => visit_fields(
+                visitor,
+                vec![("x", v.x.to_variant()), ("y", v.y.to_variant())],
+            ),
+            VariantDispatch::Rect2(v) => visit_fields(
+                visitor,
+                vec![
+                    ("position", v.position.to_variant()),
+                    ("size", v.size.to_variant()),
+                ],
+            ),
+            VariantDispatch::Vector3(v) => visit_fields(
+                visitor,
+                vec![
+                    ("x", v.x.to_variant()),
+                    ("y", v.y.to_variant()),
+                    ("z", v.z.to_variant()),
+                ],
+            ),
+            VariantDispatch::Transform2D(v) => visit_fields(
+                visitor,
+                vec![
+                    ("x", v.x.to_variant()),
+                    ("y", v.y.to_variant()),
+                    ("origin", v.origin.to_variant()),
+                ],
+            ),
+            VariantDispatch::Plane(v) => visit_fields(
+                visitor,
+                vec![("normal", v.normal.to_variant()), ("d", v.d.to_variant())],
+            ),
+            VariantDispatch::Quat(v) => visit_fields(
+                visitor,
+                vec![
+                    ("x", v.x.to_variant()),
+                    ("y", v.y.to_variant()),
+                    ("z", v.z.to_variant()),
+                    ("w", v.w.to_variant()),
+                ],
+            ),
+            VariantDispatch::Aabb(v) => visit_fields(
+                visitor,
+                vec![
+                    ("position", v.position.to_variant()),
+                    ("size", v.size.to_variant()),
+                ],
+            ),
+            VariantDispatch::Basis(v) => {
+                let elements = v.elements.iter().copied().collect::<VariantArray<Unique>>();
+                visit_fields(visitor, vec![("elements", elements.owned_to_variant())])
+            }
+            VariantDispatch::Transform(v) => visit_fields(
+                visitor,
+                vec![
+                    ("basis", v.basis.to_variant()),
+                    ("origin", v.origin.to_variant()),
+                ],
+            ),
+            VariantDispatch::Color(v) => visit_fields(
+                visitor,
+                vec![
+                    ("r", v.r.to_variant()),
+                    ("g", v.g.to_variant()),
+                    ("b", v.b.to_variant()),
+                    ("a", v.a.to_variant()),
+                ],
+            ),
+            VariantDispatch::Dictionary(dict) => {
+                let mut map = MapDeserializer::<_, Error>::new(dict.iter());
+                let value = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(value)
+            }
+            VariantDispatch::VariantArray(array) => visit_array(visitor, array.iter()),
+            VariantDispatch::ByteArray(array) => visitor.visit_byte_buf(array.read().to_vec()),
+            VariantDispatch::Int32Array(array) => visit_pool_array(visitor, &array),
+            VariantDispatch::Float32Array(array) => visit_pool_array(visitor, &array),
+            VariantDispatch::StringArray(array) => visit_pool_array(visitor, &array),
+            VariantDispatch::Vector2Array(array) => visit_pool_array(visitor, &array),
+            VariantDispatch::Vector3Array(array) => visit_pool_array(visitor, &array),
+            VariantDispatch::ColorArray(array) => visit_pool_array(visitor, &array),
+            VariantDispatch::Rid(_) | VariantDispatch::Object(_) => {
+                Err(Error::UnsupportedType(self.variant.get_type()))
+            }
+        }
+    }
+
+    #[inline]
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.variant.is_nil() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[inline]
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // Byte arrays are read as bytes by default, but can be sequences too.
+        match self.variant.try_to_byte_array() {
+            Some(array) => visit_pool_array(visitor, &array),
+            None => self.deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        // `Variant`s are read as they are, with their own types as the enum variants.
+        if name == "Variant" {
+            let ty = self.variant.get_type();
+            if let VariantType::Rid | VariantType::Object = ty {
+                return Err(Error::UnsupportedType(ty));
+            }
+
+            return visitor.visit_enum(EnumDeserializer {
+                variant: VariantType::NAMES[ty as usize].to_string(),
+                value: self.variant,
+            });
+        }
+
+        match self.variant.dispatch() {
+            VariantDispatch::GodotString(variant) => {
+                let variant: StringDeserializer<Error> = variant.to_string().into_deserializer();
+                visitor.visit_enum(variant)
+            }
+            VariantDispatch::Dictionary(dict) if dict.len() == 1 => {
+                let (variant, value) = dict.iter().next().unwrap();
+                let variant = variant
+                    .try_to_string()
+                    .ok_or_else(|| Error::Custom("enum variant names must be strings".into()))?;
+                visitor.visit_enum(EnumDeserializer { variant, value })
+            }
+            _ => Err(Error::Custom(format!(
+                "expected a String or a Dictionary with a single key for an enum, found {:?}",
+                self.variant.get_type(),
+            ))),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct map struct identifier ignored_any
+    }
+}
+
+/// Access to an enum variant stored as a `Dictionary` with a single key.
+struct EnumDeserializer {
+    variant: String,
+    value: Variant,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = Error;
+    type Variant = VariantDeserializer;
+
+    #[inline]
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer), Error> {
+        let variant: StringDeserializer<Error> = self.variant.into_deserializer();
+        let variant = seed.deserialize(variant)?;
+        Ok((variant, VariantDeserializer::new(self.value)))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = Error;
+
+    #[inline]
+    fn unit_variant(self) -> Result<(), Error> {
+        <() as de::Deserialize>::deserialize(self)
+    }
+
+    #[inline]
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    #[inline]
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    #[inline]
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
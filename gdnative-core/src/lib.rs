@@ -44,6 +44,7 @@ pub mod nativescript;
 pub mod log;
 mod new_ref;
 pub mod object;
+pub mod panic;
 pub mod ref_kind;
 pub mod thread_access;
 
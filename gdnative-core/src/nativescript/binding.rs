@@ -0,0 +1,387 @@
+//! Per-object data attached through the NativeScript instance binding API.
+//!
+//! Instance bindings are values associated with each engine object by the library, regardless
+//! of whether a script is attached. They are created lazily the first time they are requested
+//! for an object, and dropped when the object is freed.
+//!
+//! Binding types have to be registered with `InitHandle::register_instance_binding` in the
+//! `godot_nativescript_init` callback, after which they can be retrieved with `Ref::binding`
+//! or `TRef::binding`.
+
+use std::any::TypeId;
+use std::collections::{HashMap, HashSet};
+use std::ffi::CString;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use once_cell::sync::Lazy;
+use parking_lot::{Mutex, RwLock};
+
+use crate::private::{get_api, GlobalClass};
+use crate::sys;
+
+/// Trait for data attached to engine objects through the instance binding API.
+///
+/// A value is created with `InstanceBinding::new` the first time the binding is requested for
+/// an object, and lives until the object is freed. Since objects can be accessed from any
+/// thread, binding types must be `Send + Sync`, and use interior mutability for any state
+/// that changes afterwards.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[derive(Default)]
+/// struct AccessCount(AtomicUsize);
+///
+/// impl InstanceBinding for AccessCount {
+///     fn new() -> Self {
+///         AccessCount::default()
+///     }
+/// }
+///
+/// fn init(handle: InitHandle) {
+///     handle.register_instance_binding::<AccessCount>();
+/// }
+///
+/// fn touch(node: &Ref<Reference, Unique>) {
+///     if let Some(count) = node.binding::<AccessCount>() {
+///         count.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+/// ```
+///
+/// # Thread safety
+///
+/// The engine allocates binding data without synchronization. The first access to the
+/// bindings of an object should not race with another access to the same object.
+pub trait InstanceBinding: Sized + Send + Sync + 'static {
+    /// Creates the binding data for a newly accessed object.
+    fn new() -> Self;
+}
+
+/// Indices of the registered binding types, returned by the engine.
+static BINDINGS: Lazy<RwLock<HashMap<TypeId, libc::c_int>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Index of the internal `ObjectCache` binding, or -1 if it isn't registered. Kept apart from
+/// `BINDINGS` since it's used on every cast.
+static OBJECT_CACHE_INDEX: AtomicI32 = AtomicI32::new(-1);
+
+/// Objects whose `ObjectCache` has been allocated. Since the engine allocates bindings lazily
+/// and without synchronization, the cache is only requested from the engine when casting if
+/// it's known to exist already.
+static ALLOCATED_CACHES: Lazy<RwLock<HashSet<usize>>> = Lazy::new(|| RwLock::new(HashSet::new()));
+
+/// Registers `B` with the engine. Returns `false` if `B` was already registered.
+///
+/// # Safety
+///
+/// The API must be bound. Should only be called from `InitHandle`.
+pub(crate) unsafe fn register<B: InstanceBinding>() -> bool {
+    let mut bindings = BINDINGS.write();
+    if bindings.contains_key(&TypeId::of::<B>()) {
+        return false;
+    }
+
+    bindings.insert(TypeId::of::<B>(), register_functions::<B>(alloc::<B>));
+    true
+}
+
+/// Registers the bindings used internally.
+///
+/// # Safety
+///
+/// The API must be bound. Should only be called from `InitHandle`.
+pub(crate) unsafe fn register_internal() {
+    if OBJECT_CACHE_INDEX.load(Ordering::Acquire) < 0 {
+        let index = register_functions::<ObjectCache>(alloc_object_cache);
+        OBJECT_CACHE_INDEX.store(index, Ordering::Release);
+    }
+}
+
+/// Registers global type tags for engine classes, which are passed to the allocator of
+/// `ObjectCache`.
+///
+/// # Safety
+///
+/// The API must be bound. Should only be called during `nativescript_init`.
+pub(crate) unsafe fn register_global_type_tags(classes: &[&'static GlobalClass]) {
+    let index = OBJECT_CACHE_INDEX.load(Ordering::Acquire);
+    if index < 0 {
+        return;
+    }
+
+    let api = get_api();
+    for &class in classes {
+        let name = CString::new(class.name).unwrap();
+        (api.godot_nativescript_set_global_type_tag)(
+            index,
+            name.as_ptr(),
+            class as *const GlobalClass as *const libc::c_void,
+        );
+    }
+}
+
+type AllocFn = unsafe extern "C" fn(
+    *mut libc::c_void,
+    *const libc::c_void,
+    *mut sys::godot_object,
+) -> *mut libc::c_void;
+
+unsafe extern "C" fn alloc<B: InstanceBinding>(
+    _data: *mut libc::c_void,
+    _global_type_tag: *const libc::c_void,
+    _owner: *mut sys::godot_object,
+) -> *mut libc::c_void {
+    match panic::catch_unwind(B::new) {
+        Ok(binding) => Box::into_raw(Box::new(binding)) as *mut libc::c_void,
+        Err(_) => {
+            godot_error!(
+                "gdnative-core: instance binding {} panicked while being created",
+                std::any::type_name::<B>(),
+            );
+            ptr::null_mut()
+        }
+    }
+}
+
+unsafe fn register_functions<B: InstanceBinding>(alloc: AllocFn) -> libc::c_int {
+    unsafe extern "C" fn free<B: InstanceBinding>(
+        _data: *mut libc::c_void,
+        binding: *mut libc::c_void,
+    ) {
+        if binding.is_null() {
+            return;
+        }
+
+        let binding = Box::from_raw(binding as *mut B);
+        if panic::catch_unwind(AssertUnwindSafe(|| drop(binding))).is_err() {
+            godot_error!(
+                "gdnative-core: instance binding {} panicked while being dropped",
+                std::any::type_name::<B>(),
+            );
+        }
+    }
+
+    unsafe extern "C" fn refcount_incremented(
+        _binding: *mut libc::c_void,
+        _owner: *mut sys::godot_object,
+    ) {
+    }
+
+    unsafe extern "C" fn refcount_decremented(
+        _binding: *mut libc::c_void,
+        _owner: *mut sys::godot_object,
+    ) -> bool {
+        // Bindings never keep their objects alive.
+        true
+    }
+
+    (get_api().godot_nativescript_register_instance_binding_data_functions)(
+        sys::godot_instance_binding_functions {
+            alloc_instance_binding_data: Some(alloc),
+            free_instance_binding_data: Some(free::<B>),
+            refcount_incremented_instance_binding: Some(refcount_incremented),
+            refcount_decremented_instance_binding: Some(refcount_decremented),
+            data: ptr::null_mut(),
+            free_func: None,
+        },
+    )
+}
+
+/// Returns the binding `B` of `owner`, or `None` if `B` isn't registered or failed to be
+/// created.
+///
+/// # Safety
+///
+/// `owner` must be a valid object. The returned pointer is only valid while it's alive.
+#[inline]
+pub(crate) unsafe fn get<B: InstanceBinding>(
+    owner: NonNull<sys::godot_object>,
+) -> Option<NonNull<B>> {
+    let index = *BINDINGS.read().get(&TypeId::of::<B>())?;
+    get_by_index(index, owner)
+}
+
+#[inline]
+unsafe fn get_by_index<B: InstanceBinding>(
+    index: libc::c_int,
+    owner: NonNull<sys::godot_object>,
+) -> Option<NonNull<B>> {
+    let binding = (get_api().godot_nativescript_get_instance_binding_data)(index, owner.as_ptr());
+    NonNull::new(binding as *mut B)
+}
+
+/// Unregisters all bindings. Should only be called in `crate::cleanup_internal_state`.
+///
+/// # Safety
+///
+/// The API must still be bound.
+pub(crate) unsafe fn cleanup() {
+    let api = get_api();
+
+    for (_, index) in BINDINGS.write().drain() {
+        (api.godot_nativescript_unregister_instance_binding_data_functions)(index);
+    }
+
+    let index = OBJECT_CACHE_INDEX.swap(-1, Ordering::AcqRel);
+    if index >= 0 {
+        (api.godot_nativescript_unregister_instance_binding_data_functions)(index);
+    }
+
+    ALLOCATED_CACHES.write().clear();
+}
+
+/// Type tag and user-data pointer of a script instance of this library.
+#[derive(Copy, Clone)]
+pub(crate) struct CachedScript {
+    pub(crate) type_tag: *const libc::c_void,
+    pub(crate) user_data: *mut libc::c_void,
+}
+
+// Safety: the pointers are only used as identifiers, or dereferenced through `UserData`, which
+// handles thread safety itself.
+unsafe impl Send for CachedScript {}
+
+/// Internal binding that caches the engine class and script instance of an object, to avoid
+/// calling into the engine on each cast. It's allocated by the constructors of script classes,
+/// which also fill in the script instance, and the script instance is cleared by their
+/// destructors. Objects without a script of this library don't get a cache.
+struct ObjectCache {
+    owner: usize,
+    class: Option<&'static GlobalClass>,
+    script: Mutex<Option<CachedScript>>,
+}
+
+impl InstanceBinding for ObjectCache {
+    #[inline]
+    fn new() -> Self {
+        ObjectCache {
+            owner: 0,
+            class: None,
+            script: Mutex::new(None),
+        }
+    }
+}
+
+impl Drop for ObjectCache {
+    #[inline]
+    fn drop(&mut self) {
+        ALLOCATED_CACHES.write().remove(&self.owner);
+    }
+}
+
+unsafe extern "C" fn alloc_object_cache(
+    _data: *mut libc::c_void,
+    global_type_tag: *const libc::c_void,
+    owner: *mut sys::godot_object,
+) -> *mut libc::c_void {
+    let cache = ObjectCache {
+        owner: owner as usize,
+        // Safety: only tags registered in `register_global_type_tags` are passed for our index.
+        class: (global_type_tag as *const GlobalClass).as_ref(),
+        ..ObjectCache::new()
+    };
+
+    Box::into_raw(Box::new(cache)) as *mut libc::c_void
+}
+
+/// Returns the cache of `owner`, allocating it if necessary.
+///
+/// # Safety
+///
+/// `owner` must be a valid object, which isn't accessed from other threads at the same time,
+/// like during the construction of its script instance.
+#[inline]
+unsafe fn allocate_object_cache(owner: NonNull<sys::godot_object>) -> Option<NonNull<ObjectCache>> {
+    let index = OBJECT_CACHE_INDEX.load(Ordering::Acquire);
+    if index < 0 {
+        return None;
+    }
+
+    let cache = get_by_index(index, owner)?;
+
+    // Only recorded once the engine has stored the binding, so other threads never cause it to
+    // be allocated a second time.
+    ALLOCATED_CACHES.write().insert(owner.as_ptr() as usize);
+
+    Some(cache)
+}
+
+/// Returns the cache of `owner` if it has been allocated already.
+///
+/// # Safety
+///
+/// `owner` must be a valid object.
+#[inline]
+unsafe fn object_cache(owner: NonNull<sys::godot_object>) -> Option<NonNull<ObjectCache>> {
+    let index = OBJECT_CACHE_INDEX.load(Ordering::Acquire);
+    if index < 0 || !ALLOCATED_CACHES.read().contains(&(owner.as_ptr() as usize)) {
+        return None;
+    }
+
+    get_by_index(index, owner)
+}
+
+/// Returns whether `owner` is an instance of the engine class `class_name`, using its global
+/// type tag. Returns `None` if the tag isn't available, including when `owner` has no cache,
+/// in which case the engine should be asked instead.
+///
+/// # Safety
+///
+/// `owner` must be a valid object.
+#[inline]
+pub(crate) unsafe fn is_class(owner: NonNull<sys::godot_object>, class_name: &str) -> Option<bool> {
+    let mut class = object_cache(owner)?.as_ref().class?;
+
+    loop {
+        if class.name == class_name {
+            return Some(true);
+        }
+
+        class = match class.base {
+            Some(base) => base,
+            None => return Some(false),
+        };
+    }
+}
+
+/// Records the script instance of `owner`, allocating its cache. Called by script constructors.
+///
+/// # Safety
+///
+/// `owner` must be a valid object, and `script` the instance being constructed for it.
+#[inline]
+pub(crate) unsafe fn set_cached_script(owner: NonNull<sys::godot_object>, script: CachedScript) {
+    if let Some(cache) = allocate_object_cache(owner) {
+        *cache.as_ref().script.lock() = Some(script);
+    }
+}
+
+/// Clears the script instance of `owner`. Called by script destructors.
+///
+/// # Safety
+///
+/// `owner` must be a valid object.
+#[inline]
+pub(crate) unsafe fn clear_cached_script(owner: NonNull<sys::godot_object>) {
+    if let Some(cache) = object_cache(owner) {
+        cache.as_ref().script.lock().take();
+    }
+}
+
+/// Returns the cached script instance of `owner`. The outer `Option` is `None` if the cache
+/// isn't available or hasn't been allocated, and the inner one if `owner` has no script
+/// instance of this library.
+///
+/// # Safety
+///
+/// `owner` must be a valid object.
+#[inline]
+pub(crate) unsafe fn cached_script(
+    owner: NonNull<sys::godot_object>,
+) -> Option<Option<CachedScript>> {
+    let cache = object_cache(owner)?;
+    Some(*cache.as_ref().script.lock())
+}
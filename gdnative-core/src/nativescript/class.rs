@@ -1,3 +1,5 @@
+use std::any::TypeId;
+use std::marker::PhantomData;
 use std::ptr::NonNull;
 
 use crate::core_types::{
@@ -7,13 +9,14 @@ use crate::nativescript::init::ClassBuilder;
 use crate::nativescript::{Map, MapMut, MapOwned, UserData};
 use crate::object::{
     AssumeSafeLifetime, LifetimeConstraint, QueueFree, RawObject, Ref, RefImplBound, SafeAsRaw,
-    SafeDeref, TRef,
+    SafeDeref, SubClass, TRef,
 };
 use crate::object::{GodotObject, Instanciable};
 use crate::private::{get_api, ReferenceCountedClassPlaceholder};
 use crate::ref_kind::{ManuallyManaged, RefCounted};
 use crate::thread_access::{NonUniqueThreadAccess, Shared, ThreadAccess, ThreadLocal, Unique};
 
+use super::binding;
 use super::class_registry;
 use super::emplace;
 /// Trait used for describing and initializing a Godot script class.
@@ -83,6 +86,14 @@ pub trait NativeClass: Sized + 'static {
     #[inline]
     fn register_properties(_builder: &ClassBuilder<Self>) {}
 
+    /// Whether `InitHandle::add_auto_registered_classes` registers this class with
+    /// `add_tool_class`. Implemented by `#[derive(NativeClass)]` for the `#[tool]` attribute.
+    #[doc(hidden)]
+    #[inline]
+    fn is_tool() -> bool {
+        false
+    }
+
     /// Convenience method to create an `Instance<Self, Unique>`. This is a new `Self::Base`
     /// with the script attached.
     ///
@@ -122,6 +133,133 @@ pub trait NativeClassMethods: NativeClass {
     fn register(builder: &ClassBuilder<Self>);
 }
 
+/// Trait for script classes that inherit from another Rust script class, `P`.
+///
+/// Godot doesn't support script inheritance for NativeScript. Instead, a sub-class holds the
+/// state of its parent script in a [`ParentScript`] field, and all exported methods,
+/// properties and signals of `P` are registered on the sub-class using
+/// `ClassBuilder::inherit_script`. When inherited members are called from Godot, the parent
+/// state is passed to the methods of `P`.
+///
+/// A derive macro is available for this trait through the `#[inherit_script]` attribute of
+/// the `NativeClass` derive macro.
+///
+/// [`ParentScript`]: ./struct.ParentScript.html
+pub trait NativeSubClass<P: NativeClass>: NativeClass {
+    /// Returns a reference to the state of the parent script.
+    fn parent_script(&self) -> &ParentScript<P>;
+}
+
+/// The state of a parent script `P`, held by a sub-class implementing `NativeSubClass<P>`.
+///
+/// The value is stored in its own `P::UserData` wrapper, so it can be passed to the methods of
+/// `P` independently of the sub-class.
+pub struct ParentScript<P: NativeClass> {
+    user_data: *const libc::c_void,
+    _marker: PhantomData<P::UserData>,
+}
+
+impl<P: NativeClass> ParentScript<P> {
+    /// Wraps `script` in a new user-data wrapper to be used as the parent script state.
+    #[inline]
+    pub fn new(script: P) -> Self {
+        ParentScript {
+            user_data: P::UserData::new(script).into_user_data(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a clone of the user-data wrapper of the parent script.
+    #[inline]
+    pub fn script(&self) -> P::UserData {
+        unsafe { P::UserData::clone_from_user_data_unchecked(self.user_data) }
+    }
+
+    /// Calls a function with a reference to the parent script, and returns its return value.
+    #[inline]
+    pub fn map<F, U>(&self, op: F) -> Result<U, <P::UserData as Map>::Err>
+    where
+        P::UserData: Map,
+        F: FnOnce(&P) -> U,
+    {
+        self.script().map(op)
+    }
+
+    /// Calls a function with a mutable reference to the parent script, and returns its return
+    /// value.
+    #[inline]
+    pub fn map_mut<F, U>(&self, op: F) -> Result<U, <P::UserData as MapMut>::Err>
+    where
+        P::UserData: MapMut,
+        F: FnOnce(&mut P) -> U,
+    {
+        self.script().map_mut(op)
+    }
+
+    pub(crate) fn user_data_ptr(&self) -> *const libc::c_void {
+        self.user_data
+    }
+}
+
+impl<P> Default for ParentScript<P>
+where
+    P: NativeClass + Default,
+{
+    #[inline]
+    fn default() -> Self {
+        ParentScript::new(P::default())
+    }
+}
+
+impl<P: NativeClass> Drop for ParentScript<P> {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            drop(P::UserData::consume_user_data_unchecked(self.user_data));
+        }
+    }
+}
+
+impl<P: NativeClass> std::fmt::Debug for ParentScript<P> {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ParentScript")
+            .field(&self.user_data)
+            .finish()
+    }
+}
+
+// Safety: `ParentScript` has the same semantics as an owned `P::UserData`.
+unsafe impl<P> Send for ParentScript<P>
+where
+    P: NativeClass,
+    P::UserData: Send,
+{
+}
+
+unsafe impl<P> Sync for ParentScript<P>
+where
+    P: NativeClass,
+    P::UserData: Sync,
+{
+}
+
+/// Returns the user-data pointer of the parent script `P`, given one of `C`. Used by
+/// `ClassBuilder::inherit_script`.
+pub(crate) unsafe fn upcast_user_data<C, P>(
+    user_data: *const libc::c_void,
+) -> Option<*const libc::c_void>
+where
+    C: NativeSubClass<P>,
+    C::UserData: Map,
+    P: NativeClass,
+{
+    let script = C::UserData::clone_from_user_data_unchecked(user_data);
+    script
+        .map(|script| script.parent_script().user_data_ptr())
+        .ok()
+}
+
 /// Trait for types that can be used as the `owner` arguments of exported methods. This trait
 /// is sealed and has no public interface.
 ///
@@ -413,6 +551,29 @@ where
     pub fn from_base(owner: Ref<T::Base, Access>) -> Option<Self> {
         Self::try_from_base(owner).ok()
     }
+
+    /// Performs a static upcast to the parent script `P`, keeping the reference count of the
+    /// base object.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the script is not accessible, in which case the reference is
+    /// dropped.
+    #[inline]
+    pub fn upcast<P>(self) -> Result<Instance<P, Access>, <T::UserData as Map>::Err>
+    where
+        T: NativeSubClass<P>,
+        T::UserData: Map,
+        T::Base: SubClass<P::Base>,
+        P: NativeClass,
+        P::Base: GodotObject<RefKind = <T::Base as GodotObject>::RefKind>,
+    {
+        let script = self.script.map(|script| script.parent_script().script())?;
+        Ok(Instance {
+            owner: self.owner.upcast(),
+            script,
+        })
+    }
 }
 
 impl<T: NativeClass, Access: ThreadAccess> Instance<T, Access>
@@ -477,6 +638,33 @@ impl<T: NativeClass> Instance<T, Shared> {
     }
 }
 
+/// Methods for auditing live instances, with the `instance_tracking` feature.
+#[cfg(feature = "instance_tracking")]
+impl<T: NativeClass> Instance<T, Shared> {
+    /// Returns the number of script instances of `T` that are currently alive. Instances of
+    /// scripts inheriting from `T` are not counted.
+    #[inline]
+    pub fn live_count() -> usize {
+        class_registry::live_count::<T>()
+    }
+
+    /// Returns all script instances of `T` that are currently alive, in no particular order.
+    /// Instances of scripts inheriting from `T` are not included.
+    ///
+    /// # Safety
+    ///
+    /// No reference-counted instance of `T` may be in the process of being freed on another
+    /// thread during the call, since its reference count would be incremented after reaching
+    /// zero.
+    #[inline]
+    pub unsafe fn live_instances() -> Vec<Self> {
+        class_registry::map_live_instances::<T, _, _>(|owner, user_data| Instance {
+            owner: Ref::from_sys(owner),
+            script: T::UserData::clone_from_user_data_unchecked(user_data),
+        })
+    }
+}
+
 impl<T: NativeClass> Instance<T, Shared>
 where
     T::Base: GodotObject<RefKind = ManuallyManaged>,
@@ -739,19 +927,62 @@ fn try_get_user_data_ptr<T: NativeClass>(owner: &RawObject<T::Base>) -> Option<*
     unsafe {
         let api = get_api();
 
-        let owner_ptr = owner.sys().as_ptr();
+        let owner_ptr = owner.sys();
 
-        let type_tag = (api.godot_nativescript_get_type_tag)(owner_ptr);
-        if type_tag.is_null() {
-            return None;
+        // The cache is authoritative when available: script constructors and destructors of
+        // this library keep it up to date.
+        if let Some(script) = binding::cached_script(owner_ptr) {
+            let script = script?;
+            return upcast_user_data_ptr::<T>(script.type_tag, script.user_data);
         }
 
-        if !crate::nativescript::type_tag::check::<T>(type_tag) {
+        let type_tag = (api.godot_nativescript_get_type_tag)(owner_ptr.as_ptr());
+        if type_tag.is_null() {
             return None;
         }
 
-        Some((api.godot_nativescript_get_userdata)(owner_ptr))
+        let user_data = (api.godot_nativescript_get_userdata)(owner_ptr.as_ptr());
+        upcast_user_data_ptr::<T>(type_tag, user_data)
+    }
+}
+
+/// Resolves the user-data pointer passed by Godot into one of `T`, which might be a parent
+/// script of the actual script class of `owner`.
+///
+/// # Safety
+///
+/// `owner` must be a valid object with a script of this library attached, and `user_data`
+/// must be its user-data pointer.
+pub(crate) unsafe fn resolve_user_data_ptr<T: NativeClass>(
+    owner: NonNull<sys::godot_object>,
+    user_data: *mut libc::c_void,
+) -> Option<*mut libc::c_void> {
+    if !class_registry::has_subclasses::<T>() {
+        return Some(user_data);
+    }
+
+    let type_tag = (get_api().godot_nativescript_get_type_tag)(owner.as_ptr());
+    if type_tag.is_null() {
+        return None;
+    }
+
+    upcast_user_data_ptr::<T>(type_tag, user_data)
+}
+
+unsafe fn upcast_user_data_ptr<T: NativeClass>(
+    type_tag: *const libc::c_void,
+    user_data: *mut libc::c_void,
+) -> Option<*mut libc::c_void> {
+    if crate::nativescript::type_tag::check::<T>(type_tag) {
+        return Some(user_data);
     }
+
+    class_registry::upcast_user_data(
+        crate::nativescript::type_tag::type_id(type_tag),
+        TypeId::of::<T>(),
+        user_data,
+    )
+    .map(|user_data| user_data as *mut libc::c_void)
 }
 
 mod private {
@@ -2,10 +2,32 @@ use crate::nativescript::NativeClass;
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 use std::any::TypeId;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+#[cfg(feature = "instance_tracking")]
+use parking_lot::Mutex;
+#[cfg(feature = "instance_tracking")]
+use std::ptr::NonNull;
+
+#[cfg(feature = "instance_tracking")]
+use crate::sys;
 
 static CLASS_REGISTRY: Lazy<RwLock<HashSet<TypeId>>> = Lazy::new(|| RwLock::new(HashSet::new()));
 
+static INHERITANCE: Lazy<RwLock<Inheritance>> = Lazy::new(|| RwLock::new(Inheritance::default()));
+
+/// Function that takes a user-data pointer of a sub-class, and returns the user-data pointer
+/// of its parent script. Returns `None` if the parent script can't be accessed at the time.
+pub(crate) type UpcastFn = unsafe fn(*const libc::c_void) -> Option<*const libc::c_void>;
+
+#[derive(Default)]
+struct Inheritance {
+    /// Direct parent scripts of each sub-class, along with functions to obtain their user-data.
+    parents: HashMap<TypeId, (TypeId, UpcastFn)>,
+    /// Classes that are inherited by at least one other script.
+    bases: HashSet<TypeId>,
+}
+
 /// Can be used to validate whether or not `C` has been added using `InitHandle::add_class<C>()`
 /// Returns true if added otherwise false.
 #[inline]
@@ -23,8 +45,160 @@ pub(crate) fn register_class<C: NativeClass>() -> bool {
     CLASS_REGISTRY.write().insert(type_id)
 }
 
+/// Registers `P` as the direct parent script of `C`.
+#[inline]
+pub(crate) fn register_parent<C: NativeClass, P: NativeClass>(upcast: UpcastFn) {
+    let mut inheritance = INHERITANCE.write();
+    inheritance
+        .parents
+        .insert(TypeId::of::<C>(), (TypeId::of::<P>(), upcast));
+    inheritance.bases.insert(TypeId::of::<P>());
+}
+
+/// Returns `true` if any script has been registered as a sub-class of `P`.
+#[inline]
+pub(crate) fn has_subclasses<P: NativeClass>() -> bool {
+    INHERITANCE.read().bases.contains(&TypeId::of::<P>())
+}
+
+/// Walks up the inheritance chain from `from` to `to`, converting `user_data` along the way.
+/// Returns `None` if `to` isn't an ancestor of `from`, or if any parent script is inaccessible.
+///
+/// # Safety
+///
+/// `user_data` must be a valid user-data pointer for the script type `from`.
+#[inline]
+pub(crate) unsafe fn upcast_user_data(
+    mut from: TypeId,
+    to: TypeId,
+    mut user_data: *const libc::c_void,
+) -> Option<*const libc::c_void> {
+    while from != to {
+        let (parent, upcast) = *INHERITANCE.read().parents.get(&from)?;
+        user_data = upcast(user_data)?;
+        from = parent;
+    }
+
+    Some(user_data)
+}
+
+/// Live script instances of a class, recorded with the `instance_tracking` feature.
+#[cfg(feature = "instance_tracking")]
+struct LiveInstances {
+    class_name: &'static str,
+    /// User-data pointers of the instances, keyed by their base objects.
+    objects: HashMap<usize, usize>,
+}
+
+#[cfg(feature = "instance_tracking")]
+static LIVE_INSTANCES: Lazy<Mutex<HashMap<TypeId, LiveInstances>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Records the construction of an instance of `C` attached to `owner`.
+#[cfg(feature = "instance_tracking")]
+#[inline]
+pub(crate) fn track_construction<C: NativeClass>(
+    owner: NonNull<sys::godot_object>,
+    user_data: *const libc::c_void,
+) {
+    LIVE_INSTANCES
+        .lock()
+        .entry(TypeId::of::<C>())
+        .or_insert_with(|| LiveInstances {
+            class_name: C::class_name(),
+            objects: HashMap::new(),
+        })
+        .objects
+        .insert(owner.as_ptr() as usize, user_data as usize);
+}
+
+/// Records the destruction of the instance of `C` attached to `owner`.
+#[cfg(feature = "instance_tracking")]
+#[inline]
+pub(crate) fn track_destruction<C: NativeClass>(owner: NonNull<sys::godot_object>) {
+    if let Some(live) = LIVE_INSTANCES.lock().get_mut(&TypeId::of::<C>()) {
+        live.objects.remove(&(owner.as_ptr() as usize));
+    }
+}
+
+/// Returns the number of live instances of `C`.
+#[cfg(feature = "instance_tracking")]
+#[inline]
+pub(crate) fn live_count<C: NativeClass>() -> usize {
+    LIVE_INSTANCES
+        .lock()
+        .get(&TypeId::of::<C>())
+        .map_or(0, |live| live.objects.len())
+}
+
+/// Calls `f` with the base object and user-data pointer of each live instance of `C`. The
+/// registry is locked during the calls, so instances can't be destroyed in the meantime.
+#[cfg(feature = "instance_tracking")]
+#[inline]
+pub(crate) fn map_live_instances<C, F, R>(mut f: F) -> Vec<R>
+where
+    C: NativeClass,
+    F: FnMut(NonNull<sys::godot_object>, *const libc::c_void) -> R,
+{
+    let live_instances = LIVE_INSTANCES.lock();
+    let live = match live_instances.get(&TypeId::of::<C>()) {
+        Some(live) => live,
+        None => return Vec::new(),
+    };
+
+    live.objects
+        .iter()
+        .filter_map(|(&owner, &user_data)| {
+            let owner = NonNull::new(owner as *mut sys::godot_object)?;
+            Some(f(owner, user_data as *const libc::c_void))
+        })
+        .collect()
+}
+
+/// Prints the instances that are still alive, which are assumed to be leaked. Called during
+/// `terminate`.
+///
+/// # Safety
+///
+/// The API must still be bound.
+#[cfg(feature = "instance_tracking")]
+pub(crate) unsafe fn report_leaks() {
+    let live_instances = LIVE_INSTANCES.lock();
+
+    let mut leaked = live_instances
+        .values()
+        .filter(|live| !live.objects.is_empty())
+        .collect::<Vec<_>>();
+
+    leaked.sort_by_key(|live| live.class_name);
+
+    for live in leaked {
+        let mut instance_ids = live
+            .objects
+            .keys()
+            .map(|&owner| super::task::instance_id(NonNull::new_unchecked(owner as *mut _)))
+            .collect::<Vec<_>>();
+
+        instance_ids.sort_unstable();
+
+        godot_warn!(
+            "gdnative-core: {} instance(s) of {} leaked, with instance IDs {:?}",
+            instance_ids.len(),
+            live.class_name,
+            instance_ids,
+        );
+    }
+}
+
 /// Clears the registry
 #[inline]
 pub(crate) fn cleanup() {
     CLASS_REGISTRY.write().clear();
+
+    #[cfg(feature = "instance_tracking")]
+    LIVE_INSTANCES.lock().clear();
+
+    let mut inheritance = INHERITANCE.write();
+    inheritance.parents.clear();
+    inheritance.bases.clear();
 }
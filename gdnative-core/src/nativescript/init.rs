@@ -40,21 +40,27 @@ use std::ffi::CString;
 use std::marker::PhantomData;
 use std::ptr;
 
-use crate::core_types::{GodotString, Variant};
+use crate::core_types::{GodotString, Variant, VariantType};
 use crate::nativescript::NativeClass;
 use crate::nativescript::NativeClassMethods;
-use crate::nativescript::UserData;
+use crate::nativescript::NativeSubClass;
+use crate::nativescript::{Map, UserData};
+use crate::object::SubClass;
 use crate::private::get_api;
 
+use super::binding::{self, InstanceBinding};
 use super::class_registry;
 use super::emplace;
 pub mod method;
 pub mod property;
 
 pub use self::method::{
-    Method, MethodBuilder, RpcMode, ScriptMethod, ScriptMethodAttributes, ScriptMethodFn, Varargs,
+    Method, MethodArg, MethodBuilder, RpcMode, ScriptMethod, ScriptMethodAttributes,
+    ScriptMethodFn, Varargs,
+};
+pub use self::property::{
+    DynamicProperties, Export, ExportInfo, PropertyBuilder, PropertyInfo, Usage as PropertyUsage,
 };
-pub use self::property::{Export, ExportInfo, PropertyBuilder, Usage as PropertyUsage};
 
 /// A handle that can register new classes to the engine during initialization.
 ///
@@ -82,6 +88,66 @@ impl InitHandle {
         self.add_maybe_tool_class::<C>(false)
     }
 
+    /// Registers all classes with a `#[methods]` impl block in the crate and its dependencies,
+    /// using `add_tool_class` for the ones with the `#[tool]` attribute and `add_class` for
+    /// the others. The classes are collected at link time by the `inventory` feature. Classes
+    /// implementing `NativeClassMethods` manually must still be registered by hand.
+    ///
+    /// Classes that have already been registered, for example with a separate call to
+    /// `add_class`, are skipped.
+    #[cfg(feature = "inventory")]
+    #[inline]
+    pub fn add_auto_registered_classes(self) {
+        for class in crate::private::inventory::iter::<crate::private::AutoRegisteredClass> {
+            (class.register)(self);
+        }
+    }
+
+    /// Registers `C` if it hasn't been registered yet. Used by the classes collected for
+    /// `add_auto_registered_classes`.
+    ///
+    /// This is intended to be an internal interface.
+    #[cfg(feature = "inventory")]
+    #[doc(hidden)]
+    #[inline]
+    pub fn add_auto_registered_class<C>(self)
+    where
+        C: NativeClassMethods,
+    {
+        if !class_registry::is_class_registered::<C>() {
+            self.add_maybe_tool_class::<C>(C::is_tool());
+        }
+    }
+
+    /// Registers the classes and instance bindings used internally by the bindings.
+    ///
+    /// This is intended to be an internal interface.
+    #[doc(hidden)]
+    #[inline]
+    pub fn add_internal_classes(self) {
+        unsafe { binding::register_internal() };
+        self.add_class::<super::signal::SignalClosure>();
+        self.add_class::<super::user_data::diagnostics::LockDiagnostics>();
+    }
+
+    /// Registers an instance binding type, so it can be retrieved with `Ref::binding`.
+    ///
+    /// # Panics
+    ///
+    /// If `B` has already been registered.
+    #[inline]
+    pub fn register_instance_binding<B>(self)
+    where
+        B: InstanceBinding,
+    {
+        if !unsafe { binding::register::<B>() } {
+            panic!(
+                "`{type_name}` has already been registered",
+                type_name = std::any::type_name::<B>()
+            );
+        }
+    }
+
     /// Registers a new tool class to the engine.
     #[inline]
     pub fn add_tool_class<C>(self)
@@ -106,10 +172,12 @@ impl InitHandle {
             let class_name = CString::new(C::class_name()).unwrap();
             let base_name = CString::new(C::Base::class_name()).unwrap();
 
+            let type_tag = crate::nativescript::type_tag::create::<C>();
+
             let create = {
                 unsafe extern "C" fn constructor<C: NativeClass>(
                     this: *mut sys::godot_object,
-                    _method_data: *mut libc::c_void,
+                    method_data: *mut libc::c_void,
                 ) -> *mut libc::c_void {
                     use std::panic::{self, AssertUnwindSafe};
 
@@ -142,32 +210,54 @@ impl InitHandle {
                             .unwrap_or_else(|| C::init(TRef::new(C::Base::cast_ref(owner))))
                     })) {
                         Ok(val) => val,
-                        Err(_) => {
-                            godot_error!(
-                                "gdnative-core: error constructing {}: constructor panicked",
+                        Err(payload) => {
+                            crate::panic::handle_panic(
                                 C::class_name(),
+                                crate::panic::Callback::Constructor,
+                                None,
+                                payload,
                             );
                             return ptr::null_mut();
                         }
                     };
 
                     let wrapper = C::UserData::new(val);
-                    C::UserData::into_user_data(wrapper) as *mut _
+                    let user_data = C::UserData::into_user_data(wrapper) as *mut libc::c_void;
+
+                    binding::set_cached_script(
+                        this,
+                        binding::CachedScript {
+                            type_tag: method_data as *const libc::c_void,
+                            user_data,
+                        },
+                    );
+
+                    #[cfg(feature = "instance_tracking")]
+                    class_registry::track_construction::<C>(this, user_data);
+
+                    user_data
                 }
 
                 sys::godot_instance_create_func {
                     create_func: Some(constructor::<C>),
-                    method_data: ptr::null_mut(),
+                    method_data: type_tag as *mut libc::c_void,
                     free_func: None,
                 }
             };
 
             let destroy = {
                 unsafe extern "C" fn destructor<C: NativeClass>(
-                    _this: *mut sys::godot_object,
+                    this: *mut sys::godot_object,
                     _method_data: *mut libc::c_void,
                     user_data: *mut libc::c_void,
                 ) {
+                    if let Some(this) = ptr::NonNull::new(this) {
+                        binding::clear_cached_script(this);
+
+                        #[cfg(feature = "instance_tracking")]
+                        class_registry::track_destruction::<C>(this);
+                    }
+
                     if user_data.is_null() {
                         godot_error!(
                             "gdnative-core: user data pointer for {} is null (did the constructor fail?)",
@@ -208,7 +298,7 @@ impl InitHandle {
             (get_api().godot_nativescript_set_type_tag)(
                 self.handle as *mut _,
                 class_name.as_ptr() as *const _,
-                crate::nativescript::type_tag::create::<C>(),
+                type_tag,
             );
 
             let builder = ClassBuilder {
@@ -238,15 +328,7 @@ impl<C: NativeClass> ClassBuilder<C> {
     pub fn add_method_advanced(&self, method: ScriptMethod) {
         let method_name = CString::new(method.name).unwrap();
 
-        let rpc = match method.attributes.rpc_mode {
-            RpcMode::Master => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_MASTER,
-            RpcMode::Remote => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_REMOTE,
-            RpcMode::Puppet => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_PUPPET,
-            RpcMode::RemoteSync => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_REMOTESYNC,
-            RpcMode::Disabled => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_DISABLED,
-            RpcMode::MasterSync => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_MASTERSYNC,
-            RpcMode::PuppetSync => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_PUPPETSYNC,
-        };
+        let rpc = method.attributes.rpc_mode.sys();
 
         let attr = sys::godot_method_attributes { rpc_type: rpc };
 
@@ -267,6 +349,62 @@ impl<C: NativeClass> ClassBuilder<C> {
         }
     }
 
+    /// Registers the names and types of the arguments of a method with the editor. Does
+    /// nothing if `args` is empty.
+    #[inline]
+    fn set_method_argument_information(&self, name: &str, args: &[MethodArg]) {
+        if args.is_empty() {
+            return;
+        }
+
+        let method_name = CString::new(name).unwrap();
+
+        let owned = args
+            .iter()
+            .map(|arg| {
+                let arg_name = GodotString::from_str(&arg.name);
+                let hint_string = arg.export_info.hint_string.new_ref();
+                (arg, arg_name, hint_string)
+            })
+            .collect::<Vec<_>>();
+
+        let args = owned
+            .iter()
+            .map(|(arg, arg_name, hint_string)| sys::godot_method_arg {
+                name: arg_name.to_sys(),
+                type_: arg.export_info.variant_type as sys::godot_variant_type,
+                hint: arg.export_info.hint_kind,
+                hint_string: hint_string.to_sys(),
+            })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            (get_api().godot_nativescript_set_method_argument_information)(
+                self.init_handle,
+                self.class_name.as_ptr(),
+                method_name.as_ptr(),
+                args.len() as libc::c_int,
+                args.as_ptr(),
+            );
+        }
+    }
+
+    /// Registers the documentation of a method, shown in the editor's help panel.
+    #[inline]
+    fn set_method_documentation(&self, name: &str, documentation: &str) {
+        let method_name = CString::new(name).unwrap();
+        let documentation = GodotString::from_str(documentation);
+
+        unsafe {
+            (get_api().godot_nativescript_set_method_documentation)(
+                self.init_handle,
+                self.class_name.as_ptr(),
+                method_name.as_ptr(),
+                documentation.to_sys(),
+            );
+        }
+    }
+
     #[inline]
     #[deprecated(note = "Unsafe registration is deprecated. Use `build_method` instead.")]
     pub fn add_method_with_rpc_mode(&self, name: &str, method: ScriptMethodFn, rpc_mode: RpcMode) {
@@ -333,8 +471,89 @@ impl<C: NativeClass> ClassBuilder<C> {
         PropertyBuilder::new(self, name)
     }
 
+    /// Starts a group of properties in the inspector.
+    ///
+    /// Properties registered after this call are shown in the group named `name`, as long as
+    /// their names start with `prefix`, which is stripped from the displayed names. With an
+    /// empty `prefix`, all following properties are included until the next group or
+    /// category. A group with an empty `name` ends the current group.
+    ///
+    /// The group is registered as a property, so its name shouldn't be used by any other
+    /// property of the class. This also means that each group can only be started once, and
+    /// a group can only be ended once: Godot keeps the first position of a property name that
+    /// is registered again. Register ungrouped properties before the first group instead of
+    /// ending groups where possible.
+    #[inline]
+    pub fn add_property_group(&self, name: &str, prefix: &str) {
+        property::add_pseudo_property(self, name, prefix, PropertyUsage::GROUP);
+    }
+
+    /// Starts a category of properties in the inspector. Categories are shown as headers,
+    /// like the ones for the script and base classes.
+    ///
+    /// The category is registered as a property, so its name shouldn't be used by any other
+    /// property of the class.
+    #[inline]
+    pub fn add_property_category(&self, name: &str) {
+        property::add_pseudo_property(self, name, "", PropertyUsage::CATEGORY);
+    }
+
+    /// Sets the documentation of the class being registered, shown in the editor's help
+    /// panel.
+    #[inline]
+    pub fn set_class_documentation(&self, documentation: &str) {
+        let documentation = GodotString::from_str(documentation);
+
+        unsafe {
+            (get_api().godot_nativescript_set_class_documentation)(
+                self.init_handle,
+                self.class_name.as_ptr(),
+                documentation.to_sys(),
+            );
+        }
+    }
+
+    /// Sets the documentation of a signal of the class being registered, shown in the editor's
+    /// help panel.
+    #[inline]
+    pub fn set_signal_documentation(&self, name: &str, documentation: &str) {
+        let signal_name = CString::new(name).unwrap();
+        let documentation = GodotString::from_str(documentation);
+
+        unsafe {
+            (get_api().godot_nativescript_set_signal_documentation)(
+                self.init_handle,
+                self.class_name.as_ptr(),
+                signal_name.as_ptr(),
+                documentation.to_sys(),
+            );
+        }
+    }
+
     #[inline]
     pub fn add_signal(&self, signal: Signal) {
+        self.register_signal(signal, |arg| arg.default.get_type())
+    }
+
+    /// Registers a signal with the `variant_type` of each argument's `export_info` as its
+    /// type, instead of the type of its default value. Used by the `#[signal]` attribute.
+    #[doc(hidden)]
+    #[inline]
+    pub fn add_signal_with_export_types(&self, signal: Signal) {
+        self.register_signal(signal, |arg| arg.export_info.variant_type)
+    }
+
+    #[inline]
+    fn register_signal<F>(&self, signal: Signal, arg_type: F)
+    where
+        F: Fn(&SignalArgument) -> VariantType,
+    {
+        assert!(
+            signal.num_default_args <= signal.args.len(),
+            "signal `{}` has more default arguments than arguments",
+            signal.name,
+        );
+
         unsafe {
             let name = GodotString::from_str(signal.name);
             let owned = signal
@@ -346,17 +565,31 @@ impl<C: NativeClass> ClassBuilder<C> {
                     (arg, arg_name, hint_string)
                 })
                 .collect::<Vec<_>>();
+
+            let mut default_args = signal.args[signal.args.len() - signal.num_default_args..]
+                .iter()
+                .map(|arg| arg.default.to_sys())
+                .collect::<Vec<_>>();
+
             let mut args = owned
                 .iter()
                 .map(|(arg, arg_name, hint_string)| sys::godot_signal_argument {
                     name: arg_name.to_sys(),
-                    type_: arg.default.get_type() as i32,
+                    type_: arg_type(arg) as i32,
                     hint: arg.export_info.hint_kind,
                     hint_string: hint_string.to_sys(),
                     usage: arg.usage.to_sys(),
                     default_value: arg.default.to_sys(),
                 })
                 .collect::<Vec<_>>();
+
+            // Godot 3.2 reads the default values from the `default_value` fields of the
+            // *first* `num_default_args` arguments instead of `default_args`, so the trailing
+            // defaults are also copied there. The fields are otherwise unused by the engine.
+            for (arg, default) in args.iter_mut().zip(&default_args) {
+                arg.default_value = *default;
+            }
+
             (get_api().godot_nativescript_register_signal)(
                 self.init_handle,
                 self.class_name.as_ptr(),
@@ -364,21 +597,58 @@ impl<C: NativeClass> ClassBuilder<C> {
                     name: name.to_sys(),
                     num_args: args.len() as i32,
                     args: args.as_mut_ptr(),
-                    num_default_args: 0,
-                    default_args: ptr::null_mut(),
+                    num_default_args: default_args.len() as i32,
+                    default_args: default_args.as_mut_ptr(),
                 },
             );
         }
     }
+
+    /// Registers all exported methods, properties and signals of the parent script `P` on
+    /// the class being registered. When called from Godot, the inherited members operate on
+    /// the state returned by `NativeSubClass::parent_script`.
+    ///
+    /// This should be called before anything else is registered, so members of the class
+    /// itself can override inherited ones with the same names. The `NativeClass` derive macro
+    /// does this automatically for the `#[inherit_script]` attribute.
+    ///
+    /// Inherited members access the parent state through `C::UserData::map`, so they will fail
+    /// to be called while the sub-class script is exclusively borrowed.
+    #[inline]
+    pub fn inherit_script<P>(&self)
+    where
+        C: NativeSubClass<P>,
+        C::UserData: Map,
+        C::Base: SubClass<P::Base>,
+        P: NativeClassMethods,
+    {
+        class_registry::register_parent::<C, P>(
+            crate::nativescript::class::upcast_user_data::<C, P>,
+        );
+
+        let builder = ClassBuilder::<P> {
+            init_handle: self.init_handle,
+            class_name: self.class_name.clone(),
+            _marker: PhantomData,
+        };
+
+        P::register_properties(&builder);
+        P::register(&builder);
+    }
 }
 
 pub struct Signal<'l> {
     pub name: &'l str,
     pub args: &'l [SignalArgument<'l>],
+    /// Number of trailing arguments whose `default` values are used when they are omitted,
+    /// for example in `emit_signal` calls from GDScript.
+    pub num_default_args: usize,
 }
 
 pub struct SignalArgument<'l> {
     pub name: &'l str,
+    /// Default value of the argument. Only used if the argument is one of the last
+    /// `Signal::num_default_args` arguments.
     pub default: Variant,
     pub export_info: ExportInfo,
     pub usage: PropertyUsage,
@@ -7,13 +7,17 @@ use std::borrow::Cow;
 use std::fmt;
 use std::marker::PhantomData;
 
-use crate::core_types::{FromVariant, FromVariantError, Variant};
+use crate::core_types::{
+    Dictionary, FromVariant, FromVariantError, OwnedToVariant, ToVariant, Variant, VariantType,
+};
 use crate::log::Site;
 use crate::nativescript::class::{NativeClass, RefInstance};
-use crate::object::{Ref, TRef};
-use crate::thread_access::Shared;
+use crate::object::{GodotObject, Ref, TRef};
+use crate::panic::Callback;
+use crate::thread_access::{Shared, ThreadAccess};
 
 use super::ClassBuilder;
+use super::{Export, ExportInfo};
 
 /// Builder type used to register a method on a `NativeClass`.
 pub struct MethodBuilder<'a, C, F> {
@@ -22,6 +26,8 @@ pub struct MethodBuilder<'a, C, F> {
     method: F,
 
     rpc_mode: RpcMode,
+    args: Option<Vec<MethodArg>>,
+    documentation: Option<&'a str>,
 }
 
 impl<'a, C, F> MethodBuilder<'a, C, F>
@@ -35,6 +41,8 @@ where
             name,
             method,
             rpc_mode: RpcMode::Disabled,
+            args: None,
+            documentation: None,
         }
     }
 
@@ -45,9 +53,28 @@ where
         self
     }
 
+    /// Set the argument information shown in the editor for this method, replacing the
+    /// information returned by `Method::method_args`.
+    #[inline]
+    pub fn with_args<I>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = MethodArg>,
+    {
+        self.args = Some(args.into_iter().collect());
+        self
+    }
+
+    /// Set the documentation shown for this method in the editor's help panel.
+    #[inline]
+    pub fn with_documentation(mut self, documentation: &'a str) -> Self {
+        self.documentation = Some(documentation);
+        self
+    }
+
     /// Register the method.
     #[inline]
     pub fn done(self) {
+        let args = self.args.unwrap_or_else(F::method_args);
         let method_data = Box::into_raw(Box::new(self.method));
 
         let script_method = ScriptMethod {
@@ -61,6 +88,13 @@ where
         };
 
         self.class_builder.add_method_advanced(script_method);
+        self.class_builder
+            .set_method_argument_information(self.name, &args);
+
+        if let Some(documentation) = self.documentation {
+            self.class_builder
+                .set_method_documentation(self.name, documentation);
+        }
     }
 }
 
@@ -74,6 +108,7 @@ where
     /// but can be used with any `Method` type with `Copy + Default`.
     #[inline]
     pub fn done_stateless(self) {
+        let args = self.args.unwrap_or_else(F::method_args);
         let script_method = ScriptMethod {
             name: self.name,
             method_ptr: Some(method_wrapper::<C, Stateless<F>>),
@@ -88,6 +123,13 @@ where
         };
 
         self.class_builder.add_method_advanced(script_method);
+        self.class_builder
+            .set_method_argument_information(self.name, &args);
+
+        if let Some(documentation) = self.documentation {
+            self.class_builder
+                .set_method_documentation(self.name, documentation);
+        }
     }
 }
 
@@ -102,6 +144,7 @@ pub type ScriptMethodFn = unsafe extern "C" fn(
     *mut *mut sys::godot_variant,
 ) -> sys::godot_variant;
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum RpcMode {
     Disabled,
     Remote,
@@ -119,6 +162,66 @@ impl Default for RpcMode {
     }
 }
 
+impl RpcMode {
+    #[inline]
+    pub(crate) fn sys(self) -> sys::godot_method_rpc_mode {
+        match self {
+            RpcMode::Master => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_MASTER,
+            RpcMode::Remote => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_REMOTE,
+            RpcMode::Puppet => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_PUPPET,
+            RpcMode::RemoteSync => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_REMOTESYNC,
+            RpcMode::Disabled => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_DISABLED,
+            RpcMode::MasterSync => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_MASTERSYNC,
+            RpcMode::PuppetSync => sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_PUPPETSYNC,
+        }
+    }
+}
+
+/// How errors returned from fallible exported methods are reported to the caller. In both
+/// cases, the error is also logged with `godot_error!`, along with the site of the method.
+///
+/// See the `err` option of `#[export]`. Exported methods returning a `Result` use `Nil` by
+/// default.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ErrorReturn {
+    /// Returns `null` to the caller.
+    Nil,
+    /// Returns a `Dictionary` to the caller, with the error message under the `"error"` key
+    /// and the site of the method under the `"site"` key.
+    Dict,
+}
+
+impl ErrorReturn {
+    /// Converts the return value of a fallible method to a `Variant`. `Ok` values are
+    /// returned as is, while errors are logged and reported according to `self`.
+    ///
+    /// `E` is usually a `std::error::Error`, but any `Display` type, like `Box<dyn Error>`,
+    /// can be used.
+    #[inline]
+    pub fn convert<T, E>(self, site: Site<'_>, result: Result<T, E>) -> Variant
+    where
+        T: OwnedToVariant,
+        E: fmt::Display,
+    {
+        let err = match result {
+            Ok(value) => return value.owned_to_variant(),
+            Err(err) => err,
+        };
+
+        crate::log::error(site, &err);
+
+        match self {
+            ErrorReturn::Nil => Variant::new(),
+            ErrorReturn::Dict => {
+                let dict = Dictionary::new();
+                dict.insert("error", err.to_string());
+                dict.insert("site", site.to_string());
+                dict.into_shared().to_variant()
+            }
+        }
+    }
+}
+
 #[deprecated(
     note = "Unsafe registration is deprecated. Use the safe, higher-level `MethodBuilder` API instead."
 )]
@@ -150,6 +253,14 @@ pub trait Method<C: NativeClass>: Send + Sync + 'static {
     fn site() -> Option<Site<'static>> {
         None
     }
+
+    /// Returns the names and types of the arguments of this method, to be shown in the editor.
+    ///
+    /// Default implementation returns an empty list, in which case no information is registered.
+    #[inline]
+    fn method_args() -> Vec<MethodArg> {
+        Vec::new()
+    }
 }
 
 /// Wrapper for stateless methods that produces values with `Copy` and `Default`.
@@ -192,6 +303,14 @@ pub trait StaticArgsMethod<C: NativeClass>: Send + Sync + 'static {
     fn site() -> Option<Site<'static>> {
         None
     }
+
+    /// Returns the names and types of the arguments of this method, to be shown in the editor.
+    ///
+    /// Default implementation returns an empty list, in which case no information is registered.
+    #[inline]
+    fn method_args() -> Vec<MethodArg> {
+        Vec::new()
+    }
 }
 
 impl<C: NativeClass, F: StaticArgsMethod<C>> Method<C> for StaticArgs<F> {
@@ -218,6 +337,87 @@ impl<C: NativeClass, F: StaticArgsMethod<C>> Method<C> for StaticArgs<F> {
     fn site() -> Option<Site<'static>> {
         F::site()
     }
+
+    #[inline]
+    fn method_args() -> Vec<MethodArg> {
+        F::method_args()
+    }
+}
+
+/// Name and type of a method argument, shown in the editor for autocompletion and in the
+/// documentation panel.
+#[derive(Debug)]
+pub struct MethodArg {
+    pub(super) name: Cow<'static, str>,
+    pub(super) export_info: ExportInfo,
+}
+
+impl MethodArg {
+    /// Creates argument information with the given name and type.
+    #[inline]
+    pub fn new<S: Into<Cow<'static, str>>>(name: S, export_info: ExportInfo) -> Self {
+        MethodArg {
+            name: name.into(),
+            export_info,
+        }
+    }
+
+    /// Creates argument information for a type implementing `Export`, without a hint.
+    #[inline]
+    pub fn of<T: Export, S: Into<Cow<'static, str>>>(name: S) -> Self {
+        Self::new(name, T::export_info(None))
+    }
+}
+
+/// Helper used by `godot_wrap_method!` to determine the `ExportInfo` of argument types.
+/// Types implementing `Export` use their own export information, and references to Godot
+/// objects are registered as `Object`. All other types are registered as `Nil`, which allows
+/// values of any type.
+#[doc(hidden)]
+pub struct ArgExportInfo<T>(PhantomData<T>);
+
+impl<T> ArgExportInfo<T> {
+    #[allow(clippy::new_without_default)]
+    #[inline]
+    pub fn new() -> Self {
+        ArgExportInfo(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait ExportArgInfo {
+    fn export_info(&self) -> ExportInfo;
+}
+
+impl<T: Export> ExportArgInfo for &&ArgExportInfo<T> {
+    #[inline]
+    fn export_info(&self) -> ExportInfo {
+        T::export_info(None)
+    }
+}
+
+#[doc(hidden)]
+pub trait ObjectArgInfo {
+    fn export_info(&self) -> ExportInfo;
+}
+
+impl<T: GodotObject, Access: ThreadAccess> ObjectArgInfo for &ArgExportInfo<Ref<T, Access>> {
+    #[inline]
+    fn export_info(&self) -> ExportInfo {
+        ExportInfo::new(VariantType::Object)
+    }
+}
+
+#[doc(hidden)]
+pub trait AnyArgInfo {
+    fn export_info(&self) -> ExportInfo;
+}
+
+impl<T> AnyArgInfo for ArgExportInfo<T> {
+    #[inline]
+    fn export_info(&self) -> ExportInfo {
+        ExportInfo::new(VariantType::Nil)
+    }
 }
 
 /// Safe interface to a list of borrowed method arguments with a convenient API
@@ -259,6 +459,13 @@ impl<'a> Varargs<'a> {
         T::read(self)
     }
 
+    /// Discards the last `n` arguments. Used to skip arguments bound to signal connections.
+    #[inline]
+    pub(crate) fn drop_last(&mut self, n: usize) {
+        let args = self.iter.as_slice();
+        self.iter = args[..args.len().saturating_sub(n)].iter();
+    }
+
     /// Returns the remaining arguments as a slice of `Variant`s.
     #[inline]
     pub fn as_slice(&self) -> &'a [&'a Variant] {
@@ -312,6 +519,7 @@ impl<'a> Iterator for Varargs<'a> {
 /// Trait for structures that can be parsed from `Varargs`.
 ///
 /// This trait can be derived for structure types where each type implements `FromVariant`.
+/// It's also implemented for tuples of up to 8 `FromVariant` types.
 /// The order of fields matter for this purpose:
 ///
 /// ```ignore
@@ -326,6 +534,35 @@ pub trait FromVarargs: Sized {
     fn read<'a>(args: &mut Varargs<'a>) -> Result<Self, Vec<ArgumentError<'a>>>;
 }
 
+macro_rules! impl_from_varargs_for_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: FromVariant),*> FromVarargs for ($($name,)*) {
+            #[inline]
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn read<'a>(args: &mut Varargs<'a>) -> Result<Self, Vec<ArgumentError<'a>>> {
+                let mut errors = Vec::new();
+                $(let $name = args.read::<$name>().get().map_err(|err| errors.push(err)).ok();)*
+
+                if !errors.is_empty() {
+                    return Err(errors);
+                }
+
+                Ok(($($name.unwrap(),)*))
+            }
+        }
+    };
+}
+
+impl_from_varargs_for_tuple!();
+impl_from_varargs_for_tuple!(A);
+impl_from_varargs_for_tuple!(A, B);
+impl_from_varargs_for_tuple!(A, B, C);
+impl_from_varargs_for_tuple!(A, B, C, D);
+impl_from_varargs_for_tuple!(A, B, C, D, E);
+impl_from_varargs_for_tuple!(A, B, C, D, E, F);
+impl_from_varargs_for_tuple!(A, B, C, D, E, F, G);
+impl_from_varargs_for_tuple!(A, B, C, D, E, F, G, H);
+
 /// Builder for providing additional argument information for error reporting.
 pub struct ArgBuilder<'r, 'a, T> {
     args: &'r mut Varargs<'a>,
@@ -579,6 +816,20 @@ unsafe extern "C" fn method_wrapper<C: NativeClass, F: Method<C>>(
         }
     };
 
+    let user_data = match crate::nativescript::class::resolve_user_data_ptr::<C>(this, user_data) {
+        Some(user_data) => user_data,
+        None => {
+            crate::log::error(
+                F::site().unwrap_or_default(),
+                format_args!(
+                    "gdnative-core: cannot access the {} part of the script instance (is it borrowed?)",
+                    C::class_name(),
+                ),
+            );
+            return Variant::new().forget();
+        }
+    };
+
     let result = std::panic::catch_unwind(move || {
         let method = &*(method_data as *const F);
 
@@ -588,15 +839,13 @@ unsafe extern "C" fn method_wrapper<C: NativeClass, F: Method<C>>(
 
         let args = Varargs::from_sys(num_args, args);
 
+        let _scope = crate::nativescript::user_data::diagnostics::enter_method(F::site());
         F::call(method, this, args)
     });
 
     result
-        .unwrap_or_else(|_| {
-            crate::log::error(
-                F::site().unwrap_or_default(),
-                "gdnative-core: method panicked (check stderr for output)",
-            );
+        .unwrap_or_else(|payload| {
+            crate::panic::handle_panic(C::class_name(), Callback::Method, F::site(), payload);
             Variant::new()
         })
         .forget()
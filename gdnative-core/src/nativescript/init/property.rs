@@ -7,11 +7,13 @@ use crate::object::Ref;
 use crate::private::get_api;
 use crate::thread_access::Shared;
 
-use super::ClassBuilder;
+use super::{ClassBuilder, RpcMode};
 
 mod accessor;
+pub mod dynamic;
 pub mod hint;
 
+pub use dynamic::{DynamicProperties, PropertyInfo};
 pub use hint::*;
 
 use accessor::{Getter, InvalidGetter, InvalidSetter, RawGetter, RawSetter, Setter};
@@ -74,6 +76,8 @@ pub struct PropertyBuilder<'a, C, T: Export, S = InvalidSetter<'a>, G = InvalidG
     default: Option<T>,
     hint: Option<T::Hint>,
     usage: Usage,
+    rpc_mode: RpcMode,
+    documentation: Option<&'a str>,
     class_builder: &'a ClassBuilder<C>,
 }
 
@@ -92,6 +96,8 @@ where
             default: None,
             hint: None,
             usage: Usage::DEFAULT,
+            rpc_mode: RpcMode::Disabled,
+            documentation: None,
             class_builder,
         }
     }
@@ -115,7 +121,7 @@ where
         let default = self.default.to_variant();
 
         let mut attr = sys::godot_property_attributes {
-            rset_type: sys::godot_method_rpc_mode_GODOT_METHOD_RPC_MODE_DISABLED, // TODO:
+            rset_type: self.rpc_mode.sys(),
             type_: variant_type as sys::godot_int,
             hint: hint_kind,
             hint_string: hint_string.to_sys(),
@@ -138,6 +144,18 @@ where
                 get,
             );
         }
+
+        if let Some(documentation) = self.documentation {
+            let documentation = GodotString::from_str(documentation);
+            unsafe {
+                (get_api().godot_nativescript_set_property_documentation)(
+                    self.class_builder.init_handle,
+                    self.class_builder.class_name.as_ptr(),
+                    path.as_ptr(),
+                    documentation.to_sys(),
+                );
+            }
+        }
     }
 
     /// Provides a setter function with the signature `fn(&mut C, owner: C::Base, value: T)`
@@ -157,6 +175,8 @@ where
             default: self.default,
             hint: self.hint,
             usage: self.usage,
+            rpc_mode: self.rpc_mode,
+            documentation: self.documentation,
             class_builder: self.class_builder,
         }
     }
@@ -178,6 +198,8 @@ where
             default: self.default,
             hint: self.hint,
             usage: self.usage,
+            rpc_mode: self.rpc_mode,
+            documentation: self.documentation,
             class_builder: self.class_builder,
         }
     }
@@ -199,6 +221,8 @@ where
             default: self.default,
             hint: self.hint,
             usage: self.usage,
+            rpc_mode: self.rpc_mode,
+            documentation: self.documentation,
             class_builder: self.class_builder,
         }
     }
@@ -220,6 +244,8 @@ where
             default: self.default,
             hint: self.hint,
             usage: self.usage,
+            rpc_mode: self.rpc_mode,
+            documentation: self.documentation,
             class_builder: self.class_builder,
         }
     }
@@ -241,6 +267,8 @@ where
             default: self.default,
             hint: self.hint,
             usage: self.usage,
+            rpc_mode: self.rpc_mode,
+            documentation: self.documentation,
             class_builder: self.class_builder,
         }
     }
@@ -262,6 +290,8 @@ where
             default: self.default,
             hint: self.hint,
             usage: self.usage,
+            rpc_mode: self.rpc_mode,
+            documentation: self.documentation,
             class_builder: self.class_builder,
         }
     }
@@ -287,6 +317,57 @@ where
         self.usage = usage;
         self
     }
+
+    /// Sets the RPC mode used when the property is set with `rset`.
+    #[inline]
+    pub fn with_rpc_mode(mut self, rpc_mode: RpcMode) -> Self {
+        self.rpc_mode = rpc_mode;
+        self
+    }
+
+    /// Sets the documentation shown for the property in the editor's help panel.
+    #[inline]
+    pub fn with_documentation(mut self, documentation: &'a str) -> Self {
+        self.documentation = Some(documentation);
+        self
+    }
+}
+
+/// Registers a pseudo-property that is used to organize the inspector, like groups and
+/// categories. The property has no valid accessors.
+pub(super) fn add_pseudo_property<C: NativeClass>(
+    class_builder: &ClassBuilder<C>,
+    name: &str,
+    hint_string: &str,
+    usage: Usage,
+) {
+    let hint_string = GodotString::from_str(hint_string);
+    let default = Variant::new();
+
+    let mut attr = sys::godot_property_attributes {
+        rset_type: RpcMode::Disabled.sys(),
+        type_: VariantType::Nil as sys::godot_int,
+        hint: sys::godot_property_hint_GODOT_PROPERTY_HINT_NONE,
+        hint_string: hint_string.to_sys(),
+        usage: usage.to_sys(),
+        default_value: default.to_sys(),
+    };
+
+    let path = ::std::ffi::CString::new(name).unwrap();
+
+    unsafe {
+        let set = RawSetter::<C, Variant>::into_godot_function(InvalidSetter::new(name));
+        let get = RawGetter::<C, Variant>::into_godot_function(InvalidGetter::new(name));
+
+        (get_api().godot_nativescript_register_property)(
+            class_builder.init_handle,
+            class_builder.class_name.as_ptr(),
+            path.as_ptr() as *const _,
+            &mut attr,
+            set,
+            get,
+        );
+    }
 }
 
 bitflags::bitflags! {
@@ -481,4 +562,25 @@ mod impl_export {
             hint.unwrap_or_default().export_info()
         }
     }
+
+    impl<T> Export for TypedVariantArray<T, Shared>
+    where
+        T: Export,
+    {
+        type Hint = T::Hint;
+
+        #[inline]
+        fn export_info(hint: Option<Self::Hint>) -> ExportInfo {
+            ArrayHint::with_maybe_element_hint::<T>(hint).export_info()
+        }
+    }
+
+    impl<K, V> Export for TypedDictionary<K, V, Shared> {
+        type Hint = NoHint;
+
+        #[inline]
+        fn export_info(_hint: Option<Self::Hint>) -> ExportInfo {
+            ExportInfo::new(VariantType::Dictionary)
+        }
+    }
 }
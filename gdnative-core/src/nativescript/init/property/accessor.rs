@@ -9,6 +9,7 @@ use crate::nativescript::Map;
 use crate::nativescript::MapMut;
 use crate::nativescript::NativeClass;
 use crate::object::{GodotObject, RawObject};
+use crate::panic::Callback;
 use crate::*;
 
 mod invalid;
@@ -242,6 +243,19 @@ where
                 }
             };
 
+            let class = match unsafe {
+                crate::nativescript::class::resolve_user_data_ptr::<C>(this, class)
+            } {
+                Some(class) => class,
+                None => {
+                    godot_error!(
+                        "gdnative-core: cannot access the {} part of the script instance (is it borrowed?)",
+                        C::class_name(),
+                    );
+                    return;
+                }
+            };
+
             let result = std::panic::catch_unwind(|| unsafe {
                 let user_data = C::UserData::clone_from_user_data_unchecked(class as *const _);
                 let owner = TRef::new(C::Base::cast_ref(RawObject::from_sys_ref_unchecked(this)));
@@ -259,8 +273,13 @@ where
                 }
             });
 
-            result.unwrap_or_else(|_| {
-                godot_error!("gdnative-core: property setter panicked (check stderr for output)");
+            result.unwrap_or_else(|payload| {
+                crate::panic::handle_panic(
+                    C::class_name(),
+                    Callback::PropertySetter,
+                    None,
+                    payload,
+                );
             })
         }
         set.set_func = Some(invoke::<SelfArg, C, F, T>);
@@ -317,6 +336,19 @@ where
                 }
             };
 
+            let class = match unsafe {
+                crate::nativescript::class::resolve_user_data_ptr::<C>(this, class)
+            } {
+                Some(class) => class,
+                None => {
+                    godot_error!(
+                        "gdnative-core: cannot access the {} part of the script instance (is it borrowed?)",
+                        C::class_name(),
+                    );
+                    return Variant::new().forget();
+                }
+            };
+
             let result = std::panic::catch_unwind(|| unsafe {
                 let user_data = C::UserData::clone_from_user_data_unchecked(class as *const _);
                 let owner = TRef::new(C::Base::cast_ref(RawObject::from_sys_ref_unchecked(this)));
@@ -331,8 +363,13 @@ where
                 }
             });
 
-            result.unwrap_or_else(|_| {
-                godot_error!("gdnative-core: property getter panicked (check stderr for output)");
+            result.unwrap_or_else(|payload| {
+                crate::panic::handle_panic(
+                    C::class_name(),
+                    Callback::PropertyGetter,
+                    None,
+                    payload,
+                );
                 Variant::new().forget()
             })
         }
@@ -0,0 +1,234 @@
+//! Properties that are only known at runtime, through the `_get_property_list`, `_get` and
+//! `_set` virtual methods.
+
+use std::marker::PhantomData;
+
+use crate::core_types::{Dictionary, GodotString, ToVariant, Variant, VariantArray};
+use crate::nativescript::init::method::{Method, Varargs};
+use crate::nativescript::init::ClassBuilder;
+use crate::nativescript::user_data::{Map, MapMut};
+use crate::nativescript::{NativeClass, RefInstance};
+use crate::object::TRef;
+use crate::thread_access::Shared;
+
+use super::{Export, ExportInfo, Usage};
+
+/// Trait for scripts with a set of properties that is only known at runtime, for example
+/// parameters loaded from data files.
+///
+/// Types implementing this trait with a `#[methods]` impl block get the `_get_property_list`,
+/// `_get` and `_set` methods registered automatically. Dynamic properties are shown in the
+/// inspector after the registered ones, and are only consulted for names that don't belong
+/// to registered properties.
+///
+/// The user data of the type must implement both `Map` and `MapMut`.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[derive(NativeClass)]
+/// #[inherit(Reference)]
+/// struct Material {
+///     params: HashMap<String, f64>,
+/// }
+///
+/// impl DynamicProperties for Material {
+///     fn property_list(&self, _owner: TRef<Reference>) -> Vec<PropertyInfo> {
+///         self.params
+///             .keys()
+///             .map(|name| PropertyInfo::of::<f64>(format!("params/{}", name), None))
+///             .collect()
+///     }
+///
+///     fn get_property(&self, _owner: TRef<Reference>, name: &str) -> Option<Variant> {
+///         let name = name.strip_prefix("params/")?;
+///         self.params.get(name).map(|value| value.to_variant())
+///     }
+///
+///     fn set_property(&mut self, _owner: TRef<Reference>, name: &str, value: Variant) -> bool {
+///         match (name.strip_prefix("params/"), value.try_to_f64()) {
+///             (Some(name), Some(value)) => self.params.insert(name.into(), value).is_some(),
+///             _ => false,
+///         }
+///     }
+/// }
+/// ```
+pub trait DynamicProperties: NativeClass
+where
+    Self::UserData: Map + MapMut,
+{
+    /// Returns the list of dynamic properties.
+    fn property_list(&self, owner: TRef<'_, Self::Base>) -> Vec<PropertyInfo>;
+
+    /// Returns the value of the property `name`, or `None` if it isn't a dynamic property.
+    fn get_property(&self, owner: TRef<'_, Self::Base>, name: &str) -> Option<Variant>;
+
+    /// Sets the property `name` to `value`. Returns `false` if it isn't a dynamic property,
+    /// or if `value` isn't valid for it.
+    fn set_property(&mut self, owner: TRef<'_, Self::Base>, name: &str, value: Variant) -> bool;
+}
+
+/// Description of a dynamic property, returned from `DynamicProperties::property_list`.
+#[derive(Debug)]
+pub struct PropertyInfo {
+    name: GodotString,
+    export_info: ExportInfo,
+    usage: Usage,
+}
+
+impl PropertyInfo {
+    /// Creates a property with the given name and type information, and the default usage.
+    #[inline]
+    pub fn new<S: Into<GodotString>>(name: S, export_info: ExportInfo) -> Self {
+        PropertyInfo {
+            name: name.into(),
+            export_info,
+            usage: Usage::DEFAULT,
+        }
+    }
+
+    /// Creates a property of a type implementing `Export`, with an optional hint.
+    #[inline]
+    pub fn of<T: Export, S: Into<GodotString>>(name: S, hint: Option<T::Hint>) -> Self {
+        Self::new(name, T::export_info(hint))
+    }
+
+    /// Sets the usage flags of the property.
+    #[inline]
+    pub fn with_usage(mut self, usage: Usage) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    fn to_dictionary(&self) -> Dictionary<Shared> {
+        let dict = Dictionary::new();
+        dict.insert("name", &self.name);
+        dict.insert("type", self.export_info.variant_type as i64);
+        dict.insert("hint", self.export_info.hint_kind as i64);
+        dict.insert("hint_string", &self.export_info.hint_string);
+        dict.insert("usage", self.usage.bits() as i64);
+        dict.into_shared()
+    }
+}
+
+/// Helper used by `#[methods]` to register the virtual methods for `DynamicProperties`, only
+/// if the type implements it.
+#[doc(hidden)]
+pub struct DynamicPropertiesProbe<C>(PhantomData<C>);
+
+impl<C> DynamicPropertiesProbe<C> {
+    #[allow(clippy::new_without_default)]
+    #[inline]
+    pub fn new() -> Self {
+        DynamicPropertiesProbe(PhantomData)
+    }
+}
+
+#[doc(hidden)]
+pub trait RegisterDynamicProperties<C> {
+    fn register(&self, builder: &ClassBuilder<C>);
+}
+
+impl<C> RegisterDynamicProperties<C> for &DynamicPropertiesProbe<C>
+where
+    C: DynamicProperties,
+    C::UserData: Map + MapMut,
+{
+    #[inline]
+    fn register(&self, builder: &ClassBuilder<C>) {
+        builder
+            .build_method("_get_property_list", GetPropertyList)
+            .done_stateless();
+        builder.build_method("_get", Get).done_stateless();
+        builder.build_method("_set", Set).done_stateless();
+    }
+}
+
+#[doc(hidden)]
+pub trait RegisterNoDynamicProperties<C> {
+    fn register(&self, builder: &ClassBuilder<C>);
+}
+
+impl<C> RegisterNoDynamicProperties<C> for DynamicPropertiesProbe<C> {
+    #[inline]
+    fn register(&self, _builder: &ClassBuilder<C>) {}
+}
+
+#[derive(Copy, Clone, Default)]
+struct GetPropertyList;
+
+impl<C> Method<C> for GetPropertyList
+where
+    C: DynamicProperties,
+    C::UserData: Map + MapMut,
+{
+    fn call(&self, this: RefInstance<'_, C, Shared>, _args: Varargs<'_>) -> Variant {
+        let list = VariantArray::new();
+
+        let result = this.map(|script, owner| {
+            for property in script.property_list(owner) {
+                list.push(property.to_dictionary());
+            }
+        });
+
+        if let Err(err) = result {
+            godot_error!("gdnative-core: cannot list dynamic properties: {:?}", err);
+        }
+
+        list.into_shared().to_variant()
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct Get;
+
+impl<C> Method<C> for Get
+where
+    C: DynamicProperties,
+    C::UserData: Map + MapMut,
+{
+    fn call(&self, this: RefInstance<'_, C, Shared>, mut args: Varargs<'_>) -> Variant {
+        let name = match args.read::<GodotString>().get() {
+            Ok(name) => name.to_string(),
+            Err(err) => {
+                err.log_error();
+                return Variant::new();
+            }
+        };
+
+        this.map(|script, owner| script.get_property(owner, &name))
+            .unwrap_or_else(|err| {
+                godot_error!("gdnative-core: cannot get dynamic property: {:?}", err);
+                None
+            })
+            .unwrap_or_else(Variant::new)
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct Set;
+
+impl<C> Method<C> for Set
+where
+    C: DynamicProperties,
+    C::UserData: Map + MapMut,
+{
+    fn call(&self, this: RefInstance<'_, C, Shared>, mut args: Varargs<'_>) -> Variant {
+        let (name, value) = match args.read_many::<(GodotString, Variant)>() {
+            Ok((name, value)) => (name.to_string(), value),
+            Err(errors) => {
+                for err in errors {
+                    err.log_error();
+                }
+                return false.to_variant();
+            }
+        };
+
+        this.map_mut(|script, owner| script.set_property(owner, &name, value))
+            .unwrap_or_else(|err| {
+                godot_error!("gdnative-core: cannot set dynamic property: {:?}", err);
+                false
+            })
+            .to_variant()
+    }
+}
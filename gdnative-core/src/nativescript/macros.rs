@@ -13,20 +13,37 @@
 ///
 /// Overriding the default entry point names can be useful if several gdnative
 /// libraries are linked statically  to avoid name clashes.
+///
+/// With the `inventory` feature, `auto` can be used in place of the callback to register
+/// all classes with a `#[methods]` impl block automatically:
+///
+/// ```ignore
+/// godot_nativescript_init!(auto);
+/// ```
 #[macro_export]
 macro_rules! godot_nativescript_init {
-    () => {
-        fn godot_nativescript_init_empty(_init: $crate::nativescript::init::InitHandle) {}
-        $crate::godot_nativescript_init!(godot_nativescript_init_empty);
+    // The `@hook` forms are used by crates wrapping this macro, to run `$hook` after the
+    // internal classes are registered and before `$callback` is called.
+    (@hook $hook:block) => {
+        $crate::godot_nativescript_init!(@hook $hook _ as godot_nativescript_init);
     };
-    ($callback:ident) => {
-        $crate::godot_nativescript_init!($callback as godot_nativescript_init);
+    (@hook $hook:block auto) => {
+        $crate::godot_nativescript_init!(@hook $hook auto as godot_nativescript_init);
     };
-    (_ as $fn_name:ident) => {
+    (@hook $hook:block auto as $fn_name:ident) => {
+        fn godot_nativescript_init_auto(init: $crate::nativescript::init::InitHandle) {
+            init.add_auto_registered_classes();
+        }
+        $crate::godot_nativescript_init!(@hook $hook godot_nativescript_init_auto as $fn_name);
+    };
+    (@hook $hook:block _ as $fn_name:ident) => {
         fn godot_nativescript_init_empty(_init: $crate::nativescript::init::InitHandle) {}
-        $crate::godot_nativescript_init!(godot_nativescript_init_empty as $fn_name);
+        $crate::godot_nativescript_init!(@hook $hook godot_nativescript_init_empty as $fn_name);
+    };
+    (@hook $hook:block $callback:ident) => {
+        $crate::godot_nativescript_init!(@hook $hook $callback as godot_nativescript_init);
     };
-    ($callback:ident as $fn_name:ident) => {
+    (@hook $hook:block $callback:ident as $fn_name:ident) => {
         #[no_mangle]
         #[doc(hidden)]
         #[allow(unused_unsafe)]
@@ -36,7 +53,10 @@ macro_rules! godot_nativescript_init {
             }
 
             let __result = ::std::panic::catch_unwind(|| {
-                $callback($crate::nativescript::init::InitHandle::new(handle));
+                let handle = $crate::nativescript::init::InitHandle::new(handle);
+                handle.add_internal_classes();
+                $hook
+                $callback(handle);
             });
 
             if __result.is_err() {
@@ -44,6 +64,30 @@ macro_rules! godot_nativescript_init {
             }
         }
     };
+    ($($args:tt)*) => {
+        $crate::godot_nativescript_init!(@hook {} $($args)*);
+    };
+}
+
+/// Submits `$register`, a `fn(InitHandle)`, to the list of classes registered by
+/// `InitHandle::add_auto_registered_classes`. Does nothing without the `inventory` feature.
+#[cfg(feature = "inventory")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! godot_submit_class {
+    ($register:expr) => {
+        $crate::private::inventory::submit! {
+            #![crate = $crate::private]
+            $crate::private::AutoRegisteredClass { register: $register }
+        }
+    };
+}
+
+#[cfg(not(feature = "inventory"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! godot_submit_class {
+    ($register:expr) => {};
 }
 
 /// Declare all the API endpoints necessary to initialize a NativeScript library.
@@ -60,8 +104,17 @@ macro_rules! godot_nativescript_init {
 /// `godot_nativescript_init`, and `godot_gdnative_terminate`. If you need different prefixes
 /// to avoid name clashes when multiple GDNative libraries are linked statically, please use
 /// the respective macros directly.
+///
+/// With the `inventory` feature, `godot_init!(auto)` registers all classes with a
+/// `#[methods]` impl block without an init callback. See
+/// [`InitHandle::add_auto_registered_classes`](nativescript/init/struct.InitHandle.html#method.add_auto_registered_classes).
 #[macro_export]
 macro_rules! godot_init {
+    (auto) => {
+        $crate::godot_gdnative_init!();
+        $crate::godot_nativescript_init!(auto);
+        $crate::godot_gdnative_terminate!();
+    };
     ($callback:ident) => {
         $crate::godot_gdnative_init!();
         $crate::godot_nativescript_init!($callback);
@@ -86,6 +139,7 @@ macro_rules! godot_wrap_method_inner {
     (
         $type_name:ty,
         $map_method:ident,
+        $(#[err = $err:ident])?
         fn $method_name:ident(
             $self:ident,
             $owner:ident : $owner_ty:ty
@@ -124,7 +178,11 @@ macro_rules! godot_wrap_method_inner {
                                     $($pname,)*
                                     $($opt_pname,)*
                                 );
-                                gdnative::core_types::OwnedToVariant::owned_to_variant(ret)
+                                $crate::godot_wrap_method_return!(
+                                    ret,
+                                    $crate::godot_site!($type_name::$method_name)
+                                    $(, $err)?
+                                )
                             }
                         })
                         .unwrap_or_else(|err| {
@@ -137,6 +195,23 @@ macro_rules! godot_wrap_method_inner {
                 fn site() -> Option<$crate::log::Site<'static>> {
                     Some($crate::godot_site!($type_name::$method_name))
                 }
+
+                fn method_args() -> Vec<$crate::nativescript::init::method::MethodArg> {
+                    use $crate::nativescript::init::method::{
+                        AnyArgInfo, ArgExportInfo, ExportArgInfo, MethodArg, ObjectArgInfo,
+                    };
+
+                    vec![
+                        $(MethodArg::new(
+                            stringify!($pname).trim_start_matches('_'),
+                            (&&&ArgExportInfo::<$pty>::new()).export_info(),
+                        ),)*
+                        $(MethodArg::new(
+                            stringify!($opt_pname).trim_start_matches('_'),
+                            (&&&ArgExportInfo::<$opt_pty>::new()).export_info(),
+                        ),)*
+                    ]
+                }
             }
 
             $crate::nativescript::init::method::StaticArgs::new(ThisMethod)
@@ -144,13 +219,33 @@ macro_rules! godot_wrap_method_inner {
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! godot_wrap_method_return {
+    ($ret:expr, $site:expr) => {
+        $crate::core_types::OwnedToVariant::owned_to_variant($ret)
+    };
+    ($ret:expr, $site:expr, $err:ident) => {
+        $crate::nativescript::init::method::ErrorReturn::$err.convert($site, $ret)
+    };
+}
+
 /// Convenience macro to wrap an object's method into a function pointer
 /// that can be passed to the engine when registering a class.
+///
+/// Methods returning `Result` can be prefixed with `#[err = Nil]` or `#[err = Dict]` to
+/// log errors and report them to the caller according to `ErrorReturn`, instead of
+/// converting the `Result` itself:
+///
+/// ```ignore
+/// godot_wrap_method!(MyClass, #[err = Nil] fn load(&self, owner: &Node, path: String) -> Result<i64, io::Error>)
+/// ```
 #[macro_export]
 macro_rules! godot_wrap_method {
     // mutable
     (
         $type_name:ty,
+        $(#[err = $err:ident])?
         fn $method_name:ident(
             &mut $self:ident,
             $owner:ident : $owner_ty:ty
@@ -162,6 +257,7 @@ macro_rules! godot_wrap_method {
         $crate::godot_wrap_method_inner!(
             $type_name,
             map_mut,
+            $(#[err = $err])?
             fn $method_name(
                 $self,
                 $owner: $owner_ty
@@ -173,6 +269,7 @@ macro_rules! godot_wrap_method {
     // immutable
     (
         $type_name:ty,
+        $(#[err = $err:ident])?
         fn $method_name:ident(
             & $self:ident,
             $owner:ident : $owner_ty:ty
@@ -184,6 +281,7 @@ macro_rules! godot_wrap_method {
         $crate::godot_wrap_method_inner!(
             $type_name,
             map,
+            $(#[err = $err])?
             fn $method_name(
                 $self,
                 $owner: $owner_ty
@@ -195,6 +293,7 @@ macro_rules! godot_wrap_method {
     // owned
     (
         $type_name:ty,
+        $(#[err = $err:ident])?
         fn $method_name:ident(
             mut $self:ident,
             $owner:ident : $owner_ty:ty
@@ -206,6 +305,7 @@ macro_rules! godot_wrap_method {
         $crate::godot_wrap_method_inner!(
             $type_name,
             map_owned,
+            $(#[err = $err])?
             fn $method_name(
                 $self,
                 $owner: $owner_ty
@@ -217,6 +317,7 @@ macro_rules! godot_wrap_method {
     // owned
     (
         $type_name:ty,
+        $(#[err = $err:ident])?
         fn $method_name:ident(
             $self:ident,
             $owner:ident : $owner_ty:ty
@@ -228,6 +329,7 @@ macro_rules! godot_wrap_method {
         $crate::godot_wrap_method_inner!(
             $type_name,
             map_owned,
+            $(#[err = $err])?
             fn $method_name(
                 $self,
                 $owner: $owner_ty
@@ -4,12 +4,16 @@ pub(crate) mod class_registry;
 mod emplace;
 mod macros;
 
+pub mod binding;
 pub mod class;
 pub mod init;
 pub mod profiling;
+pub mod signal;
+pub mod task;
 pub mod type_tag;
 pub mod user_data;
 
+pub use binding::InstanceBinding;
 pub use class::*;
 pub use init::*;
 pub use user_data::{Map, MapMut, MapOwned, UserData};
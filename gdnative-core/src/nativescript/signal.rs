@@ -0,0 +1,173 @@
+//! Connecting signals to Rust closures.
+
+use std::ptr::NonNull;
+
+use crate::core_types::{GodotError, GodotString, ToVariant, Variant, VariantArray};
+use crate::nativescript::init::method::{Method, Varargs};
+use crate::nativescript::init::ClassBuilder;
+use crate::nativescript::user_data::LocalCellData;
+use crate::nativescript::{Instance, NativeClass, NativeClassMethods, RefInstance};
+use crate::object::GodotObject;
+use crate::private::{get_api, ObjectMethodTable, ReferenceCountedClassPlaceholder};
+use crate::sys;
+use crate::thread_access::Shared;
+
+/// Name of the method that calls the closure.
+const INVOKE: &str = "invoke";
+
+/// Connects `signal` of `owner` to a Rust closure, which is called with the arguments of the
+/// signal every time it's emitted.
+///
+/// The closure is owned by the connection. It's dropped when the connection is removed, which
+/// happens automatically when `owner` is freed. As a result, if the closure holds a reference
+/// to a reference-counted `owner`, neither of them will ever be freed.
+///
+/// Must be called after the library is initialized.
+///
+/// # Errors
+///
+/// If the connection fails, for example when `owner` doesn't have a signal named `signal`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let button = Button::new();
+/// connect_fn(&*button, "pressed", move |_args| {
+///     godot_print!("button pressed");
+/// })?;
+/// ```
+pub fn connect_fn<T, F>(owner: &T, signal: &str, f: F) -> Result<(), GodotError>
+where
+    T: GodotObject,
+    F: FnMut(Varargs<'_>) + 'static,
+{
+    unsafe { connect_sys(owner.as_raw().sys(), signal, 0, f) }
+}
+
+/// Connects `signal` of the object at `owner` to a Rust closure, with the given connection
+/// `flags`.
+///
+/// # Safety
+///
+/// `owner` must point to a valid object.
+pub(crate) unsafe fn connect_sys<F>(
+    owner: NonNull<sys::godot_object>,
+    signal: &str,
+    flags: i64,
+    f: F,
+) -> Result<(), GodotError>
+where
+    F: FnMut(Varargs<'_>) + 'static,
+{
+    let closure = Instance::emplace(SignalClosure { f: Box::new(f) }).into_shared();
+    let target = closure.base().to_variant();
+
+    // The closure object is bound as the last argument of the connection, so it stays alive
+    // for as long as the connection exists.
+    let binds = VariantArray::new();
+    binds.push(&target);
+
+    let args = [
+        GodotString::from(signal).to_variant(),
+        target,
+        GodotString::from(INVOKE).to_variant(),
+        binds.into_shared().to_variant(),
+        Variant::from_i64(flags),
+    ];
+
+    let api = get_api();
+    let connect = ObjectMethodTable::get(api).connect;
+
+    let mut arg_ptrs = [
+        args[0].sys(),
+        args[1].sys(),
+        args[2].sys(),
+        args[3].sys(),
+        args[4].sys(),
+    ];
+
+    let ret = (api.godot_method_bind_call)(
+        connect,
+        owner.as_ptr(),
+        arg_ptrs.as_mut_ptr(),
+        arg_ptrs.len() as libc::c_int,
+        std::ptr::null_mut(),
+    );
+
+    let ret = Variant::from_sys(ret);
+    GodotError::result_from_sys(ret.to_i64() as sys::godot_error)
+}
+
+/// Calls `f` once on the main thread, the next time the engine flushes its message queue.
+///
+/// Must be called after the library is initialized.
+pub(crate) fn call_deferred_fn<F>(f: F)
+where
+    F: FnOnce() + 'static,
+{
+    let mut f = Some(f);
+    let closure = Instance::emplace(SignalClosure {
+        f: Box::new(move |_| {
+            if let Some(f) = f.take() {
+                f();
+            }
+        }),
+    })
+    .into_shared();
+
+    // The closure object is passed as the last argument, so it stays alive until it's called.
+    let target = closure.base().to_variant();
+    let args = [GodotString::from(INVOKE).to_variant(), target];
+    let mut arg_ptrs = [args[0].sys(), args[1].sys()];
+
+    unsafe {
+        let api = get_api();
+        let ret = (api.godot_method_bind_call)(
+            ObjectMethodTable::get(api).call_deferred,
+            closure.base().as_ptr(),
+            arg_ptrs.as_mut_ptr(),
+            arg_ptrs.len() as libc::c_int,
+            std::ptr::null_mut(),
+        );
+
+        drop(Variant::from_sys(ret));
+    }
+}
+
+/// Script class holding a closure connected with `connect_fn` or called with
+/// `call_deferred_fn`.
+pub(crate) struct SignalClosure {
+    f: Box<dyn FnMut(Varargs<'_>)>,
+}
+
+impl NativeClass for SignalClosure {
+    type Base = ReferenceCountedClassPlaceholder;
+    type UserData = LocalCellData<SignalClosure>;
+
+    fn class_name() -> &'static str {
+        "__GdnativeSignalClosure"
+    }
+}
+
+impl NativeClassMethods for SignalClosure {
+    fn register(builder: &ClassBuilder<Self>) {
+        builder.build_method(INVOKE, Invoke).done_stateless();
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct Invoke;
+
+impl Method<SignalClosure> for Invoke {
+    fn call(&self, this: RefInstance<'_, SignalClosure, Shared>, mut args: Varargs<'_>) -> Variant {
+        // The last argument is the closure object itself, bound in `connect_fn` or passed in
+        // `call_deferred_fn`.
+        args.drop_last(1);
+
+        if let Err(err) = this.map_mut(|closure, _| (closure.f)(args)) {
+            godot_error!("gdnative-core: cannot call signal closure: {}", err);
+        }
+
+        Variant::new()
+    }
+}
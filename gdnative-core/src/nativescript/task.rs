@@ -0,0 +1,354 @@
+//! Running futures on the main thread, driven by the engine's idle frames.
+//!
+//! Tasks can wait for signals with [`AwaitSignal::signal`], which makes it possible to write
+//! sequences of events, like `yield` in GDScript:
+//!
+//! ```ignore
+//! #[export]
+//! fn _ready(&self, owner: TRef<Node>) {
+//!     let animation = owner.get_node_as::<AnimationPlayer>("AnimationPlayer").unwrap();
+//!     let timer = owner.get_node_as::<Timer>("Timer").unwrap();
+//!
+//!     spawn_local(&*owner, async move {
+//!         animation.play("open", -1.0, 1.0, false);
+//!         let (_name,): (GodotString,) = animation.signal("animation_finished").await;
+//!
+//!         timer.start(1.0);
+//!         let () = timer.signal("timeout").await;
+//!
+//!         godot_print!("done");
+//!     });
+//! }
+//! ```
+//!
+//! Tasks are polled on the main thread, when the `SceneTree` emits `idle_frame`. All
+//! functions in this module must be called on the main thread.
+//!
+//! Tasks spawned before the main loop is created, for example in the init callback, start
+//! once the engine flushes its message queue for the first time. If no `SceneTree` exists by
+//! then, an error is logged, and they only start when another task is spawned after the
+//! `SceneTree` is created.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use indexmap::IndexSet;
+use parking_lot::Mutex;
+
+use crate::nativescript::init::method::FromVarargs;
+use crate::nativescript::signal::{call_deferred_fn, connect_sys};
+use crate::object::GodotObject;
+use crate::private::{get_api, EngineMethodTable, ObjectMethodTable};
+use crate::sys;
+
+/// `Object.CONNECT_ONESHOT`.
+const CONNECT_ONESHOT: i64 = 4;
+
+/// Spawns a task that runs `future` to completion on the main thread.
+///
+/// The task is tied to the lifetime of `owner`: it's cancelled, dropping `future`, if `owner`
+/// is freed before the task completes.
+///
+/// # Panics
+///
+/// If the API isn't initialized.
+pub fn spawn_local<T, F>(owner: &T, future: F)
+where
+    T: GodotObject,
+    F: Future<Output = ()> + 'static,
+{
+    let owner_id = unsafe { instance_id(owner.as_raw().sys()) };
+
+    EXECUTOR.with(|executor| {
+        let mut executor = executor.borrow_mut();
+        executor.spawn(Task {
+            owner_id,
+            future: Box::pin(future),
+        });
+
+        if !executor.is_driven {
+            executor.is_driven = connect_driver(true);
+        }
+    });
+}
+
+/// Extension trait for waiting for signals in tasks. See [`spawn_local`].
+pub trait AwaitSignal: GodotObject {
+    /// Returns a future that resolves with the arguments of the next emission of `signal`.
+    ///
+    /// The connection is made immediately, so emissions between this call and the first poll
+    /// of the future aren't missed. Excess arguments are ignored, so `()` can be used to wait
+    /// for any signal regardless of its arguments.
+    ///
+    /// If the arguments can't be converted to `A`, or if `self` is freed before `signal` is
+    /// emitted, the future never resolves.
+    fn signal<A>(&self, signal: &str) -> SignalFuture<A>
+    where
+        A: FromVarargs + 'static,
+    {
+        let state = Rc::new(RefCell::new(SignalState {
+            value: None,
+            waker: None,
+        }));
+
+        let closure_state = state.clone();
+        let result = unsafe {
+            connect_sys(
+                self.as_raw().sys(),
+                signal,
+                CONNECT_ONESHOT,
+                move |mut args| match args.read_many::<A>() {
+                    Ok(value) => {
+                        let waker = {
+                            let mut state = closure_state.borrow_mut();
+                            state.value = Some(value);
+                            state.waker.take()
+                        };
+
+                        if let Some(waker) = waker {
+                            waker.wake();
+                        }
+                    }
+                    Err(errors) => {
+                        for err in errors {
+                            err.log_error();
+                        }
+                    }
+                },
+            )
+        };
+
+        if let Err(err) = result {
+            godot_error!(
+                "gdnative-core: cannot connect to signal `{}`: {}",
+                signal,
+                err
+            );
+        }
+
+        SignalFuture { state }
+    }
+}
+
+impl<T: GodotObject> AwaitSignal for T {}
+
+/// Future returned by [`AwaitSignal::signal`].
+pub struct SignalFuture<A> {
+    state: Rc<RefCell<SignalState<A>>>,
+}
+
+struct SignalState<A> {
+    value: Option<A>,
+    waker: Option<Waker>,
+}
+
+impl<A> Future for SignalFuture<A> {
+    type Output = A;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<A> {
+        let mut state = self.state.borrow_mut();
+        match state.value.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+type TaskId = usize;
+
+struct Task {
+    owner_id: i64,
+    future: Pin<Box<dyn Future<Output = ()>>>,
+}
+
+impl Task {
+    fn is_owner_alive(&self) -> bool {
+        unsafe { !(get_api().godot_instance_from_id)(self.owner_id as sys::godot_int).is_null() }
+    }
+}
+
+#[derive(Default)]
+struct Executor {
+    tasks: HashMap<TaskId, Task>,
+    next_id: TaskId,
+    ready: Arc<Mutex<IndexSet<TaskId>>>,
+    is_driven: bool,
+}
+
+impl Executor {
+    fn spawn(&mut self, task: Task) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tasks.insert(id, task);
+        self.ready.lock().insert(id);
+    }
+}
+
+thread_local! {
+    static EXECUTOR: RefCell<Executor> = RefCell::new(Executor::default());
+}
+
+/// Polls the tasks that have been woken since the last call. Tasks are taken out of the
+/// executor while polled, so they can spawn other tasks.
+fn poll_tasks() {
+    let (ready, queue, cancelled) = EXECUTOR.with(|executor| {
+        let mut executor = executor.borrow_mut();
+
+        let cancelled = executor
+            .tasks
+            .iter()
+            .filter(|(_, task)| !task.is_owner_alive())
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+
+        let cancelled = cancelled
+            .into_iter()
+            .filter_map(|id| executor.tasks.remove(&id))
+            .collect::<Vec<_>>();
+
+        let ready = std::mem::take(&mut *executor.ready.lock());
+        (ready, executor.ready.clone(), cancelled)
+    });
+
+    // Dropping the futures of cancelled tasks may run arbitrary code.
+    drop(cancelled);
+
+    for id in ready {
+        let task = EXECUTOR.with(|executor| executor.borrow_mut().tasks.remove(&id));
+        let mut task = match task {
+            Some(task) if task.is_owner_alive() => task,
+            _ => continue,
+        };
+
+        let waker = task_waker(id, queue.clone());
+        let mut cx = Context::from_waker(&waker);
+        if task.future.as_mut().poll(&mut cx).is_pending() {
+            EXECUTOR.with(|executor| executor.borrow_mut().tasks.insert(id, task));
+        }
+    }
+}
+
+/// Connects `poll_tasks` to the `idle_frame` signal of the main loop. Returns `true` on
+/// success.
+///
+/// If `retry` is set and the main loop isn't created yet, this is tried again once, when the
+/// engine flushes its message queue. The retry isn't repeated, since messages queued during
+/// a flush are processed by the same flush.
+fn connect_driver(retry: bool) -> bool {
+    unsafe {
+        let api = get_api();
+        let engine = (api.godot_global_get_singleton)(b"Engine\0".as_ptr() as *mut _);
+
+        let mut main_loop: *mut sys::godot_object = std::ptr::null_mut();
+        let mut args: [*const libc::c_void; 0] = [];
+        (api.godot_method_bind_ptrcall)(
+            EngineMethodTable::get(api).get_main_loop,
+            engine,
+            args.as_mut_ptr(),
+            &mut main_loop as *mut _ as *mut libc::c_void,
+        );
+
+        let main_loop = match NonNull::new(main_loop) {
+            Some(main_loop) => main_loop,
+            None if retry => {
+                call_deferred_fn(|| {
+                    EXECUTOR.with(|executor| {
+                        let mut executor = executor.borrow_mut();
+                        if !executor.is_driven {
+                            executor.is_driven = connect_driver(false);
+                        }
+                    })
+                });
+                return false;
+            }
+            None => {
+                godot_error!("gdnative-core: cannot run tasks before the main loop is created");
+                return false;
+            }
+        };
+
+        match connect_sys(main_loop, "idle_frame", 0, |_| poll_tasks()) {
+            Ok(()) => true,
+            Err(err) => {
+                godot_error!(
+                    "gdnative-core: cannot run tasks without a SceneTree main loop: {}",
+                    err
+                );
+                false
+            }
+        }
+    }
+}
+
+/// Returns the instance ID of the object at `obj`.
+///
+/// # Safety
+///
+/// `obj` must point to a valid object.
+pub(crate) unsafe fn instance_id(obj: NonNull<sys::godot_object>) -> i64 {
+    let api = get_api();
+    let mut id: i64 = 0;
+    let mut args: [*const libc::c_void; 0] = [];
+    (api.godot_method_bind_ptrcall)(
+        ObjectMethodTable::get(api).get_instance_id,
+        obj.as_ptr(),
+        args.as_mut_ptr(),
+        &mut id as *mut _ as *mut libc::c_void,
+    );
+    id
+}
+
+/// Waker that marks a task as ready. Can be sent to other threads.
+struct TaskWaker {
+    id: TaskId,
+    ready: Arc<Mutex<IndexSet<TaskId>>>,
+}
+
+fn task_waker(id: TaskId, ready: Arc<Mutex<IndexSet<TaskId>>>) -> Waker {
+    let data = Arc::into_raw(Arc::new(TaskWaker { id, ready })) as *const ();
+    unsafe { Waker::from_raw(RawWaker::new(data, &VTABLE)) }
+}
+
+static VTABLE: RawWakerVTable = RawWakerVTable::new(clone_waker, wake, wake_by_ref, drop_waker);
+
+unsafe fn clone_waker(data: *const ()) -> RawWaker {
+    let waker = Arc::from_raw(data as *const TaskWaker);
+    let cloned = Arc::into_raw(waker.clone());
+    std::mem::forget(waker);
+    RawWaker::new(cloned as *const (), &VTABLE)
+}
+
+unsafe fn wake(data: *const ()) {
+    wake_by_ref(data);
+    drop_waker(data);
+}
+
+unsafe fn wake_by_ref(data: *const ()) {
+    let waker = &*(data as *const TaskWaker);
+    waker.ready.lock().insert(waker.id);
+}
+
+unsafe fn drop_waker(data: *const ()) {
+    drop(Arc::from_raw(data as *const TaskWaker));
+}
+
+/// Drops all pending tasks. Called during `terminate`, while the API is still bound.
+pub(crate) fn cleanup() {
+    let tasks = EXECUTOR.with(|executor| {
+        let mut executor = executor.borrow_mut();
+        executor.is_driven = false;
+        std::mem::take(&mut executor.tasks)
+    });
+
+    drop(tasks);
+}
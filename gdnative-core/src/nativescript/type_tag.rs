@@ -78,6 +78,21 @@ where
     }
 }
 
+/// Returns the `TypeId` of the type `tag` was created for. `tag` must be one returned by `create`.
+#[inline]
+pub(crate) unsafe fn type_id(tag: *const libc::c_void) -> TypeId {
+    if USE_TRANSMUTE {
+        // Safety: USE_TRANSMUTE is only true if layouts match
+        (*(&tag as *const *const libc::c_void as *const Tag)).type_id
+    } else {
+        let tags = TAGS.as_ref().expect("tag should be created by `create`");
+        let idx = tag as usize;
+        tags.get_index(idx & MAGIC_MASK)
+            .expect("tag should be created by `create`")
+            .type_id
+    }
+}
+
 /// Perform any cleanup actions if required. Should only be called from
 /// `crate::cleanup_internal_state`. `create` and `check` shouldn't be called after this.
 #[inline]
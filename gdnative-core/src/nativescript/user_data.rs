@@ -52,6 +52,13 @@
 //! - All your exported methods take `&self`.
 //! - Your `NativeClass` type is `Send + Sync`.
 //!
+//! ### Use a `AtomicCellData<T>` when:
+//!
+//! - You want `&mut self` methods to be callable from any thread, without the risk of
+//!   blocking or deadlocks.
+//! - You'd rather have conflicting calls fail immediately than wait for each other.
+//! - Your `NativeClass` type is `Send + Sync`.
+//!
 //! ### Use a `LocalCellData<T>` when:
 //!
 //! - Your `NativeClass` type is not `Send`, and you will only ever use it from the thread where
@@ -61,6 +68,11 @@
 //!
 //! - Your `NativeClass` type is a zero-sized type (ZST) that is `Copy + Default`.
 //! - You don't need to do anything special in `Drop`.
+//!
+//! ## Diagnosing lock contention
+//!
+//! `MutexData` and `RwLockData` can record who holds their locks, how long acquisitions wait,
+//! and how often they fail. See the `diagnostics` module for details.
 
 use parking_lot::{Mutex, RwLock};
 use std::fmt::{self, Debug, Display};
@@ -71,6 +83,8 @@ use std::time::Duration;
 
 use crate::nativescript::NativeClass;
 
+pub mod diagnostics;
+
 /// Trait for customizable user-data wrappers.
 ///
 /// See module-level documentation for detailed explanation on user-data.
@@ -317,14 +331,18 @@ where
     where
         F: FnOnce(&mut T) -> U,
     {
-        let mut guard = match OPT::DEADLOCK_POLICY {
-            DeadlockPolicy::Allow => self.lock.lock(),
-            DeadlockPolicy::Pessimistic => self.lock.try_lock().ok_or(LockFailed::Pessimistic)?,
-            DeadlockPolicy::Timeout(dur) => self
-                .lock
-                .try_lock_for(dur)
-                .ok_or(LockFailed::Timeout(dur))?,
-        };
+        let (mut guard, _held) = diagnostics::acquire(
+            T::class_name(),
+            Arc::as_ptr(&self.lock) as *const (),
+            diagnostics::LockAccess::Write,
+            || match OPT::DEADLOCK_POLICY {
+                DeadlockPolicy::Allow => Ok(self.lock.lock()),
+                DeadlockPolicy::Pessimistic => self.lock.try_lock().ok_or(LockFailed::Pessimistic),
+                DeadlockPolicy::Timeout(dur) => {
+                    self.lock.try_lock_for(dur).ok_or(LockFailed::Timeout(dur))
+                }
+            },
+        )?;
 
         Ok(op(&mut *guard))
     }
@@ -402,14 +420,18 @@ where
     where
         F: FnOnce(&T) -> U,
     {
-        let guard = match OPT::DEADLOCK_POLICY {
-            DeadlockPolicy::Allow => self.lock.read(),
-            DeadlockPolicy::Pessimistic => self.lock.try_read().ok_or(LockFailed::Pessimistic)?,
-            DeadlockPolicy::Timeout(dur) => self
-                .lock
-                .try_read_for(dur)
-                .ok_or(LockFailed::Timeout(dur))?,
-        };
+        let (guard, _held) = diagnostics::acquire(
+            T::class_name(),
+            Arc::as_ptr(&self.lock) as *const (),
+            diagnostics::LockAccess::Read,
+            || match OPT::DEADLOCK_POLICY {
+                DeadlockPolicy::Allow => Ok(self.lock.read()),
+                DeadlockPolicy::Pessimistic => self.lock.try_read().ok_or(LockFailed::Pessimistic),
+                DeadlockPolicy::Timeout(dur) => {
+                    self.lock.try_read_for(dur).ok_or(LockFailed::Timeout(dur))
+                }
+            },
+        )?;
 
         Ok(op(&*guard))
     }
@@ -427,14 +449,18 @@ where
     where
         F: FnOnce(&mut T) -> U,
     {
-        let mut guard = match OPT::DEADLOCK_POLICY {
-            DeadlockPolicy::Allow => self.lock.write(),
-            DeadlockPolicy::Pessimistic => self.lock.try_write().ok_or(LockFailed::Pessimistic)?,
-            DeadlockPolicy::Timeout(dur) => self
-                .lock
-                .try_write_for(dur)
-                .ok_or(LockFailed::Timeout(dur))?,
-        };
+        let (mut guard, _held) = diagnostics::acquire(
+            T::class_name(),
+            Arc::as_ptr(&self.lock) as *const (),
+            diagnostics::LockAccess::Write,
+            || match OPT::DEADLOCK_POLICY {
+                DeadlockPolicy::Allow => Ok(self.lock.write()),
+                DeadlockPolicy::Pessimistic => self.lock.try_write().ok_or(LockFailed::Pessimistic),
+                DeadlockPolicy::Timeout(dur) => {
+                    self.lock.try_write_for(dur).ok_or(LockFailed::Timeout(dur))
+                }
+            },
+        )?;
 
         Ok(op(&mut *guard))
     }
@@ -690,6 +716,296 @@ impl<T> Clone for LocalCellData<T> {
     }
 }
 
+/// User-data wrapper analogous to a `Arc<AtomicRefCell<T>>`, that can be used from any thread.
+///
+/// Borrows are tracked with an atomic flag. Unlike `MutexData` and `RwLockData`, conflicting
+/// borrows never block: `map`, `map_mut` and `map_owned` fail immediately with an
+/// `AtomicCellError` instead. This makes it impossible to deadlock, at the cost of failed calls
+/// when the same instance is accessed concurrently.
+#[derive(Debug)]
+pub struct AtomicCellData<T> {
+    inner: Arc<atomic_cell::AtomicCell<T>>,
+}
+
+pub use self::atomic_cell::AtomicCellError;
+
+mod atomic_cell {
+    use std::cell::UnsafeCell;
+    use std::fmt;
+    use std::mem::ManuallyDrop;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Flag set while the value is borrowed mutably. Other values of the state count the
+    /// number of shared borrows.
+    const WRITING: usize = !(usize::MAX >> 1);
+
+    /// State after the value has been moved out. Also has the `WRITING` bit set, so it's
+    /// never possible to borrow the value again.
+    const TAKEN: usize = usize::MAX;
+
+    pub struct AtomicCell<T> {
+        state: AtomicUsize,
+        value: UnsafeCell<ManuallyDrop<T>>,
+    }
+
+    /// Error indicating that a borrow has failed.
+    #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+    pub enum AtomicCellError {
+        /// A reference was requested, but a `&mut` reference already exists.
+        MutablyBorrowed,
+        /// A `&mut` reference or the value itself was requested, but shared references
+        /// already exist.
+        Borrowed,
+        /// The value has already been moved out with `map_owned`.
+        Taken,
+    }
+
+    impl std::fmt::Display for AtomicCellError {
+        #[inline]
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                AtomicCellError::MutablyBorrowed => write!(
+                    f,
+                    "borrow failed; a &mut reference already exists. The cause is likely a re-entrant call \
+                    (e.g. a GDNative Rust method calls to GDScript, which again calls a Rust method on the same object), \
+                    or a concurrent call from another thread"
+                ),
+                AtomicCellError::Borrowed => write!(
+                    f,
+                    "borrow failed; a &mut reference was requested, but a & reference already exists. The cause is likely \
+                    a re-entrant call, or a concurrent call from another thread"
+                ),
+                AtomicCellError::Taken => write!(f, "this object has already been used once"),
+            }
+        }
+    }
+
+    impl std::error::Error for AtomicCellError {}
+
+    impl<T> AtomicCell<T> {
+        #[inline]
+        pub fn new(val: T) -> Self {
+            AtomicCell {
+                state: AtomicUsize::new(0),
+                value: UnsafeCell::new(ManuallyDrop::new(val)),
+            }
+        }
+
+        #[inline]
+        pub fn try_borrow(&self) -> Result<Ref<'_, T>, AtomicCellError> {
+            let mut current = self.state.load(Ordering::Relaxed);
+            loop {
+                if current == TAKEN {
+                    return Err(AtomicCellError::Taken);
+                }
+
+                // Also fails when the count would overflow into the `WRITING` bit.
+                if (current + 1) & WRITING != 0 {
+                    return Err(AtomicCellError::MutablyBorrowed);
+                }
+
+                match self.state.compare_exchange_weak(
+                    current,
+                    current + 1,
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => return Ok(Ref { cell: self }),
+                    Err(actual) => current = actual,
+                }
+            }
+        }
+
+        #[inline]
+        pub fn try_borrow_mut(&self) -> Result<RefMut<'_, T>, AtomicCellError> {
+            self.acquire_exclusive(WRITING)?;
+            Ok(RefMut { cell: self })
+        }
+
+        #[inline]
+        pub fn try_take(&self) -> Result<T, AtomicCellError> {
+            self.acquire_exclusive(TAKEN)?;
+            // Safety: the state is now `TAKEN` for good, so the value is never accessed again,
+            // nor dropped by `AtomicCell`.
+            Ok(unsafe { ManuallyDrop::take(&mut *self.value.get()) })
+        }
+
+        #[inline]
+        fn acquire_exclusive(&self, new: usize) -> Result<(), AtomicCellError> {
+            self.state
+                .compare_exchange(0, new, Ordering::Acquire, Ordering::Relaxed)
+                .map(|_| ())
+                .map_err(|actual| match actual {
+                    TAKEN => AtomicCellError::Taken,
+                    WRITING => AtomicCellError::MutablyBorrowed,
+                    _ => AtomicCellError::Borrowed,
+                })
+        }
+    }
+
+    impl<T> Drop for AtomicCell<T> {
+        #[inline]
+        fn drop(&mut self) {
+            if *self.state.get_mut() != TAKEN {
+                // Safety: the value hasn't been taken, and there are no borrows left since
+                // we have `&mut self`.
+                unsafe { ManuallyDrop::drop(&mut *self.value.get()) }
+            }
+        }
+    }
+
+    impl<T> fmt::Debug for AtomicCell<T> {
+        #[inline]
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("AtomicCell")
+                .field("state", &self.state)
+                .finish()
+        }
+    }
+
+    // Implementing Sync is ok because all access to the value is guarded by the borrow flag.
+    // Values may be borrowed mutably, or moved out, from any thread, so `T` must be `Send`.
+    unsafe impl<T: Send + Sync> Sync for AtomicCell<T> {}
+
+    pub struct Ref<'a, T> {
+        cell: &'a AtomicCell<T>,
+    }
+
+    impl<'a, T> std::ops::Deref for Ref<'a, T> {
+        type Target = T;
+
+        #[inline]
+        fn deref(&self) -> &T {
+            // Safety: a shared borrow is held.
+            unsafe { &*self.cell.value.get() }
+        }
+    }
+
+    impl<'a, T> Drop for Ref<'a, T> {
+        #[inline]
+        fn drop(&mut self) {
+            self.cell.state.fetch_sub(1, Ordering::Release);
+        }
+    }
+
+    pub struct RefMut<'a, T> {
+        cell: &'a AtomicCell<T>,
+    }
+
+    impl<'a, T> std::ops::Deref for RefMut<'a, T> {
+        type Target = T;
+
+        #[inline]
+        fn deref(&self) -> &T {
+            // Safety: an exclusive borrow is held.
+            unsafe { &*self.cell.value.get() }
+        }
+    }
+
+    impl<'a, T> std::ops::DerefMut for RefMut<'a, T> {
+        #[inline]
+        fn deref_mut(&mut self) -> &mut T {
+            // Safety: an exclusive borrow is held.
+            unsafe { &mut *self.cell.value.get() }
+        }
+    }
+
+    impl<'a, T> Drop for RefMut<'a, T> {
+        #[inline]
+        fn drop(&mut self) {
+            self.cell.state.store(0, Ordering::Release);
+        }
+    }
+}
+
+unsafe impl<T> UserData for AtomicCellData<T>
+where
+    T: NativeClass + Send + Sync,
+{
+    type Target = T;
+
+    #[inline]
+    fn new(val: Self::Target) -> Self {
+        AtomicCellData {
+            inner: Arc::new(atomic_cell::AtomicCell::new(val)),
+        }
+    }
+
+    #[inline]
+    fn into_user_data(self) -> *const libc::c_void {
+        Arc::into_raw(self.inner) as *const libc::c_void
+    }
+
+    #[inline]
+    unsafe fn consume_user_data_unchecked(ptr: *const libc::c_void) -> Self {
+        AtomicCellData {
+            inner: Arc::from_raw(ptr as *const atomic_cell::AtomicCell<T>),
+        }
+    }
+
+    #[inline]
+    unsafe fn clone_from_user_data_unchecked(ptr: *const libc::c_void) -> Self {
+        let borrowed = Arc::from_raw(ptr as *const atomic_cell::AtomicCell<T>);
+        let arc = borrowed.clone();
+        mem::forget(borrowed);
+        AtomicCellData { inner: arc }
+    }
+}
+
+impl<T> Map for AtomicCellData<T>
+where
+    T: NativeClass + Send + Sync,
+{
+    type Err = AtomicCellError;
+
+    #[inline]
+    fn map<F, U>(&self, op: F) -> Result<U, Self::Err>
+    where
+        F: FnOnce(&Self::Target) -> U,
+    {
+        self.inner.try_borrow().map(|r| op(&*r))
+    }
+}
+
+impl<T> MapMut for AtomicCellData<T>
+where
+    T: NativeClass + Send + Sync,
+{
+    type Err = AtomicCellError;
+
+    #[inline]
+    fn map_mut<F, U>(&self, op: F) -> Result<U, Self::Err>
+    where
+        F: FnOnce(&mut Self::Target) -> U,
+    {
+        self.inner.try_borrow_mut().map(|mut w| op(&mut *w))
+    }
+}
+
+impl<T> MapOwned for AtomicCellData<T>
+where
+    T: NativeClass + Send + Sync,
+{
+    type Err = AtomicCellError;
+
+    #[inline]
+    fn map_owned<F, U>(&self, op: F) -> Result<U, Self::Err>
+    where
+        F: FnOnce(Self::Target) -> U,
+    {
+        self.inner.try_take().map(op)
+    }
+}
+
+impl<T> Clone for AtomicCellData<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        AtomicCellData {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
 /// Special user-data wrapper intended for zero-sized types, that does not perform any
 /// allocation or synchronization at runtime. Does not implement `MapMut`.
 ///
@@ -0,0 +1,548 @@
+//! Lock-contention diagnostics for `MutexData` and `RwLockData`.
+//!
+//! When enabled with [`set_enabled`], the locking wrappers record the method and thread of each
+//! lock holder, how long each lock acquisition had to wait, and how many acquisitions failed
+//! according to the `DeadlockPolicy`. Failed acquisitions additionally log the current holders
+//! of the lock as a warning. Diagnostics are disabled by default, and cost a single atomic load
+//! per lock acquisition when disabled.
+//!
+//! The data is available from Rust through [`lock_stats`] and [`lock_holders`]. It can also be
+//! queried from GDScript, for example by debug overlays, through the internal
+//! `__GdnativeLockDiagnostics` class, which is registered with every library:
+//!
+//! ```gdscript
+//! var script = NativeScript.new()
+//! script.library = preload("res://my_library.gdnlib")
+//! script.set_class_name("__GdnativeLockDiagnostics")
+//!
+//! var diagnostics = script.new()
+//! diagnostics.set_enabled(true)
+//! for stats in diagnostics.get_lock_stats():
+//!     print(stats["class_name"], ": ", stats["failures"], " failures")
+//! ```
+//!
+//! The class has the methods `is_enabled`, `set_enabled`, `get_lock_stats`, `get_lock_holders`
+//! and `reset`. Stats and holders are returned as arrays of dictionaries with the same fields
+//! as [`LockStats`] and [`LockHolder`], with durations in microseconds.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::core_types::{Dictionary, ToVariant, Variant, VariantArray};
+use crate::log::Site;
+use crate::nativescript::init::method::{Method, Varargs};
+use crate::nativescript::init::ClassBuilder;
+use crate::nativescript::user_data::{Aether, LockFailed};
+use crate::nativescript::{NativeClass, NativeClassMethods, RefInstance};
+use crate::object::TRef;
+use crate::private::ReferenceCountedClassPlaceholder;
+use crate::thread_access::Shared;
+
+/// Upper bounds of the buckets in wait-time histograms. Waits of at least the last bound are
+/// counted in an extra, final bucket.
+pub const WAIT_BUCKETS: [Duration; 5] = [
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+    Duration::from_millis(1),
+    Duration::from_millis(10),
+    Duration::from_millis(100),
+];
+
+/// Number of buckets in wait-time histograms.
+pub const WAIT_HISTOGRAM_LEN: usize = WAIT_BUCKETS.len() + 1;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static STATE: Lazy<Mutex<State>> = Lazy::new(|| Mutex::new(State::default()));
+
+thread_local! {
+    static CURRENT_METHOD: Cell<Option<Site<'static>>> = Cell::new(None);
+}
+
+/// Enables or disables lock diagnostics globally. Data recorded so far is kept.
+#[inline]
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Release);
+}
+
+/// Returns whether lock diagnostics are enabled.
+#[inline]
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+/// Clears all recorded statistics. Current holders are kept, since they are still holding
+/// their locks.
+#[inline]
+pub fn reset() {
+    STATE.lock().stats.clear();
+}
+
+/// Returns the recorded statistics of each script class, sorted by class name.
+#[inline]
+pub fn lock_stats() -> Vec<LockStats> {
+    let mut stats = STATE.lock().stats.values().cloned().collect::<Vec<_>>();
+    stats.sort_by_key(|stats| stats.class_name);
+    stats
+}
+
+/// Returns the current holders of all locks acquired while diagnostics were enabled, sorted by
+/// class name.
+#[inline]
+pub fn lock_holders() -> Vec<LockHolder> {
+    let now = Instant::now();
+    let mut holders = STATE
+        .lock()
+        .holders
+        .values()
+        .flatten()
+        .map(|record| record.to_holder(now))
+        .collect::<Vec<_>>();
+
+    holders.sort_by_key(|holder| holder.class_name);
+    holders
+}
+
+/// Lock statistics of a script class.
+#[derive(Clone, Debug)]
+pub struct LockStats {
+    class_name: &'static str,
+    acquisitions: u64,
+    failures: u64,
+    wait_histogram: [u64; WAIT_HISTOGRAM_LEN],
+    total_wait: Duration,
+    max_wait: Duration,
+}
+
+impl LockStats {
+    fn new(class_name: &'static str) -> Self {
+        LockStats {
+            class_name,
+            acquisitions: 0,
+            failures: 0,
+            wait_histogram: [0; WAIT_HISTOGRAM_LEN],
+            total_wait: Duration::default(),
+            max_wait: Duration::default(),
+        }
+    }
+
+    fn record_wait(&mut self, wait: Duration) {
+        let bucket = WAIT_BUCKETS
+            .iter()
+            .position(|&bound| wait < bound)
+            .unwrap_or(WAIT_BUCKETS.len());
+
+        self.acquisitions += 1;
+        self.wait_histogram[bucket] += 1;
+        self.total_wait += wait;
+        self.max_wait = self.max_wait.max(wait);
+    }
+
+    /// Returns the name of the script class.
+    #[inline]
+    pub fn class_name(&self) -> &'static str {
+        self.class_name
+    }
+
+    /// Returns the number of successful lock acquisitions.
+    #[inline]
+    pub fn acquisitions(&self) -> u64 {
+        self.acquisitions
+    }
+
+    /// Returns the number of acquisitions that failed with `LockFailed`.
+    #[inline]
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+
+    /// Returns the number of successful acquisitions in each bucket of `WAIT_BUCKETS`.
+    #[inline]
+    pub fn wait_histogram(&self) -> &[u64; WAIT_HISTOGRAM_LEN] {
+        &self.wait_histogram
+    }
+
+    /// Returns the total time spent waiting in successful acquisitions.
+    #[inline]
+    pub fn total_wait(&self) -> Duration {
+        self.total_wait
+    }
+
+    /// Returns the longest time spent waiting in a successful acquisition.
+    #[inline]
+    pub fn max_wait(&self) -> Duration {
+        self.max_wait
+    }
+
+    fn to_dictionary(&self) -> Dictionary<Shared> {
+        let histogram = VariantArray::new();
+        for &count in self.wait_histogram.iter() {
+            histogram.push(count);
+        }
+
+        let dict = Dictionary::new();
+        dict.insert("class_name", self.class_name);
+        dict.insert("acquisitions", self.acquisitions);
+        dict.insert("failures", self.failures);
+        dict.insert("wait_histogram", histogram.into_shared());
+        dict.insert("total_wait", self.total_wait.as_micros() as u64);
+        dict.insert("max_wait", self.max_wait.as_micros() as u64);
+        dict.into_shared()
+    }
+}
+
+/// Kind of access a lock is held with.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum LockAccess {
+    /// Shared access through `Map` on `RwLockData`.
+    Read,
+    /// Exclusive access, through `MapMut` or any access on `MutexData`.
+    Write,
+}
+
+impl Display for LockAccess {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockAccess::Read => f.write_str("read"),
+            LockAccess::Write => f.write_str("write"),
+        }
+    }
+}
+
+/// Current holder of a lock.
+#[derive(Clone, Debug)]
+pub struct LockHolder {
+    class_name: &'static str,
+    method: Option<Site<'static>>,
+    thread: String,
+    access: LockAccess,
+    held_for: Duration,
+}
+
+impl LockHolder {
+    /// Returns the name of the script class.
+    #[inline]
+    pub fn class_name(&self) -> &'static str {
+        self.class_name
+    }
+
+    /// Returns the site of the exported method that acquired the lock, if it was acquired
+    /// within one.
+    #[inline]
+    pub fn method(&self) -> Option<Site<'static>> {
+        self.method
+    }
+
+    /// Returns the name of the thread holding the lock, or its ID if it's unnamed.
+    #[inline]
+    pub fn thread(&self) -> &str {
+        &self.thread
+    }
+
+    /// Returns the kind of access the lock is held with.
+    #[inline]
+    pub fn access(&self) -> LockAccess {
+        self.access
+    }
+
+    /// Returns how long the lock has been held.
+    #[inline]
+    pub fn held_for(&self) -> Duration {
+        self.held_for
+    }
+
+    fn to_dictionary(&self) -> Dictionary<Shared> {
+        let dict = Dictionary::new();
+        dict.insert("class_name", self.class_name);
+        dict.insert("method", self.method.map(|site| site.to_string()));
+        dict.insert("thread", &self.thread);
+        dict.insert("access", self.access.to_string());
+        dict.insert("held_for", self.held_for.as_micros() as u64);
+        dict.into_shared()
+    }
+}
+
+impl Display for LockHolder {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} lock on {} held by thread {} for {:?}",
+            self.access, self.class_name, self.thread, self.held_for
+        )?;
+
+        if let Some(method) = self.method {
+            write!(f, " ({})", method)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct State {
+    stats: HashMap<&'static str, LockStats>,
+    /// Holders of each lock, keyed by the address of the lock.
+    holders: HashMap<usize, Vec<HolderRecord>>,
+}
+
+struct HolderRecord {
+    class_name: &'static str,
+    method: Option<Site<'static>>,
+    thread_id: ThreadId,
+    thread: String,
+    access: LockAccess,
+    since: Instant,
+}
+
+impl HolderRecord {
+    fn to_holder(&self, now: Instant) -> LockHolder {
+        LockHolder {
+            class_name: self.class_name,
+            method: self.method,
+            thread: self.thread.clone(),
+            access: self.access,
+            held_for: now.saturating_duration_since(self.since),
+        }
+    }
+}
+
+/// Records the exported method being called on this thread until the returned scope is dropped.
+#[inline]
+pub(crate) fn enter_method(site: Option<Site<'static>>) -> MethodScope {
+    if !is_enabled() {
+        return MethodScope { previous: None };
+    }
+
+    let previous = CURRENT_METHOD.with(|current| current.replace(site));
+    MethodScope {
+        previous: Some(previous),
+    }
+}
+
+pub(crate) struct MethodScope {
+    previous: Option<Option<Site<'static>>>,
+}
+
+impl Drop for MethodScope {
+    #[inline]
+    fn drop(&mut self) {
+        if let Some(previous) = self.previous {
+            let _ = CURRENT_METHOD.try_with(|current| current.set(previous));
+        }
+    }
+}
+
+/// Acquires a lock with `acquire`, recording diagnostics if enabled. The returned `Held` value
+/// must be kept alive for as long as the lock is held.
+#[inline]
+pub(crate) fn acquire<G, F>(
+    class_name: &'static str,
+    lock: *const (),
+    access: LockAccess,
+    acquire: F,
+) -> Result<(G, Option<Held>), LockFailed>
+where
+    F: FnOnce() -> Result<G, LockFailed>,
+{
+    if !is_enabled() {
+        return acquire().map(|guard| (guard, None));
+    }
+
+    let start = Instant::now();
+    let result = acquire();
+    let now = Instant::now();
+
+    let lock = lock as usize;
+    let mut state = STATE.lock();
+    let stats = state
+        .stats
+        .entry(class_name)
+        .or_insert_with(|| LockStats::new(class_name));
+
+    match result {
+        Ok(guard) => {
+            stats.record_wait(now.saturating_duration_since(start));
+
+            let current = thread::current();
+            let thread_id = current.id();
+            let record = HolderRecord {
+                class_name,
+                method: CURRENT_METHOD.with(Cell::get),
+                thread_id,
+                thread: current
+                    .name()
+                    .map(String::from)
+                    .unwrap_or_else(|| format!("{:?}", thread_id)),
+                access,
+                since: now,
+            };
+
+            state.holders.entry(lock).or_default().push(record);
+
+            Ok((guard, Some(Held { lock, thread_id })))
+        }
+        Err(err) => {
+            stats.failures += 1;
+
+            let holders = state
+                .holders
+                .get(&lock)
+                .map(|records| {
+                    records
+                        .iter()
+                        .map(|record| record.to_holder(now).to_string())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            drop(state);
+
+            if holders.is_empty() {
+                godot_warn!("gdnative-core: {} on {} (holder unknown)", err, class_name);
+            } else {
+                godot_warn!(
+                    "gdnative-core: {} on {}; current holders: {}",
+                    err,
+                    class_name,
+                    holders.join("; ")
+                );
+            }
+
+            Err(err)
+        }
+    }
+}
+
+/// Removes the holder record of a lock when dropped.
+pub(crate) struct Held {
+    lock: usize,
+    thread_id: ThreadId,
+}
+
+impl Drop for Held {
+    #[inline]
+    fn drop(&mut self) {
+        let mut state = STATE.lock();
+        if let Some(records) = state.holders.get_mut(&self.lock) {
+            if let Some(idx) = records
+                .iter()
+                .rposition(|record| record.thread_id == self.thread_id)
+            {
+                records.swap_remove(idx);
+            }
+
+            if records.is_empty() {
+                state.holders.remove(&self.lock);
+            }
+        }
+    }
+}
+
+/// Internal script class exposing lock diagnostics to GDScript.
+#[derive(Copy, Clone, Default)]
+pub(crate) struct LockDiagnostics;
+
+impl NativeClass for LockDiagnostics {
+    type Base = ReferenceCountedClassPlaceholder;
+    type UserData = Aether<LockDiagnostics>;
+
+    fn class_name() -> &'static str {
+        "__GdnativeLockDiagnostics"
+    }
+
+    fn init(_owner: TRef<'_, ReferenceCountedClassPlaceholder, Shared>) -> Self {
+        LockDiagnostics
+    }
+}
+
+impl NativeClassMethods for LockDiagnostics {
+    fn register(builder: &ClassBuilder<Self>) {
+        builder
+            .build_method("is_enabled", IsEnabled)
+            .done_stateless();
+        builder
+            .build_method("set_enabled", SetEnabled)
+            .done_stateless();
+        builder
+            .build_method("get_lock_stats", GetLockStats)
+            .done_stateless();
+        builder
+            .build_method("get_lock_holders", GetLockHolders)
+            .done_stateless();
+        builder.build_method("reset", Reset).done_stateless();
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct IsEnabled;
+
+impl Method<LockDiagnostics> for IsEnabled {
+    fn call(&self, _this: RefInstance<'_, LockDiagnostics, Shared>, _args: Varargs<'_>) -> Variant {
+        is_enabled().to_variant()
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct SetEnabled;
+
+impl Method<LockDiagnostics> for SetEnabled {
+    fn call(
+        &self,
+        _this: RefInstance<'_, LockDiagnostics, Shared>,
+        mut args: Varargs<'_>,
+    ) -> Variant {
+        match args.read::<bool>().get() {
+            Ok(enabled) => set_enabled(enabled),
+            Err(err) => err.log_error(),
+        }
+
+        Variant::new()
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct GetLockStats;
+
+impl Method<LockDiagnostics> for GetLockStats {
+    fn call(&self, _this: RefInstance<'_, LockDiagnostics, Shared>, _args: Varargs<'_>) -> Variant {
+        let list = VariantArray::new();
+        for stats in lock_stats() {
+            list.push(stats.to_dictionary());
+        }
+
+        list.into_shared().to_variant()
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct GetLockHolders;
+
+impl Method<LockDiagnostics> for GetLockHolders {
+    fn call(&self, _this: RefInstance<'_, LockDiagnostics, Shared>, _args: Varargs<'_>) -> Variant {
+        let list = VariantArray::new();
+        for holder in lock_holders() {
+            list.push(holder.to_dictionary());
+        }
+
+        list.into_shared().to_variant()
+    }
+}
+
+#[derive(Copy, Clone, Default)]
+struct Reset;
+
+impl Method<LockDiagnostics> for Reset {
+    fn call(&self, _this: RefInstance<'_, LockDiagnostics, Shared>, _args: Varargs<'_>) -> Variant {
+        reset();
+        Variant::new()
+    }
+}
@@ -14,7 +14,7 @@ use crate::thread_access::{
 };
 
 #[cfg(feature = "nativescript")]
-use crate::nativescript::{Instance, NativeClass, RefInstance};
+use crate::nativescript::{Instance, InstanceBinding, NativeClass, RefInstance};
 
 mod raw;
 
@@ -495,6 +495,22 @@ where
     {
         Instance::try_from_base(self)
     }
+
+    /// Returns the instance binding `B` of the object, creating it if this is the first
+    /// access. Returns `None` if `B` isn't registered, or if it failed to be created.
+    ///
+    /// Binding types must be registered with `InitHandle::register_instance_binding`.
+    #[inline]
+    #[cfg(feature = "nativescript")]
+    pub fn binding<B>(&self) -> Option<&B>
+    where
+        B: InstanceBinding,
+    {
+        unsafe {
+            crate::nativescript::binding::get::<B>(self.as_raw().sys())
+                .map(|binding| &*binding.as_ptr())
+        }
+    }
 }
 
 /// Methods for references that can't be used directly, and have to be assumed safe `unsafe`ly.
@@ -927,6 +943,22 @@ impl<'a, T: GodotObject, Access: ThreadAccess> TRef<'a, T, Access> {
     {
         RefInstance::try_from_base(self)
     }
+
+    /// Returns the instance binding `B` of the object, creating it if this is the first
+    /// access. Returns `None` if `B` isn't registered, or if it failed to be created.
+    ///
+    /// Binding types must be registered with `InitHandle::register_instance_binding`.
+    #[inline]
+    #[cfg(feature = "nativescript")]
+    pub fn binding<B>(self) -> Option<&'a B>
+    where
+        B: InstanceBinding,
+    {
+        unsafe {
+            crate::nativescript::binding::get::<B>(self.obj.as_raw().sys())
+                .map(|binding| &*binding.as_ptr())
+        }
+    }
 }
 
 impl<'a, Kind, T, Access> TRef<'a, T, Access>
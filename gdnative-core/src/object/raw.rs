@@ -222,13 +222,39 @@ impl<T: GodotObject> Debug for RawObject<T> {
     }
 }
 
-/// Checks whether the raw object pointer is of a certain Godot class.
+/// Checks whether the raw object pointer is of a certain Godot class. Uses the global type tag
+/// of the object if it has a script of this library, and calls into the engine otherwise.
 ///
 /// # Safety
 ///
 /// The `obj` pointer must be pointing to a valid Godot object.
 #[inline]
 unsafe fn ptr_is_class(obj: *mut sys::godot_object, class_name: &str) -> bool {
+    if let Some(is_class) = ptr_is_class_by_tag(obj, class_name) {
+        return is_class;
+    }
+
+    ptr_is_class_by_engine(obj, class_name)
+}
+
+#[cfg(feature = "nativescript")]
+#[inline]
+unsafe fn ptr_is_class_by_tag(obj: *mut sys::godot_object, class_name: &str) -> Option<bool> {
+    crate::nativescript::binding::is_class(NonNull::new(obj)?, class_name)
+}
+
+#[cfg(not(feature = "nativescript"))]
+#[inline]
+unsafe fn ptr_is_class_by_tag(_obj: *mut sys::godot_object, _class_name: &str) -> Option<bool> {
+    None
+}
+
+/// Checks whether the raw object pointer is of a certain Godot class, by calling `is_class`.
+///
+/// # Safety
+///
+/// The `obj` pointer must be pointing to a valid Godot object.
+unsafe fn ptr_is_class_by_engine(obj: *mut sys::godot_object, class_name: &str) -> bool {
     let api = crate::private::get_api();
     let method_bind = crate::private::ObjectMethodTable::get(api).is_class;
 
@@ -0,0 +1,347 @@
+//! Configuring how panics in exported callbacks are handled.
+//!
+//! Panics in constructors, exported methods and property accessors are caught before they
+//! can unwind into the engine. What happens next is decided by the global [`PanicPolicy`],
+//! which defaults to logging a short error and continuing with a default return value.
+//!
+//! A hook can also be set with [`set_hook`], which receives a [`PanicContext`] describing
+//! each caught panic, for example to forward it to a crash reporter.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! fn init(handle: InitHandle) {
+//!     // Fail fast in CI builds.
+//!     if std::env::var_os("CI").is_some() {
+//!         gdnative::panic::set_policy(PanicPolicy::Abort);
+//!     }
+//!
+//!     handle.add_class::<MyClass>();
+//! }
+//! ```
+//!
+//! ## Backtraces
+//!
+//! Backtraces are only captured with the `backtrace` feature. Without it, the standard panic
+//! hook still prints them to stderr when `RUST_BACKTRACE` is set.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt::{self, Display};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use once_cell::sync::Lazy;
+
+use crate::log::Site;
+
+/// What happens after a panic in an exported callback is caught.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PanicPolicy {
+    /// Logs a short error with `godot_error!`, and continues with a default return value.
+    /// This is the default.
+    Log,
+    /// Logs the panic message and location, the class and callback that panicked, and the
+    /// backtrace if available, then continues with a default return value.
+    Report,
+    /// Logs the same information as `Report`, then aborts the process.
+    Abort,
+}
+
+impl Default for PanicPolicy {
+    #[inline]
+    fn default() -> Self {
+        PanicPolicy::Log
+    }
+}
+
+impl PanicPolicy {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => PanicPolicy::Report,
+            2 => PanicPolicy::Abort,
+            _ => PanicPolicy::Log,
+        }
+    }
+
+    fn to_u8(self) -> u8 {
+        match self {
+            PanicPolicy::Log => 0,
+            PanicPolicy::Report => 1,
+            PanicPolicy::Abort => 2,
+        }
+    }
+}
+
+/// Kind of callback in which a panic was caught.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Callback {
+    Constructor,
+    Method,
+    PropertyGetter,
+    PropertySetter,
+}
+
+impl Display for Callback {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Callback::Constructor => "constructor",
+            Callback::Method => "method",
+            Callback::PropertyGetter => "property getter",
+            Callback::PropertySetter => "property setter",
+        };
+
+        f.write_str(name)
+    }
+}
+
+/// Information about a panic caught in an exported callback, passed to the hook set with
+/// [`set_hook`].
+#[derive(Debug)]
+pub struct PanicContext<'a> {
+    class_name: &'a str,
+    callback: Callback,
+    site: Option<Site<'a>>,
+    message: Option<&'a str>,
+    location: Option<&'a str>,
+    backtrace: Option<&'a str>,
+}
+
+impl<'a> PanicContext<'a> {
+    /// Returns the name of the script class.
+    #[inline]
+    pub fn class_name(&self) -> &'a str {
+        self.class_name
+    }
+
+    /// Returns the kind of callback that panicked.
+    #[inline]
+    pub fn callback(&self) -> Callback {
+        self.callback
+    }
+
+    /// Returns the site of the callback, if known. This is available for methods registered
+    /// with `#[methods]` or `godot_wrap_method!`.
+    #[inline]
+    pub fn site(&self) -> Option<Site<'a>> {
+        self.site
+    }
+
+    /// Returns the panic message, if it's a string.
+    #[inline]
+    pub fn message(&self) -> Option<&'a str> {
+        self.message
+    }
+
+    /// Returns the source location of the panic as `file:line:column`. Only available once
+    /// a policy or hook has been set, since the location is recorded by a panic hook.
+    #[inline]
+    pub fn location(&self) -> Option<&'a str> {
+        self.location
+    }
+
+    /// Returns the backtrace of the panic. Only available with the `backtrace` feature, once
+    /// a policy or hook has been set.
+    #[inline]
+    pub fn backtrace(&self) -> Option<&'a str> {
+        self.backtrace
+    }
+}
+
+impl<'a> Display for PanicContext<'a> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} of {} panicked", self.callback, self.class_name)?;
+
+        if let Some(site) = self.site {
+            write!(f, " ({})", site)?;
+        }
+
+        write!(f, ": {}", self.message.unwrap_or("<non-string payload>"))?;
+
+        if let Some(location) = self.location {
+            write!(f, "\n  at {}", location)?;
+        }
+
+        if let Some(backtrace) = self.backtrace {
+            write!(f, "\n{}", backtrace)?;
+        }
+
+        Ok(())
+    }
+}
+
+type Hook = Arc<dyn Fn(&PanicContext<'_>) + Send + Sync>;
+
+static POLICY: AtomicU8 = AtomicU8::new(0);
+
+static HOOK: Lazy<RwLock<Option<Hook>>> = Lazy::new(|| RwLock::new(None));
+
+#[allow(deprecated)] // `PanicInfo` is renamed in later versions of Rust.
+type StdHook = Box<dyn Fn(&std::panic::PanicInfo<'_>) + Send + Sync + 'static>;
+
+/// The standard panic hook that was replaced by `install_panic_hook`, if it is installed.
+static PREVIOUS_PANIC_HOOK: Lazy<Mutex<Option<Arc<StdHook>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Sets the global policy for panics in exported callbacks.
+#[inline]
+pub fn set_policy(policy: PanicPolicy) {
+    install_panic_hook();
+    POLICY.store(policy.to_u8(), Ordering::Release);
+}
+
+/// Returns the current global policy for panics in exported callbacks.
+#[inline]
+pub fn policy() -> PanicPolicy {
+    PanicPolicy::from_u8(POLICY.load(Ordering::Acquire))
+}
+
+/// Sets a hook that is called with the context of every panic caught in exported callbacks,
+/// before the policy is applied. Replaces any previous hook.
+///
+/// The hook is called on the thread of the callback, which isn't necessarily the main thread.
+#[inline]
+pub fn set_hook<F>(hook: F)
+where
+    F: Fn(&PanicContext<'_>) + Send + Sync + 'static,
+{
+    install_panic_hook();
+    *HOOK.write().unwrap_or_else(|err| err.into_inner()) = Some(Arc::new(hook));
+}
+
+/// Removes the hook set with [`set_hook`], if any.
+#[inline]
+pub fn take_hook() {
+    HOOK.write().unwrap_or_else(|err| err.into_inner()).take();
+}
+
+/// Location and backtrace of the last panic on this thread, recorded by the panic hook.
+struct PanicRecord {
+    location: Option<String>,
+    backtrace: Option<String>,
+}
+
+thread_local! {
+    static LAST_PANIC: RefCell<Option<PanicRecord>> = RefCell::new(None);
+}
+
+/// Installs a panic hook recording the location of panics, which then calls the previous
+/// hook. Only done once a policy or hook is set, to leave the hook alone otherwise. The
+/// previous hook is restored by `cleanup`.
+#[allow(deprecated)] // `PanicInfo` is renamed in later versions of Rust.
+fn install_panic_hook() {
+    let mut previous_hook = PREVIOUS_PANIC_HOOK
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+
+    if previous_hook.is_some() {
+        return;
+    }
+
+    let previous = Arc::new(std::panic::take_hook());
+    *previous_hook = Some(Arc::clone(&previous));
+
+    std::panic::set_hook(Box::new(move |info: &std::panic::PanicInfo<'_>| {
+        let record = PanicRecord {
+            location: info.location().map(|location| location.to_string()),
+            backtrace: capture_backtrace(),
+        };
+
+        let _ = LAST_PANIC.try_with(|last| *last.borrow_mut() = Some(record));
+
+        previous(info);
+    }));
+}
+
+/// Restores the panic hook replaced by `install_panic_hook`, and resets the policy and hook.
+/// Called during `terminate`, since the hooks would otherwise outlive the library.
+pub(crate) fn cleanup() {
+    take_hook();
+    POLICY.store(PanicPolicy::Log.to_u8(), Ordering::Release);
+
+    let previous = PREVIOUS_PANIC_HOOK
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .take();
+
+    if let Some(previous) = previous {
+        // Drops the installed hook, which holds the other reference to `previous`. A hook set
+        // after ours is dropped as well, since it may wrap ours.
+        drop(std::panic::take_hook());
+
+        // Only fails if the hook is still running on another thread, in which case the
+        // default hook stays installed.
+        if let Ok(previous) = Arc::try_unwrap(previous) {
+            std::panic::set_hook(previous);
+        }
+    }
+}
+
+#[cfg(feature = "backtrace")]
+fn capture_backtrace() -> Option<String> {
+    Some(format!("{:?}", backtrace::Backtrace::new()))
+}
+
+#[cfg(not(feature = "backtrace"))]
+fn capture_backtrace() -> Option<String> {
+    None
+}
+
+/// Handles a panic caught in an exported callback, according to the current policy and hook.
+pub(crate) fn handle_panic(
+    class_name: &str,
+    callback: Callback,
+    site: Option<Site<'_>>,
+    payload: Box<dyn Any + Send>,
+) {
+    let record = LAST_PANIC
+        .try_with(|last| last.borrow_mut().take())
+        .ok()
+        .flatten();
+
+    let (location, backtrace) = match &record {
+        Some(record) => (record.location.as_deref(), record.backtrace.as_deref()),
+        None => (None, None),
+    };
+
+    let message = payload
+        .downcast_ref::<&'static str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str));
+
+    let context = PanicContext {
+        class_name,
+        callback,
+        site,
+        message,
+        location,
+        backtrace,
+    };
+
+    let hook = HOOK
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .as_ref()
+        .cloned();
+
+    if let Some(hook) = hook {
+        hook(&context);
+    }
+
+    let site = site.unwrap_or_default();
+    match policy() {
+        PanicPolicy::Log => crate::log::error(
+            site,
+            format_args!(
+                "gdnative-core: {} of {} panicked (check stderr for output)",
+                callback, class_name,
+            ),
+        ),
+        PanicPolicy::Report => crate::log::error(site, format_args!("gdnative-core: {}", context)),
+        PanicPolicy::Abort => {
+            crate::log::error(site, format_args!("gdnative-core: {}", context));
+            std::process::abort();
+        }
+    }
+}
@@ -33,6 +33,7 @@ pub unsafe fn bind_api(options: *mut sys::godot_gdnative_init_options) -> bool {
     #[cfg(feature = "nativescript")]
     {
         NativeScriptMethodTable::get(get_api());
+        EngineMethodTable::get(get_api());
     }
 
     true
@@ -110,9 +111,15 @@ pub fn get_gdnative_library_sys() -> *mut sys::godot_object {
 pub unsafe fn cleanup_internal_state() {
     #[cfg(feature = "nativescript")]
     {
+        #[cfg(feature = "instance_tracking")]
+        crate::nativescript::class_registry::report_leaks();
+
+        crate::nativescript::binding::cleanup();
         crate::nativescript::type_tag::cleanup();
         crate::nativescript::class_registry::cleanup();
+        crate::nativescript::task::cleanup();
     }
+    crate::panic::cleanup();
     GODOT_API = None;
 }
 
@@ -142,6 +149,42 @@ unsafe fn report_init_error(
     }
 }
 
+#[cfg(feature = "inventory")]
+pub use inventory;
+
+/// Class collected by `#[methods]` for automatic registration.
+#[cfg(feature = "inventory")]
+pub struct AutoRegisteredClass {
+    pub register: fn(crate::nativescript::init::InitHandle),
+}
+
+#[cfg(feature = "inventory")]
+inventory::collect!(AutoRegisteredClass);
+
+/// Engine class generated by the bindings. References to these are used as the global type
+/// tags of the classes.
+pub struct GlobalClass {
+    pub name: &'static str,
+    pub base: Option<&'static GlobalClass>,
+}
+
+/// Registers global type tags for engine classes, which are then used to check class
+/// compatibility in casts without calling into the engine. Does nothing without the
+/// `nativescript` feature.
+///
+/// This is intended to be an internal interface. It should be called during
+/// `nativescript_init`, after `InitHandle::add_internal_classes`.
+#[inline]
+pub fn register_global_type_tags(classes: &[&'static GlobalClass]) {
+    #[cfg(feature = "nativescript")]
+    unsafe {
+        crate::nativescript::binding::register_global_type_tags(classes)
+    };
+
+    #[cfg(not(feature = "nativescript"))]
+    let _ = classes;
+}
+
 pub mod godot_object {
     pub trait Sealed {}
 }
@@ -170,6 +213,19 @@ unsafe impl crate::object::GodotObject for ReferenceCountedClassPlaceholder {
 
 impl godot_object::Sealed for ReferenceCountedClassPlaceholder {}
 
+impl crate::object::Instanciable for ReferenceCountedClassPlaceholder {
+    #[inline]
+    fn construct() -> crate::object::Ref<Self, crate::thread_access::Unique> {
+        unsafe {
+            let class_name = b"Reference\0".as_ptr() as *const libc::c_char;
+            let ctor = (get_api().godot_get_class_constructor)(class_name).unwrap();
+            let obj = std::ptr::NonNull::new(ctor())
+                .expect("Reference constructor should not return null");
+            crate::object::Ref::init_from_sys(obj)
+        }
+    }
+}
+
 macro_rules! make_method_table {
     (struct $tablename:ident for $class:ident { $($methods:ident,)* }) => {
         pub(crate) struct $tablename {
@@ -213,6 +269,9 @@ macro_rules! make_method_table {
 make_method_table!(struct ObjectMethodTable for Object {
     get_class,
     is_class,
+    connect,
+    call_deferred,
+    get_instance_id,
 });
 
 make_method_table!(struct ReferenceMethodTable for Reference {
@@ -229,3 +288,8 @@ make_method_table!(struct NativeScriptMethodTable for NativeScript {
     set_library,
     new,
 });
+
+#[cfg(feature = "nativescript")]
+make_method_table!(struct EngineMethodTable for _Engine {
+    get_main_loop,
+});
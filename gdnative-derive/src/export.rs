@@ -0,0 +1,62 @@
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Fields};
+
+pub(crate) fn derive_export(input: &DeriveInput) -> Result<TokenStream2, syn::Error> {
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "#[derive(Export)] only supports fieldless enums",
+            ))
+        }
+    };
+
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "#[derive(Export)] does not support generic enums",
+        ));
+    }
+
+    if let Some(variant) = variants
+        .iter()
+        .find(|variant| !matches!(variant.fields, Fields::Unit))
+    {
+        return Err(syn::Error::new(
+            variant.fields.span(),
+            "#[derive(Export)] only supports fieldless enums",
+        ));
+    }
+
+    let ident = &input.ident;
+    let derived = crate::automatically_derived();
+
+    let var_idents = variants.iter().map(|variant| &variant.ident);
+    let var_ident_string_literals = variants
+        .iter()
+        .map(|variant| Literal::string(&variant.ident.to_string()));
+
+    Ok(quote! {
+        #derived
+        impl ::gdnative::nativescript::init::property::Export for #ident {
+            type Hint = ::gdnative::nativescript::init::property::hint::IntHint<i64>;
+
+            fn export_info(
+                hint: Option<Self::Hint>,
+            ) -> ::gdnative::nativescript::init::property::ExportInfo {
+                use ::gdnative::nativescript::init::property::hint::{EnumHint, IntHint};
+
+                hint.unwrap_or_else(|| {
+                    IntHint::Enum(EnumHint::new(vec![
+                        #(
+                            format!("{}:{}", #var_ident_string_literals, #ident::#var_idents as i64),
+                        )*
+                    ]))
+                })
+                .export_info()
+            }
+        }
+    })
+}
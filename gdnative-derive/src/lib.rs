@@ -9,6 +9,7 @@ use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use syn::{AttributeArgs, DeriveInput, ItemFn, ItemImpl};
 
+mod export;
 mod extend_bounds;
 mod methods;
 mod native_script;
@@ -16,6 +17,44 @@ mod profiled;
 mod varargs;
 mod variant;
 
+/// Collects the exported methods and signals of a `NativeClass` in an impl block, and
+/// generates a `NativeClassMethods` implementation that registers them.
+///
+/// Methods marked with `#[export]` are registered as methods of the script. If the type
+/// implements `DynamicProperties`, the `_get_property_list`, `_get` and `_set` methods are
+/// registered as well.
+///
+/// Signals can be declared as functions without bodies marked with `#[signal]`. The
+/// arguments are registered as signal arguments, and the declaration is replaced with an
+/// associated function named `emit_<signal>`, that takes the owner and the arguments:
+///
+/// ```ignore
+/// #[methods]
+/// impl Enemy {
+///     #[signal]
+///     fn hit(damage: i64, source: Ref<Node>);
+///
+///     #[export]
+///     fn _on_collision(&self, owner: &Node, source: Ref<Node>) {
+///         Self::emit_hit(owner, 10, source);
+///     }
+/// }
+/// ```
+///
+/// ## `#[export]` options
+///
+/// - `name = "foo"`: Registers the method under a different name.
+/// - `rpc = "remote_sync"`: Sets the RPC mode of the method. For methods with an RPC mode
+///   other than `disabled`, typed functions are generated to call the method remotely:
+///   `rpc_foo(owner, args...)`, `rpc_id_foo(owner, peer_id, args...)`,
+///   `rpc_unreliable_foo(owner, args...)` and `rpc_unreliable_id_foo(owner, peer_id, args...)`,
+///   which are `pub(crate)` whatever the visibility of the method. `#[opt]` arguments are taken as `Option`s, and
+///   passed up to the first `None`. Only classes inheriting `Node` can have RPC methods.
+/// - `err = "nil"` / `err = "dict"`: For methods returning `Result<T, E>` where `E` is an
+///   error type, logs errors with `godot_error!` and returns `null` or a `Dictionary`
+///   describing the error to the caller. `Ok` values are returned as is. `err = "nil"` is the
+///   default for methods with a return type named `Result`, so `E` must implement `Display`.
+///   See `ErrorReturn`.
 #[proc_macro_attribute]
 pub fn methods(meta: TokenStream, input: TokenStream) -> TokenStream {
     if syn::parse::<syn::parse::Nothing>(meta.clone()).is_err() {
@@ -83,6 +122,9 @@ pub fn profiled(meta: TokenStream, input: TokenStream) -> TokenStream {
 
 /// Makes it possible to use a type as a NativeScript.
 ///
+/// Doc comments on the type and on `#[property]` fields are registered as documentation,
+/// and shown in the editor's help panel.
+///
 /// ## Type attributes
 ///
 /// The behavior of the derive macro can be customized using attributes on the type
@@ -95,13 +137,35 @@ pub fn profiled(meta: TokenStream, input: TokenStream) -> TokenStream {
 /// arguments of exported methods must be references (`TRef`, `Ref`, or `&`) to this
 /// type.
 ///
-/// Inheritance from other scripts, either in Rust or other languages, is
-/// not supported.
+/// Inheritance from scripts in other languages is not supported. To inherit from another
+/// Rust script, use `#[inherit_script]`.
 ///
 /// If no `#[inherit(...)]` is provided, [`gdnative::api::Reference`](../gdnative/api/struct.Reference.html)
 /// is used as a base class. This behavior is consistent with GDScript: omitting the
 /// `extends` keyword will inherit `Reference`.
 ///
+/// ### `#[inherit_script(path::to::Script)]`
+///
+/// Inherits the exported methods, properties and signals of another Rust script. The
+/// parent script must also implement `NativeClassMethods`, usually through `#[methods]`.
+/// Exactly one field must be marked with `#[parent_script]` to hold the state of the
+/// parent, which is used when inherited members are called:
+///
+/// ```ignore
+/// #[derive(NativeClass)]
+/// #[inherit_script(Enemy)]
+/// struct FlyingEnemy {
+///     #[parent_script]
+///     enemy: ParentScript<Enemy>,
+///     altitude: f32,
+/// }
+/// ```
+///
+/// Members of the class itself override inherited ones with the same names. If no
+/// `#[inherit(...)]` is provided, the base class of the parent script is used. Otherwise,
+/// it must be a sub-class of it. See `Instance::upcast` for converting instances to the
+/// parent script type.
+///
 ///
 /// ### `#[user_data(gdnative::user_data::SomeWrapper<Self>)]`
 ///
@@ -120,7 +184,7 @@ pub fn profiled(meta: TokenStream, input: TokenStream) -> TokenStream {
 /// struct Foo;
 ///
 /// fn my_register_function(builder: &ClassBuilder<Foo>) {
-///     builder.add_signal(Signal { name: "foo", args: &[] });
+///     builder.add_signal(Signal { name: "foo", args: &[], num_default_args: 0 });
 ///     builder.add_property::<f32>("bar")
 ///         .with_getter(|_, _| 42.0)
 ///         .with_hint(FloatHint::Range(RangeHint::new(0.0, 100.0)))
@@ -136,6 +200,11 @@ pub fn profiled(meta: TokenStream, input: TokenStream) -> TokenStream {
 ///
 /// See documentation on `Instance::emplace` for an example on how this can be used.
 ///
+/// ### `#[tool]`
+///
+/// Registers this type as a tool class when using automatic registration with the
+/// `inventory` feature. See `InitHandle::add_auto_registered_classes`.
+///
 ///
 /// ## Field attributes
 ///
@@ -164,6 +233,21 @@ pub fn profiled(meta: TokenStream, input: TokenStream) -> TokenStream {
 /// - `no_editor`
 ///
 /// Hides the property from the editor. Does not prevent it from being sent over network or saved in storage.
+///
+/// - `rpc = "remote_sync"`
+///
+/// Sets the RPC mode used when the property is set remotely with `rset`. Accepts the same
+/// values as the `rpc` option of `#[export]`.
+///
+/// - `group = "Movement"`
+///
+/// Shows the property in the `Movement` group in the inspector. Properties are registered in
+/// the order of their fields. Since a group can't be ended or started again, fields of the
+/// same group must be declared next to each other, after all fields without a group.
+///
+/// ### `#[parent_script]`
+///
+/// Marks the `ParentScript` field used by `#[inherit_script]`.
 #[proc_macro_derive(
     NativeClass,
     attributes(
@@ -173,7 +257,10 @@ pub fn profiled(meta: TokenStream, input: TokenStream) -> TokenStream {
         user_data,
         property,
         register_with,
-        no_constructor
+        no_constructor,
+        tool,
+        inherit_script,
+        parent_script
     )
 )]
 pub fn derive_native_class(input: TokenStream) -> TokenStream {
@@ -222,6 +309,38 @@ pub fn derive_from_variant(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Makes a fieldless enum usable as a property, shown as a drop-down in the inspector.
+///
+/// The generated `Export` implementation registers the enum as an integer property with an
+/// enum hint, built from the names and discriminants of the variants. The `ToVariant` and
+/// `FromVariant` implementations of the enum must use the same integers, which is what the
+/// derive macros do with `#[variant(repr = "int")]`:
+///
+/// ```ignore
+/// #[derive(Copy, Clone, ToVariant, FromVariant, Export)]
+/// #[variant(repr = "int")]
+/// enum Mode {
+///     Idle,
+///     Patrol = 5,
+///     Chase,
+/// }
+///
+/// #[derive(NativeClass)]
+/// #[inherit(Node)]
+/// struct Enemy {
+///     #[property]
+///     mode: Mode,
+/// }
+/// ```
+#[proc_macro_derive(Export)]
+pub fn derive_export(input: TokenStream) -> TokenStream {
+    let derive_input = syn::parse_macro_input!(input as DeriveInput);
+    match export::derive_export(&derive_input) {
+        Ok(stream) => stream.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 /// Enable struct types to be parsed as argument lists.
 ///
 /// The `FromVarargs` trait can be derived for structure types where each type implements
@@ -258,3 +377,32 @@ fn automatically_derived() -> proc_macro2::TokenStream {
         #[allow(nonstandard_style, unused, clippy::style, clippy::complexity, clippy::perf, clippy::pedantic)]
     }
 }
+
+/// Returns the contents of the doc comments in `attrs`, with the leading space on each line
+/// removed, or `None` if there are no doc comments.
+fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(lit_str),
+                ..
+            })) => Some(lit_str.value()),
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.split('\n')
+                .map(|line| line.strip_prefix(' ').unwrap_or(line).to_owned())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let doc = lines.join("\n");
+    let doc = doc.trim();
+    if doc.is_empty() {
+        None
+    } else {
+        Some(doc.to_owned())
+    }
+}
@@ -1,4 +1,7 @@
-use syn::{spanned::Spanned, FnArg, ImplItem, ItemImpl, Pat, PatIdent, Signature, Type};
+use syn::{
+    spanned::Spanned, FnArg, Ident, ImplItem, ImplItemMethod, ItemImpl, Pat, PatIdent, Signature,
+    Type,
+};
 
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{quote, ToTokens};
@@ -16,7 +19,7 @@ pub enum RpcMode {
 }
 
 impl RpcMode {
-    fn parse(s: &str) -> Option<Self> {
+    pub(crate) fn parse(s: &str) -> Option<Self> {
         match s {
             "remote" => Some(RpcMode::Remote),
             "remote_sync" => Some(RpcMode::RemoteSync),
@@ -50,9 +53,51 @@ impl ToTokens for RpcMode {
     }
 }
 
+/// How errors returned from exported methods are reported, set with `#[export(err = "...")]`.
+/// Methods returning a `Result` use `Nil` by default.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ErrorReturn {
+    Nil,
+    Dict,
+}
+
+impl ErrorReturn {
+    pub(crate) fn parse(s: &str) -> Option<Self> {
+        match s {
+            "nil" => Some(ErrorReturn::Nil),
+            "dict" => Some(ErrorReturn::Dict),
+            _ => None,
+        }
+    }
+}
+
+impl ToTokens for ErrorReturn {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        match self {
+            ErrorReturn::Nil => tokens.extend(quote!(#[err = Nil])),
+            ErrorReturn::Dict => tokens.extend(quote!(#[err = Dict])),
+        }
+    }
+}
+
+/// Returns whether a method returns a `Result`, judging from the name of its return type.
+fn returns_result(output: &syn::ReturnType) -> bool {
+    match output {
+        syn::ReturnType::Type(_, ty) => match &**ty {
+            Type::Path(path) => match path.path.segments.last() {
+                Some(segment) => segment.ident == "Result",
+                None => false,
+            },
+            _ => false,
+        },
+        syn::ReturnType::Default => false,
+    }
+}
+
 pub(crate) struct ClassMethodExport {
     pub(crate) class_ty: Box<Type>,
     pub(crate) methods: Vec<ExportMethod>,
+    pub(crate) signals: Vec<ExportSignal>,
 }
 
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
@@ -66,6 +111,15 @@ pub(crate) struct ExportArgs {
     pub(crate) optional_args: Option<usize>,
     pub(crate) rpc_mode: RpcMode,
     pub(crate) name_override: Option<String>,
+    pub(crate) err_return: Option<ErrorReturn>,
+    pub(crate) documentation: Option<String>,
+}
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub(crate) struct ExportSignal {
+    pub(crate) name: Ident,
+    pub(crate) args: Vec<(Ident, Type)>,
+    pub(crate) documentation: Option<String>,
 }
 
 pub(crate) fn derive_methods(item_impl: ItemImpl) -> TokenStream2 {
@@ -85,6 +139,14 @@ pub(crate) fn derive_methods(item_impl: ItemImpl) -> TokenStream2 {
             let name = sig.ident;
             let name_string = args.name_override.unwrap_or_else(|| name.to_string());
             let ret_span = sig.output.span();
+
+            // Errors are logged and `nil` is returned by default.
+            let err_return = match args.err_return {
+                Some(err_return) => Some(err_return),
+                None if returns_result(&sig.output) => Some(ErrorReturn::Nil),
+                None => None,
+            };
+
             let ret_ty = match sig.output {
                 syn::ReturnType::Default => quote_spanned!(ret_span => ()),
                 syn::ReturnType::Type(_, ty) => quote_spanned!( ret_span => #ty ),
@@ -116,6 +178,9 @@ pub(crate) fn derive_methods(item_impl: ItemImpl) -> TokenStream2 {
             };
 
             let rpc = args.rpc_mode;
+            let with_documentation = args
+                .documentation
+                .map(|documentation| quote!(.with_documentation(#documentation)));
 
             let args = sig.inputs.iter().enumerate().map(|(n, arg)| {
                 let span = arg.span();
@@ -130,17 +195,59 @@ pub(crate) fn derive_methods(item_impl: ItemImpl) -> TokenStream2 {
                 {
                     let method = ::gdnative::godot_wrap_method!(
                         #class_name,
+                        #err_return
                         fn #name ( #( #args )* ) -> #ret_ty
                     );
 
                     #builder.build_method(#name_string, method)
                         .with_rpc_mode(#rpc)
+                        #with_documentation
                         .done_stateless();
                 }
             )
         })
         .collect::<Vec<_>>();
 
+    let signals = export
+        .signals
+        .into_iter()
+        .map(|signal| {
+            let name_string = signal.name.to_string();
+
+            let args = signal.args.iter().map(|(arg, ty)| {
+                let arg_string = arg.to_string();
+                quote!(
+                    SignalArgument {
+                        name: #arg_string,
+                        default: ::gdnative::core_types::Variant::new(),
+                        export_info: (&&&ArgExportInfo::<#ty>::new()).export_info(),
+                        usage: PropertyUsage::DEFAULT,
+                    }
+                )
+            });
+
+            let set_documentation = signal.documentation.map(|documentation| {
+                quote!(#builder.set_signal_documentation(#name_string, #documentation);)
+            });
+
+            quote!(
+                {
+                    use ::gdnative::nativescript::init::method::{
+                        AnyArgInfo, ArgExportInfo, ExportArgInfo, ObjectArgInfo,
+                    };
+
+                    #builder.add_signal_with_export_types(Signal {
+                        name: #name_string,
+                        args: &[ #( #args ),* ],
+                        num_default_args: 0,
+                    });
+
+                    #set_documentation
+                }
+            )
+        })
+        .collect::<Vec<_>>();
+
     quote::quote!(
         #impl_block
 
@@ -150,9 +257,25 @@ pub(crate) fn derive_methods(item_impl: ItemImpl) -> TokenStream2 {
                 use gdnative::nativescript::init::*;
 
                 #(#methods)*
+                #(#signals)*
+
+                {
+                    use ::gdnative::nativescript::init::property::dynamic::{
+                        DynamicPropertiesProbe, RegisterDynamicProperties,
+                        RegisterNoDynamicProperties,
+                    };
+
+                    (&&DynamicPropertiesProbe::<Self>::new()).register(#builder);
+                }
             }
         }
 
+        ::gdnative::godot_submit_class!({
+            fn register(handle: ::gdnative::nativescript::init::InitHandle) {
+                handle.add_auto_registered_class::<#class_name>();
+            }
+            register
+        });
     )
 }
 
@@ -171,6 +294,7 @@ fn impl_gdnative_expose(ast: ItemImpl) -> (ItemImpl, ClassMethodExport) {
     let mut export = ClassMethodExport {
         class_ty: ast.self_ty,
         methods: vec![],
+        signals: vec![],
     };
 
     let mut methods_to_export: Vec<ExportMethod> = Vec::new();
@@ -179,10 +303,21 @@ fn impl_gdnative_expose(ast: ItemImpl) -> (ItemImpl, ClassMethodExport) {
     // add all items back to the impl block again.
     for func in ast.items {
         let items = match func {
+            ImplItem::Method(method) if method.attrs.iter().any(is_signal_attr) => {
+                match impl_signal(method) {
+                    Ok((signal, emit)) => {
+                        export.signals.push(signal);
+                        vec![emit]
+                    }
+                    Err(err) => vec![ImplItem::Verbatim(err.to_compile_error())],
+                }
+            }
             ImplItem::Method(mut method) => {
                 let mut export_args = None;
                 let mut rpc = None;
                 let mut name_override = None;
+                let mut err_return = None;
+                let mut rpc_helpers = vec![];
 
                 let mut errors = vec![];
 
@@ -305,6 +440,34 @@ fn impl_gdnative_expose(ast: ItemImpl) -> (ItemImpl, ClassMethodExport) {
                                                 return false;
                                             }
                                         }
+                                        // error reporting
+                                        "err" => {
+                                            let value = if let syn::Lit::Str(lit_str) = lit {
+                                                lit_str.value()
+                                            } else {
+                                                errors.push(syn::Error::new(
+                                                    last.span(),
+                                                    "unexpected type for err value, expected Str",
+                                                ));
+                                                return false;
+                                            };
+
+                                            if let Some(mode) = ErrorReturn::parse(value.as_str()) {
+                                                if err_return.replace(mode).is_some() {
+                                                    errors.push(syn::Error::new(
+                                                        last.span(),
+                                                        "err was set more than once",
+                                                    ));
+                                                    return false;
+                                                }
+                                            } else {
+                                                errors.push(syn::Error::new(
+                                                    last.span(),
+                                                    format!("unexpected value for err: {}", value),
+                                                ));
+                                                return false;
+                                            }
+                                        }
                                         _ => {
                                             let msg =
                                                 format!("unknown option for export: `{}`", path);
@@ -363,6 +526,15 @@ fn impl_gdnative_expose(ast: ItemImpl) -> (ItemImpl, ClassMethodExport) {
                     export_args.optional_args = optional_args;
                     export_args.rpc_mode = rpc.unwrap_or(RpcMode::Disabled);
                     export_args.name_override = name_override;
+                    export_args.err_return = err_return;
+                    export_args.documentation = crate::doc_comment(&method.attrs);
+
+                    if export_args.rpc_mode != RpcMode::Disabled {
+                        match impl_rpc_helpers(&method, &export_args) {
+                            Ok(helpers) => rpc_helpers.extend(helpers),
+                            Err(err) => errors.push(err),
+                        }
+                    }
 
                     methods_to_export.push(ExportMethod {
                         sig: method.sig.clone(),
@@ -374,6 +546,7 @@ fn impl_gdnative_expose(ast: ItemImpl) -> (ItemImpl, ClassMethodExport) {
                     .into_iter()
                     .map(|err| ImplItem::Verbatim(err.to_compile_error()))
                     .chain(std::iter::once(ImplItem::Method(method)))
+                    .chain(rpc_helpers)
                     .collect()
             }
             item => vec![item],
@@ -452,3 +625,305 @@ fn impl_gdnative_expose(ast: ItemImpl) -> (ItemImpl, ClassMethodExport) {
 
     (result, export)
 }
+
+/// Generates the `rpc_name`, `rpc_id_name`, `rpc_unreliable_name` and `rpc_unreliable_id_name`
+/// functions for an exported method with an RPC mode, which call the method remotely with
+/// typed arguments. Optional arguments are taken as `Option`s, and passed up to the first
+/// `None`. The helpers are `pub(crate)` regardless of the method's visibility, since callers
+/// usually live in other modules than the method.
+fn impl_rpc_helpers(
+    method: &ImplItemMethod,
+    args: &ExportArgs,
+) -> Result<Vec<ImplItem>, syn::Error> {
+    let sig = &method.sig;
+    let mut inputs = sig.inputs.iter().enumerate();
+
+    match inputs.next() {
+        Some((_, FnArg::Receiver(_))) => {}
+        _ => {
+            return Err(syn::Error::new(
+                sig.ident.span(),
+                "rpc methods must take self as the first argument",
+            ))
+        }
+    }
+
+    let owner_ty = match inputs.next() {
+        Some((_, FnArg::Typed(owner))) => &*owner.ty,
+        _ => {
+            return Err(syn::Error::new(
+                sig.ident.span(),
+                "rpc methods must take owner as the second argument",
+            ))
+        }
+    };
+
+    // Other classes not inheriting `Node` are rejected by the `SubClass` bound below, since
+    // the class hierarchy isn't known here.
+    if let Some(base) = owner_class_name(owner_ty) {
+        if base == "Object" || base == "Reference" {
+            return Err(syn::Error::new(
+                owner_ty.span(),
+                format!(
+                    "rpc modes can only be set on methods of classes inheriting `Node`, not `{}`",
+                    base,
+                ),
+            ));
+        }
+    }
+
+    let rpc_args = inputs
+        .map(|(i, arg)| match arg {
+            FnArg::Typed(cap) => match &*cap.pat {
+                Pat::Ident(ident) => Ok((ident.ident.clone(), (*cap.ty).clone())),
+                Pat::Wild(_) => Ok((quote::format_ident!("arg_{}", i), (*cap.ty).clone())),
+                pat => Err(syn::Error::new(
+                    pat.span(),
+                    "arguments of rpc methods must be plain identifiers",
+                )),
+            },
+            FnArg::Receiver(receiver) => {
+                Err(syn::Error::new(receiver.span(), "unexpected self argument"))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let name = &sig.ident;
+    let name_string = args
+        .name_override
+        .clone()
+        .unwrap_or_else(|| name.to_string());
+
+    let (required_args, optional_args) =
+        rpc_args.split_at(rpc_args.len() - args.optional_args.unwrap_or(0));
+    let required_names = required_args.iter().map(|(arg, _)| arg).collect::<Vec<_>>();
+    let required_types = required_args.iter().map(|(_, ty)| ty).collect::<Vec<_>>();
+    let optional_names = optional_args.iter().map(|(arg, _)| arg).collect::<Vec<_>>();
+    let optional_types = optional_args.iter().map(|(_, ty)| ty).collect::<Vec<_>>();
+
+    let helpers = [
+        ("rpc", false),
+        ("rpc_id", true),
+        ("rpc_unreliable", false),
+        ("rpc_unreliable_id", true),
+    ];
+
+    let helpers = helpers
+        .iter()
+        .map(|&(call, with_peer_id)| {
+            let call = syn::Ident::new(call, name.span());
+            let helper_name = quote::format_ident!("{}_{}", call, name);
+            let peer_id = if with_peer_id {
+                Some(quote!(peer_id,))
+            } else {
+                None
+            };
+            let peer_id_arg = peer_id.as_ref().map(|_| quote!(peer_id: i64,));
+            let doc = format!(
+                "Calls `{}` remotely with `Node::{}`, using the method's RPC mode.",
+                name_string, call,
+            );
+
+            let upcast = quote_spanned!(name.span() =>
+                let owner = ::gdnative::object::GodotObject::upcast::<::gdnative::api::Node>(owner);
+            );
+
+            ImplItem::Verbatim(quote!(
+                #[doc = #doc]
+                #[allow(dead_code, clippy::too_many_arguments)]
+                pub(crate) fn #helper_name(
+                    owner: &<Self as ::gdnative::nativescript::NativeClass>::Base,
+                    #peer_id_arg
+                    #( #required_names: #required_types, )*
+                    #( #optional_names: ::std::option::Option<#optional_types>, )*
+                ) {
+                    #upcast
+
+                    #[allow(unused_mut)]
+                    let mut __args: ::std::vec::Vec<::gdnative::core_types::Variant> = vec![
+                        #( ::gdnative::core_types::OwnedToVariant::owned_to_variant(#required_names), )*
+                    ];
+
+                    let __optional_args: ::std::vec::Vec<::std::option::Option<::gdnative::core_types::Variant>> = vec![
+                        #( #optional_names.map(::gdnative::core_types::OwnedToVariant::owned_to_variant), )*
+                    ];
+                    __args.extend(__optional_args.into_iter().take_while(|arg| arg.is_some()).flatten());
+
+                    owner.#call(#peer_id #name_string, &__args);
+                }
+            ))
+        })
+        .collect();
+
+    Ok(helpers)
+}
+
+/// Returns the name of the class in an owner argument type like `&Node` or `TRef<Node>`, if
+/// it can be determined.
+fn owner_class_name(ty: &Type) -> Option<String> {
+    let path = match ty {
+        Type::Reference(reference) => return owner_class_name(&reference.elem),
+        Type::Path(path) => &path.path,
+        _ => return None,
+    };
+
+    let last = path.segments.last()?;
+    match &last.arguments {
+        syn::PathArguments::None => Some(last.ident.to_string()),
+        syn::PathArguments::AngleBracketed(args) if last.ident == "TRef" => {
+            args.args.iter().find_map(|arg| match arg {
+                syn::GenericArgument::Type(ty) => owner_class_name(ty),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+fn is_signal_attr(attr: &syn::Attribute) -> bool {
+    matches!(attr.style, syn::AttrStyle::Outer) && attr.path.is_ident("signal")
+}
+
+/// Extracts a signal declared as `#[signal] fn name(arg: Type, ...);`, and generates a
+/// `emit_name(owner, arg, ...)` function to replace the declaration.
+fn impl_signal(mut method: ImplItemMethod) -> Result<(ExportSignal, ImplItem), syn::Error> {
+    method.attrs.retain(|attr| !is_signal_attr(attr));
+
+    let sig = &method.sig;
+    let generics = &sig.generics;
+    if !generics.params.is_empty() || generics.where_clause.is_some() {
+        return Err(syn::Error::new(
+            generics.span(),
+            "signals cannot have generic parameters",
+        ));
+    }
+
+    if let syn::ReturnType::Type(_, ty) = &sig.output {
+        return Err(syn::Error::new(
+            ty.span(),
+            "signals cannot have return types",
+        ));
+    }
+
+    let args = sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Typed(cap) => match &*cap.pat {
+                Pat::Ident(ident) => Ok((ident.ident.clone(), (*cap.ty).clone())),
+                pat => Err(syn::Error::new(
+                    pat.span(),
+                    "signal arguments must be plain identifiers",
+                )),
+            },
+            FnArg::Receiver(receiver) => Err(syn::Error::new(
+                receiver.span(),
+                "signals cannot take self as an argument",
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let name = sig.ident.clone();
+    let name_string = name.to_string();
+    let emit_name = quote::format_ident!("emit_{}", name);
+    let vis = &method.vis;
+    let attrs = &method.attrs;
+    let arg_names = args.iter().map(|(arg, _)| arg);
+    let arg_types = args.iter().map(|(_, ty)| ty);
+    let arg_values = args.iter().map(|(arg, _)| arg);
+
+    let emit = ImplItem::Verbatim(quote!(
+        #(#attrs)*
+        #[allow(clippy::too_many_arguments)]
+        #vis fn #emit_name(
+            owner: &<Self as ::gdnative::nativescript::NativeClass>::Base,
+            #( #arg_names: #arg_types ),*
+        ) {
+            owner.emit_signal(
+                #name_string,
+                &[ #( ::gdnative::core_types::OwnedToVariant::owned_to_variant(#arg_values) ),* ],
+            );
+        }
+    ));
+
+    let signal = ExportSignal {
+        name,
+        args,
+        documentation: crate::doc_comment(&method.attrs),
+    };
+
+    Ok((signal, emit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpc_helpers(source: &str) -> Vec<ImplItem> {
+        let item_impl: ItemImpl = syn::parse_str(source).unwrap();
+        let (item_impl, _) = impl_gdnative_expose(item_impl);
+        item_impl.items
+    }
+
+    fn helper(items: &[ImplItem], name: &str) -> ImplItemMethod {
+        items
+            .iter()
+            .filter_map(|item| match item {
+                ImplItem::Verbatim(tokens) => syn::parse2::<ImplItemMethod>(tokens.clone()).ok(),
+                _ => None,
+            })
+            .find(|method| method.sig.ident == name)
+            .unwrap_or_else(|| panic!("helper `{}` should be generated", name))
+    }
+
+    #[test]
+    fn derive_rpc_helpers() {
+        let items = rpc_helpers(
+            r#"
+            impl Foo {
+                #[export(rpc = "remote_sync", name = "renamed")]
+                fn foo(&mut self, _owner: TRef<Node>, a: i64, #[opt] b: String) {}
+            }"#,
+        );
+
+        for name in &[
+            "rpc_foo",
+            "rpc_id_foo",
+            "rpc_unreliable_foo",
+            "rpc_unreliable_id_foo",
+        ] {
+            let helper = helper(&items, name);
+            let body = helper.block.to_token_stream().to_string();
+            assert!(body.contains("\"renamed\""), "{}: {}", name, body);
+        }
+
+        let helper = helper(&items, "rpc_id_foo");
+        let inputs = helper
+            .sig
+            .inputs
+            .iter()
+            .map(|arg| arg.to_token_stream().to_string().replace(' ', ""))
+            .collect::<Vec<_>>();
+
+        assert_eq!(4, inputs.len());
+        assert!(inputs[1].starts_with("peer_id:"));
+        assert_eq!("a:i64", inputs[2]);
+        assert_eq!("b:::std::option::Option<String>", inputs[3]);
+    }
+
+    #[test]
+    fn derive_rpc_helpers_non_node_base() {
+        let items = rpc_helpers(
+            r#"
+            impl Foo {
+                #[export(rpc = "remote")]
+                fn foo(&mut self, _owner: &Reference, a: i64) {}
+            }"#,
+        );
+
+        assert!(items.iter().any(|item| match item {
+            ImplItem::Verbatim(tokens) => tokens.to_string().contains("compile_error"),
+            _ => false,
+        }));
+    }
+}
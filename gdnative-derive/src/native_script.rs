@@ -1,9 +1,8 @@
 use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 
-use std::collections::HashMap;
 use syn::spanned::Spanned;
-use syn::{Data, DeriveInput, Fields, Ident, Meta, MetaList, NestedMeta, Path, Stmt, Type};
+use syn::{Data, DeriveInput, Fields, Ident, Member, Meta, MetaList, NestedMeta, Path, Stmt, Type};
 
 mod property_args;
 use property_args::{PropertyAttrArgs, PropertyAttrArgsBuilder};
@@ -13,8 +12,16 @@ pub(crate) struct DeriveData {
     pub(crate) base: Type,
     pub(crate) register_callback: Option<Path>,
     pub(crate) user_data: Type,
-    pub(crate) properties: HashMap<Ident, PropertyAttrArgs>,
+    pub(crate) properties: Vec<(Ident, PropertyAttrArgs)>,
     pub(crate) no_constructor: bool,
+    pub(crate) is_tool: bool,
+    pub(crate) inherit_script: Option<InheritScript>,
+    pub(crate) documentation: Option<String>,
+}
+
+pub(crate) struct InheritScript {
+    pub(crate) parent: Type,
+    pub(crate) field: Member,
 }
 
 pub(crate) fn impl_empty_nativeclass(derive_input: &DeriveInput) -> TokenStream2 {
@@ -50,18 +57,38 @@ pub(crate) fn derive_native_class(derive_input: &DeriveInput) -> Result<TokenStr
             .register_callback
             .map(|function_path| quote!(#function_path(builder);))
             .unwrap_or(quote!({}));
+        // Groups apply to all properties registered after them. The properties of a group are
+        // declared next to each other, so a group is started with the first of its properties.
+        let mut current_group = None;
         let properties = data.properties.into_iter().map(|(ident, config)| {
+            let group = if config.group != current_group {
+                current_group = config.group.clone();
+                config
+                    .group
+                    .map(|group| quote!(builder.add_property_group(#group, "");))
+            } else {
+                None
+            };
+
             let with_default = config
                 .default
                 .map(|default_value| quote!(.with_default(#default_value)));
             let with_hint = config.hint.map(|hint_fn| quote!(.with_hint(#hint_fn())));
 
+            let with_documentation = config
+                .documentation
+                .map(|documentation| quote!(.with_documentation(#documentation)));
+
             let with_usage = if config.no_editor {
                 Some(quote!(.with_usage(::gdnative::nativescript::init::property::Usage::NOEDITOR)))
             } else {
                 None
             };
 
+            let with_rpc_mode = config
+                .rpc_mode
+                .map(|rpc| quote!(.with_rpc_mode(::gdnative::nativescript::init::#rpc)));
+
             let before_get: Option<Stmt> = config
                 .before_get
                 .map(|path_expr| parse_quote!(#path_expr(this, _owner);));
@@ -80,10 +107,13 @@ pub(crate) fn derive_native_class(derive_input: &DeriveInput) -> Result<TokenStr
 
             let label = config.path.unwrap_or_else(|| format!("{}", ident));
             quote!({
+                #group
                 builder.add_property(#label)
                     #with_default
                     #with_hint
                     #with_usage
+                    #with_rpc_mode
+                    #with_documentation
                     .with_ref_getter(|this: &#name, _owner: ::gdnative::TRef<Self::Base>| {
                         #before_get
                         let res = &this.#ident;
@@ -99,6 +129,25 @@ pub(crate) fn derive_native_class(derive_input: &DeriveInput) -> Result<TokenStr
             })
         });
 
+        let (inherit_script, sub_class_impl) = match data.inherit_script {
+            Some(InheritScript { parent, field }) => (
+                Some(quote!(builder.inherit_script::<#parent>();)),
+                Some(quote!(
+                    #derived
+                    impl ::gdnative::nativescript::NativeSubClass<#parent> for #name {
+                        fn parent_script(&self) -> &::gdnative::nativescript::ParentScript<#parent> {
+                            &self.#field
+                        }
+                    }
+                )),
+            ),
+            None => (None, None),
+        };
+
+        let documentation = data
+            .documentation
+            .map(|documentation| quote!(builder.set_class_documentation(#documentation);));
+
         // string variant needed for the `class_name` function.
         let name_str = quote!(#name).to_string();
 
@@ -112,6 +161,16 @@ pub(crate) fn derive_native_class(derive_input: &DeriveInput) -> Result<TokenStr
             })
         };
 
+        let is_tool = if data.is_tool {
+            Some(quote! {
+                fn is_tool() -> bool {
+                    true
+                }
+            })
+        } else {
+            None
+        };
+
         quote!(
             #derived
             impl ::gdnative::nativescript::NativeClass for #name {
@@ -124,11 +183,17 @@ pub(crate) fn derive_native_class(derive_input: &DeriveInput) -> Result<TokenStr
 
                 #init
 
+                #is_tool
+
                 fn register_properties(builder: &::gdnative::nativescript::init::ClassBuilder<Self>) {
+                    #inherit_script
+                    #documentation
                     #(#properties)*;
                     #register_callback
                 }
             }
+
+            #sub_class_impl
         )
     };
 
@@ -143,9 +208,19 @@ fn parse_derive_input(input: &DeriveInput) -> Result<DeriveData, syn::Error> {
 
     let inherit_attr = input.attrs.iter().find(|a| a.path.is_ident("inherit"));
 
+    let parent_script = input
+        .attrs
+        .iter()
+        .find(|a| a.path.is_ident("inherit_script"))
+        .map(|attr| attr.parse_args::<Type>())
+        .transpose()?;
+
     // read base class
     let base = if let Some(attr) = inherit_attr {
         attr.parse_args::<Type>()?
+    } else if let Some(parent) = &parent_script {
+        syn::parse2::<Type>(quote! { <#parent as ::gdnative::nativescript::NativeClass>::Base })
+            .unwrap()
     } else {
         syn::parse2::<Type>(quote! { ::gdnative::api::Reference }).unwrap()
     };
@@ -174,6 +249,8 @@ fn parse_derive_input(input: &DeriveInput) -> Result<DeriveData, syn::Error> {
         .iter()
         .any(|a| a.path.is_ident("no_constructor"));
 
+    let is_tool = input.attrs.iter().any(|a| a.path.is_ident("tool"));
+
     // make sure it's a struct
     let struct_data = if let Data::Struct(data) = &input.data {
         data
@@ -184,8 +261,47 @@ fn parse_derive_input(input: &DeriveInput) -> Result<DeriveData, syn::Error> {
         ));
     };
 
+    // Find the field holding the parent script state, if any
+    let mut parent_script_fields = struct_data
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| field.attrs.iter().any(|a| a.path.is_ident("parent_script")))
+        .map(|(index, field)| {
+            field
+                .ident
+                .clone()
+                .map(Member::Named)
+                .unwrap_or_else(|| Member::Unnamed(index.into()))
+        });
+
+    let parent_script_field = parent_script_fields.next();
+    if let Some(field) = parent_script_fields.next() {
+        return Err(syn::Error::new(
+            field.span(),
+            "only one field can be marked with `#[parent_script]`",
+        ));
+    }
+
+    let inherit_script = match (parent_script, parent_script_field) {
+        (Some(parent), Some(field)) => Some(InheritScript { parent, field }),
+        (Some(parent), None) => {
+            return Err(syn::Error::new(
+                parent.span(),
+                "`#[inherit_script]` requires a field marked with `#[parent_script]`",
+            ))
+        }
+        (None, Some(field)) => {
+            return Err(syn::Error::new(
+                field.span(),
+                "`#[parent_script]` can only be used with `#[inherit_script]`",
+            ))
+        }
+        (None, None) => None,
+    };
+
     // Find all fields with a `#[property]` attribute
-    let mut properties = HashMap::new();
+    let mut properties = Vec::new();
 
     if let Fields::Named(names) = &struct_data.fields {
         for field in &names.named {
@@ -229,11 +345,39 @@ fn parse_derive_input(input: &DeriveInput) -> Result<DeriveData, syn::Error> {
                     .ident
                     .clone()
                     .ok_or_else(|| syn::Error::new(field.ident.span(), "Fields should be named"))?;
-                properties.insert(ident, builder.done());
+                let mut property_args = builder.done();
+                property_args.documentation = crate::doc_comment(&field.attrs);
+                properties.push((ident, property_args));
             }
         }
     };
 
+    // Godot keeps the properties of a script in a map keyed by name, so a group can't be
+    // started again once it's registered, and there is no way to end one. Properties are
+    // registered in declaration order, so this has to hold for the fields.
+    let mut groups = Vec::new();
+    for (ident, property_args) in &properties {
+        match &property_args.group {
+            None if !groups.is_empty() => {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "properties without a group must be declared before all grouped properties",
+                ));
+            }
+            Some(group) if groups.last() != Some(&group) => {
+                if groups.contains(&group) {
+                    let message = format!(
+                        "properties of the group `{}` must be declared next to each other",
+                        group,
+                    );
+                    return Err(syn::Error::new(ident.span(), message));
+                }
+                groups.push(group);
+            }
+            _ => {}
+        }
+    }
+
     Ok(DeriveData {
         name: ident,
         base,
@@ -241,6 +385,9 @@ fn parse_derive_input(input: &DeriveInput) -> Result<DeriveData, syn::Error> {
         user_data,
         properties,
         no_constructor,
+        is_tool,
+        inherit_script,
+        documentation: crate::doc_comment(&input.attrs),
     })
 }
 
@@ -315,4 +462,198 @@ mod tests {
 
         parse_derive_input(&input).unwrap();
     }
+
+    #[test]
+    fn derive_property_rpc_mode() {
+        let input: TokenStream2 = syn::parse_str(
+            r#"
+            #[inherit(Node)]
+            struct Foo {
+                #[property(rpc = "remote_sync")]
+                bar: String,
+            }"#,
+        )
+        .unwrap();
+
+        let input: DeriveInput = syn::parse2(input).unwrap();
+
+        let data = parse_derive_input(&input).unwrap();
+        let property = &data.properties[0].1;
+        assert_eq!(Some(crate::methods::RpcMode::RemoteSync), property.rpc_mode);
+
+        let input: TokenStream2 = syn::parse_str(
+            r#"
+            #[inherit(Node)]
+            struct Foo {
+                #[property(rpc = "everyone")]
+                bar: String,
+            }"#,
+        )
+        .unwrap();
+
+        let input: DeriveInput = syn::parse2(input).unwrap();
+        assert!(parse_derive_input(&input).is_err());
+    }
+
+    #[test]
+    fn derive_tool() {
+        let input: TokenStream2 = syn::parse_str(
+            r#"
+            #[inherit(Node)]
+            #[tool]
+            struct Foo {
+                #[property]
+                bar: String,
+            }"#,
+        )
+        .unwrap();
+
+        let input: DeriveInput = syn::parse2(input).unwrap();
+
+        let data = parse_derive_input(&input).unwrap();
+        assert!(data.is_tool);
+    }
+
+    #[test]
+    fn derive_property_group() {
+        let input: TokenStream2 = syn::parse_str(
+            r#"
+            #[inherit(Node)]
+            struct Foo {
+                #[property]
+                health: i32,
+                #[property(group = "Movement")]
+                speed: f32,
+                #[property(group = "Movement")]
+                acceleration: f32,
+                #[property(group = "Combat")]
+                damage: f32,
+            }"#,
+        )
+        .unwrap();
+
+        let input: DeriveInput = syn::parse2(input).unwrap();
+
+        let data = parse_derive_input(&input).unwrap();
+        let groups = data
+            .properties
+            .iter()
+            .map(|(ident, property)| (ident.to_string(), property.group.as_deref()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                ("health".to_string(), None),
+                ("speed".to_string(), Some("Movement")),
+                ("acceleration".to_string(), Some("Movement")),
+                ("damage".to_string(), Some("Combat")),
+            ],
+            groups
+        );
+    }
+
+    #[test]
+    fn derive_property_group_not_contiguous() {
+        let input: TokenStream2 = syn::parse_str(
+            r#"
+            #[inherit(Node)]
+            struct Foo {
+                #[property(group = "Movement")]
+                speed: f32,
+                #[property(group = "Combat")]
+                damage: f32,
+                #[property(group = "Movement")]
+                acceleration: f32,
+            }"#,
+        )
+        .unwrap();
+
+        let input: DeriveInput = syn::parse2(input).unwrap();
+        assert!(parse_derive_input(&input).is_err());
+    }
+
+    #[test]
+    fn derive_property_group_ungrouped_after_group() {
+        let input: TokenStream2 = syn::parse_str(
+            r#"
+            #[inherit(Node)]
+            struct Foo {
+                #[property(group = "Movement")]
+                speed: f32,
+                #[property]
+                health: i32,
+            }"#,
+        )
+        .unwrap();
+
+        let input: DeriveInput = syn::parse2(input).unwrap();
+        assert!(parse_derive_input(&input).is_err());
+    }
+
+    #[test]
+    fn derive_documentation() {
+        let input: TokenStream2 = syn::parse_str(
+            r#"
+            /// A foo.
+            ///
+            /// Very useful.
+            #[inherit(Node)]
+            struct Foo {
+                /// The bar of the foo.
+                #[property]
+                bar: String,
+            }"#,
+        )
+        .unwrap();
+
+        let input: DeriveInput = syn::parse2(input).unwrap();
+
+        let data = parse_derive_input(&input).unwrap();
+        assert_eq!(
+            Some("A foo.\n\nVery useful."),
+            data.documentation.as_deref()
+        );
+
+        let property = &data.properties[0].1;
+        assert_eq!(
+            Some("The bar of the foo."),
+            property.documentation.as_deref()
+        );
+    }
+
+    #[test]
+    fn derive_inherit_script() {
+        let input: TokenStream2 = syn::parse_str(
+            r#"
+            #[inherit_script(Enemy)]
+            struct FlyingEnemy {
+                #[parent_script]
+                parent: ParentScript<Enemy>,
+                altitude: f32,
+            }"#,
+        )
+        .unwrap();
+
+        let input: DeriveInput = syn::parse2(input).unwrap();
+
+        let data = parse_derive_input(&input).unwrap();
+        let inherit_script = data.inherit_script.unwrap();
+        assert_eq!(inherit_script.field, parse_quote!(parent));
+    }
+
+    #[test]
+    fn derive_inherit_script_without_field_err() {
+        let input: TokenStream2 = syn::parse_str(
+            r#"
+            #[inherit_script(Enemy)]
+            struct FlyingEnemy {
+                parent: ParentScript<Enemy>,
+            }"#,
+        )
+        .unwrap();
+
+        let input: DeriveInput = syn::parse2(input).unwrap();
+
+        assert!(parse_derive_input(&input).is_err());
+    }
 }
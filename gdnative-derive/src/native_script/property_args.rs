@@ -1,5 +1,7 @@
 use syn::spanned::Spanned;
 
+use crate::methods::RpcMode;
+
 pub struct PropertyAttrArgs {
     pub path: Option<String>,
     pub default: Option<syn::Lit>,
@@ -9,6 +11,9 @@ pub struct PropertyAttrArgs {
     pub before_set: Option<syn::Path>,
     pub after_set: Option<syn::Path>,
     pub no_editor: bool,
+    pub rpc_mode: Option<RpcMode>,
+    pub group: Option<String>,
+    pub documentation: Option<String>,
 }
 
 #[derive(Default)]
@@ -21,6 +26,8 @@ pub struct PropertyAttrArgsBuilder {
     before_set: Option<syn::Path>,
     after_set: Option<syn::Path>,
     no_editor: bool,
+    rpc_mode: Option<RpcMode>,
+    group: Option<String>,
 }
 
 impl PropertyAttrArgsBuilder {
@@ -159,6 +166,43 @@ impl PropertyAttrArgsBuilder {
                     ));
                 }
             }
+            "rpc" => {
+                let string = if let syn::Lit::Str(lit_str) = &pair.lit {
+                    lit_str.value()
+                } else {
+                    return Err(syn::Error::new(
+                        pair.span(),
+                        "rpc value is not a string literal".to_string(),
+                    ));
+                };
+
+                let mode = RpcMode::parse(string.as_str()).ok_or_else(|| {
+                    syn::Error::new(path_span, format!("unexpected value for rpc: {}", string))
+                })?;
+                if let Some(old) = self.rpc_mode.replace(mode) {
+                    return Err(syn::Error::new(
+                        pair.span(),
+                        format!("there is already a rpc value set: {:?}", old),
+                    ));
+                }
+            }
+            "group" => {
+                let string = if let syn::Lit::Str(lit_str) = &pair.lit {
+                    lit_str.value()
+                } else {
+                    return Err(syn::Error::new(
+                        pair.span(),
+                        "group value is not a string literal".to_string(),
+                    ));
+                };
+
+                if let Some(old) = self.group.replace(string) {
+                    return Err(syn::Error::new(
+                        pair.span(),
+                        format!("there is already a group set: {:?}", old),
+                    ));
+                }
+            }
             _ => {
                 return Err(syn::Error::new(
                     pair.span(),
@@ -195,6 +239,9 @@ impl PropertyAttrArgsBuilder {
             before_set: self.before_set,
             after_set: self.after_set,
             no_editor: self.no_editor,
+            rpc_mode: self.rpc_mode,
+            group: self.group,
+            documentation: None,
         }
     }
 }
@@ -4,15 +4,18 @@ use syn::{spanned::Spanned, Data, DeriveInput, Generics, Ident};
 mod attr;
 mod bounds;
 mod from;
+mod rename;
 mod repr;
 mod to;
 
+use attr::{ContainerAttr, ContainerAttrBuilder, EnumRepr};
 use bounds::extend_bounds;
 use repr::{Repr, VariantRepr};
 
 pub(crate) struct DeriveData {
     pub(crate) ident: Ident,
     pub(crate) repr: Repr,
+    pub(crate) attr: ContainerAttr,
     pub(crate) generics: Generics,
 }
 
@@ -56,8 +59,19 @@ pub(crate) fn parse_derive_input(
     bound: &syn::Path,
     dir: Direction,
 ) -> Result<DeriveData, syn::Error> {
+    let attr = input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("variant"))
+        .map(|attr| attr.parse_meta())
+        .collect::<Result<ContainerAttrBuilder, syn::Error>>()?
+        .done()?;
+
+    // `rename_all` applies to the fields of structs, and to the variants of enums.
     let repr = match input.data {
-        Data::Struct(struct_data) => Repr::Struct(VariantRepr::repr_for(&struct_data.fields)?),
+        Data::Struct(struct_data) => {
+            Repr::Struct(VariantRepr::repr_for(&struct_data.fields, attr.rename_all)?)
+        }
         Data::Enum(enum_data) => Repr::Enum(
             enum_data
                 .variants
@@ -65,7 +79,7 @@ pub(crate) fn parse_derive_input(
                 .map(|variant| {
                     Ok((
                         variant.ident.clone(),
-                        VariantRepr::repr_for(&variant.fields)?,
+                        VariantRepr::repr_for(&variant.fields, None)?,
                     ))
                 })
                 .collect::<Result<_, syn::Error>>()?,
@@ -78,11 +92,80 @@ pub(crate) fn parse_derive_input(
         }
     };
 
+    if attr.enum_repr == EnumRepr::Int {
+        let is_fieldless = match &repr {
+            Repr::Struct(_) => false,
+            Repr::Enum(variants) => variants
+                .iter()
+                .all(|(_, var_repr)| *var_repr == VariantRepr::Unit),
+        };
+
+        if !is_fieldless {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "#[variant(repr = \"int\")] is only valid for enums without fields",
+            ));
+        }
+    }
+
+    match (&attr.enum_repr, &repr) {
+        (EnumRepr::ExternallyTagged, _) | (EnumRepr::Int, _) => {}
+        (_, Repr::Struct(_)) => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "#[variant(tag = \"...\")] and #[variant(untagged)] are only valid for enums",
+            ));
+        }
+        (EnumRepr::InternallyTagged { tag }, Repr::Enum(variants)) => {
+            for (var_ident, var_repr) in variants {
+                match var_repr {
+                    VariantRepr::Unit => {}
+                    VariantRepr::Tuple(_) => {
+                        return Err(syn::Error::new(
+                            var_ident.span(),
+                            "internally tagged enums can only contain unit and struct variants",
+                        ));
+                    }
+                    VariantRepr::Struct(fields) => {
+                        if let Some(field) =
+                            fields.iter().find(|f| !f.attr.flatten && f.name == *tag)
+                        {
+                            return Err(syn::Error::new(
+                                field.ident.span(),
+                                "field name conflicts with the enum tag",
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+        (_, Repr::Enum(_)) => {}
+    }
+
+    if attr.deny_unknown_fields {
+        let var_reprs: Vec<&VariantRepr> = match &repr {
+            Repr::Struct(var_repr) => vec![var_repr],
+            Repr::Enum(variants) => variants.iter().map(|(_, var_repr)| var_repr).collect(),
+        };
+
+        for var_repr in var_reprs {
+            if let VariantRepr::Struct(fields) = var_repr {
+                if let Some(field) = fields.iter().find(|f| f.attr.flatten) {
+                    return Err(syn::Error::new(
+                        field.ident.span(),
+                        "#[variant(flatten)] cannot be used with #[variant(deny_unknown_fields)]",
+                    ));
+                }
+            }
+        }
+    }
+
     let generics = extend_bounds(input.generics, &repr, bound, dir);
 
     Ok(DeriveData {
         ident: input.ident,
         repr,
+        attr,
         generics,
     })
 }
@@ -3,14 +3,27 @@ use std::iter::FromIterator;
 use proc_macro2::Span;
 use syn::spanned::Spanned;
 
+use super::rename::RenameRule;
 use super::Direction;
 
+/// Value used for a field that is missing or skipped when converting from `Variant`.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub(crate) enum FieldDefault {
+    /// `Default::default()`
+    Default,
+    /// A call to the given function.
+    Path(syn::Path),
+}
+
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub struct Attr {
     pub skip_to_variant: bool,
     pub skip_from_variant: bool,
     pub to_variant_with: Option<syn::Path>,
     pub from_variant_with: Option<syn::Path>,
+    pub default: Option<FieldDefault>,
+    pub rename: Option<String>,
+    pub flatten: bool,
 }
 
 impl Attr {
@@ -28,6 +41,9 @@ pub struct AttrBuilder {
     skip_from_variant: bool,
     to_variant_with: Option<syn::Path>,
     from_variant_with: Option<syn::Path>,
+    default: Option<FieldDefault>,
+    rename: Option<String>,
+    flatten: bool,
     errors: Vec<syn::Error>,
 }
 
@@ -93,16 +109,25 @@ impl AttrBuilder {
             match name.as_str() {
                 skip_to_variant,
                 skip_from_variant,
+                flatten,
             }
         }
 
-        #[allow(clippy::single_match)]
         match name.as_str() {
             "skip" => {
                 self.skip_to_variant = true;
                 self.skip_from_variant = true;
                 return Ok(());
             }
+            "default" => {
+                if self.default.replace(FieldDefault::Default).is_some() {
+                    return Err(syn::Error::new(
+                        flag.span(),
+                        "the argument default is already set",
+                    ));
+                }
+                return Ok(());
+            }
             _ => {}
         }
 
@@ -117,12 +142,11 @@ impl AttrBuilder {
         self.errors.extend(err);
     }
 
-    #[allow(clippy::single_match)]
     fn try_set_pair(&mut self, pair: &syn::MetaNameValue) -> Result<(), syn::Error> {
         let syn::MetaNameValue { path, lit, .. } = pair;
 
         const VALID_KEYS: &str =
-            "to_variant_with, from_variant_with, with, skip_to_variant, skip_from_variant, skip";
+            "to_variant_with, from_variant_with, with, skip_to_variant, skip_from_variant, skip, default, rename, flatten";
 
         let name = path
             .get_ident()
@@ -216,6 +240,41 @@ impl AttrBuilder {
 
                 return Ok(());
             }
+            "default" => {
+                let path = match lit {
+                    syn::Lit::Str(lit_str) => lit_str.parse::<syn::Path>()?,
+                    _ => {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            "expecting a path to a function in double quotes: #[variant(default = \"path::to::func\")]",
+                        ))
+                    }
+                };
+
+                if self.default.replace(FieldDefault::Path(path)).is_some() {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        "the argument default is already set",
+                    ));
+                }
+
+                return Ok(());
+            }
+            "rename" => {
+                let name = match lit {
+                    syn::Lit::Str(lit_str) => lit_str.value(),
+                    _ => return Err(syn::Error::new(lit.span(), "expected string literal")),
+                };
+
+                if self.rename.replace(name).is_some() {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        "the argument rename is already set",
+                    ));
+                }
+
+                return Ok(());
+            }
             _ => {}
         }
 
@@ -247,6 +306,9 @@ impl AttrBuilder {
                 skip_from_variant: self.skip_from_variant,
                 to_variant_with: self.to_variant_with,
                 from_variant_with: self.from_variant_with,
+                default: self.default,
+                rename: self.rename,
+                flatten: self.flatten,
             })
         } else {
             let first_error = self.errors.remove(0);
@@ -262,3 +324,258 @@ impl AttrBuilder {
         }
     }
 }
+
+/// Representation of enums in `Variant`s.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub(crate) enum EnumRepr {
+    /// `{ "Variant": payload }`
+    ExternallyTagged,
+    /// `{ tag: "Variant", ...fields }`. Only valid for unit and struct variants.
+    InternallyTagged { tag: String },
+    /// `{ tag: "Variant", content: payload }`
+    AdjacentlyTagged { tag: String, content: String },
+    /// `payload`, trying each variant in order when converting from `Variant`.
+    Untagged,
+    /// The discriminant as an integer. Only valid for fieldless enums.
+    Int,
+}
+
+// `#[default]` on enum variants requires Rust 1.62, above the MSRV of 1.46.
+#[allow(clippy::derivable_impls)]
+impl Default for EnumRepr {
+    fn default() -> Self {
+        EnumRepr::ExternallyTagged
+    }
+}
+
+/// Attributes on the type deriving the conversion traits.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct ContainerAttr {
+    pub enum_repr: EnumRepr,
+    pub rename_all: Option<RenameRule>,
+    pub deny_unknown_fields: bool,
+}
+
+impl ContainerAttr {
+    /// Returns the name used for an enum variant in `Variant`s.
+    pub(crate) fn variant_name(&self, var_ident: &syn::Ident) -> String {
+        let name = var_ident.to_string();
+        match self.rename_all {
+            Some(rule) => rule.apply_to_variant(&name),
+            None => name,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ContainerAttrBuilder {
+    int_repr: Option<Span>,
+    tag: Option<syn::LitStr>,
+    content: Option<syn::LitStr>,
+    untagged: Option<Span>,
+    rename_all: Option<RenameRule>,
+    deny_unknown_fields: bool,
+    errors: Vec<syn::Error>,
+}
+
+impl ContainerAttrBuilder {
+    fn extend_meta(&mut self, meta: &syn::Meta) {
+        match meta {
+            syn::Meta::NameValue(pair) => {
+                let err = self.try_set_pair(pair).err();
+                self.errors.extend(err);
+            }
+            syn::Meta::List(list) => {
+                for nested in list.nested.iter() {
+                    match nested {
+                        syn::NestedMeta::Meta(meta) => self.extend_meta(meta),
+                        _ => {
+                            self.errors
+                                .push(syn::Error::new(nested.span(), "unexpected nested meta"));
+                        }
+                    }
+                }
+            }
+            syn::Meta::Path(flag) if flag.is_ident("untagged") => {
+                if self.untagged.replace(flag.span()).is_some() {
+                    self.errors.push(syn::Error::new(
+                        flag.span(),
+                        "the argument untagged is already set",
+                    ));
+                }
+            }
+            syn::Meta::Path(flag) if flag.is_ident("deny_unknown_fields") => {
+                self.deny_unknown_fields = true;
+            }
+            syn::Meta::Path(flag) => {
+                self.errors.push(generate_error_with_docs(
+                    flag.span(),
+                    "Unknown container attribute",
+                ));
+            }
+        }
+    }
+
+    fn try_set_pair(&mut self, pair: &syn::MetaNameValue) -> Result<(), syn::Error> {
+        let syn::MetaNameValue { path, lit, .. } = pair;
+
+        const VALID_KEYS: &str = "repr, tag, content, rename_all";
+
+        let lit_str = match lit {
+            syn::Lit::Str(lit_str) => lit_str,
+            _ => return Err(syn::Error::new(lit.span(), "expected string literal")),
+        };
+
+        if path.is_ident("repr") {
+            match lit_str.value().as_str() {
+                "int" => {}
+                _ => {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        "unknown enum representation, expected one of:\n\tint",
+                    ))
+                }
+            }
+
+            if self.int_repr.replace(lit.span()).is_some() {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "the argument repr is already set",
+                ));
+            }
+
+            return Ok(());
+        }
+
+        if path.is_ident("rename_all") {
+            let rule = RenameRule::parse(&lit_str.value()).ok_or_else(|| {
+                syn::Error::new(
+                    lit.span(),
+                    format!(
+                        "unknown case convention, expected one of:\n\t{}",
+                        RenameRule::VALID_RULES
+                    ),
+                )
+            })?;
+
+            if self.rename_all.replace(rule).is_some() {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    "the argument rename_all is already set",
+                ));
+            }
+
+            return Ok(());
+        }
+
+        let (slot, name) = if path.is_ident("tag") {
+            (&mut self.tag, "tag")
+        } else if path.is_ident("content") {
+            (&mut self.content, "content")
+        } else {
+            return Err(syn::Error::new(
+                path.span(),
+                format!("unknown argument, expected one of:\n\t{}", VALID_KEYS),
+            ));
+        };
+
+        if slot.replace(lit_str.clone()).is_some() {
+            return Err(syn::Error::new(
+                lit.span(),
+                format!("the argument {} is already set", name),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn enum_repr(&self) -> Result<EnumRepr, syn::Error> {
+        let repr = match (&self.tag, &self.content) {
+            (None, Some(content)) => {
+                return Err(syn::Error::new(
+                    content.span(),
+                    "the argument content requires tag to be set",
+                ))
+            }
+            (Some(tag), Some(content)) if tag.value() == content.value() => {
+                return Err(syn::Error::new(
+                    content.span(),
+                    "the arguments tag and content must be different",
+                ))
+            }
+            (Some(tag), Some(content)) => EnumRepr::AdjacentlyTagged {
+                tag: tag.value(),
+                content: content.value(),
+            },
+            (Some(tag), None) => EnumRepr::InternallyTagged { tag: tag.value() },
+            (None, None) => {
+                return Ok(match (self.int_repr, self.untagged) {
+                    (Some(_), Some(span)) => {
+                        return Err(syn::Error::new(
+                            span,
+                            "the argument untagged conflicts with repr",
+                        ))
+                    }
+                    (Some(_), None) => EnumRepr::Int,
+                    (None, Some(_)) => EnumRepr::Untagged,
+                    (None, None) => EnumRepr::ExternallyTagged,
+                });
+            }
+        };
+
+        if let Some(span) = self.int_repr {
+            return Err(syn::Error::new(
+                span,
+                "the argument repr conflicts with tag",
+            ));
+        }
+
+        if let Some(span) = self.untagged {
+            return Err(syn::Error::new(
+                span,
+                "the argument untagged conflicts with tag",
+            ));
+        }
+
+        Ok(repr)
+    }
+
+    pub fn done(mut self) -> Result<ContainerAttr, syn::Error> {
+        if self.errors.is_empty() {
+            match self.enum_repr() {
+                Ok(enum_repr) => {
+                    return Ok(ContainerAttr {
+                        enum_repr,
+                        rename_all: self.rename_all,
+                        deny_unknown_fields: self.deny_unknown_fields,
+                    })
+                }
+                Err(err) => self.errors.push(err),
+            }
+        }
+
+        let first_error = self.errors.remove(0);
+        let errors = self
+            .errors
+            .into_iter()
+            .fold(first_error, |mut errors, error| {
+                errors.combine(error);
+                errors
+            });
+
+        Err(errors)
+    }
+}
+
+impl FromIterator<syn::Meta> for ContainerAttrBuilder {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = syn::Meta>,
+    {
+        let mut builder = ContainerAttrBuilder::default();
+        for meta in iter {
+            builder.extend_meta(&meta);
+        }
+        builder
+    }
+}
@@ -2,13 +2,15 @@ use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
 
 use syn::Ident;
 
-use super::repr::Repr;
+use super::attr::EnumRepr;
+use super::repr::{Repr, VariantRepr};
 use super::DeriveData;
 
 pub(crate) fn expand_from_variant(derive_data: DeriveData) -> Result<TokenStream2, syn::Error> {
     let DeriveData {
         ident,
         repr,
+        attr,
         mut generics,
     } = derive_data;
 
@@ -22,13 +24,42 @@ pub(crate) fn expand_from_variant(derive_data: DeriveData) -> Result<TokenStream
 
     let return_expr = match repr {
         Repr::Struct(var_repr) => {
-            let from_variant = var_repr.from_variant(&input_ident, &quote! { #ident })?;
+            let from_variant = var_repr.from_variant(&input_ident, &quote! { #ident }, &attr)?;
             quote! {
                 {
                     #from_variant
                 }
             }
         }
+        Repr::Enum(variants) if attr.enum_repr == EnumRepr::Int => {
+            let var_idents = variants.iter().map(|(var_ident, _)| var_ident);
+            let var_idents_again = variants.iter().map(|(var_ident, _)| var_ident);
+            let var_ident_string_literals = variants
+                .iter()
+                .map(|(var_ident, _)| Literal::string(&attr.variant_name(var_ident)));
+
+            quote! {
+                {
+                    let __value = i64::from_variant(#input_ident)
+                        .map_err(|__err| FVE::InvalidEnumRepr {
+                            expected: VariantEnumRepr::Int,
+                            error: Box::new(__err),
+                        })?;
+
+                    #(
+                        if __value == #ident::#var_idents as i64 {
+                            Ok(#ident::#var_idents_again)
+                        } else
+                    )*
+                    {
+                        Err(FVE::UnknownEnumVariant {
+                            variant: __value.to_string(),
+                            expected: &[#(#var_ident_string_literals),*],
+                        })
+                    }
+                }
+            }
+        }
         Repr::Enum(variants) => {
             if variants.is_empty() {
                 return Err(syn::Error::new(
@@ -41,7 +72,7 @@ pub(crate) fn expand_from_variant(derive_data: DeriveData) -> Result<TokenStream
 
             let var_ident_strings: Vec<String> = variants
                 .iter()
-                .map(|(var_ident, _)| format!("{}", var_ident))
+                .map(|(var_ident, _)| attr.variant_name(var_ident))
                 .collect();
 
             let var_ident_string_literals = var_ident_strings
@@ -54,50 +85,182 @@ pub(crate) fn expand_from_variant(derive_data: DeriveData) -> Result<TokenStream
             let var_from_variants = variants
                 .iter()
                 .map(|(var_ident, var_repr)| {
-                    var_repr.from_variant(&var_input_ident, &quote! { #ident::#var_ident })
+                    var_repr.from_variant(&var_input_ident, &quote! { #ident::#var_ident }, &attr)
                 })
                 .collect::<Result<Vec<_>, _>>()?;
 
             let var_input_ident_iter = std::iter::repeat(&var_input_ident);
 
-            quote! {
-                {
-                    let __dict = ::gdnative::core_types::Dictionary::from_variant(#input_ident)
-                        .map_err(|__err| FVE::InvalidEnumRepr {
-                            expected: VariantEnumRepr::ExternallyTagged,
-                            error: Box::new(__err),
-                        })?;
+            match &attr.enum_repr {
+                EnumRepr::InternallyTagged { tag } => {
+                    let tag_literal = Literal::string(tag);
 
-                    let __keys = __dict.keys();
-                    if __keys.len() != 1 {
-                        Err(FVE::InvalidEnumRepr {
-                            expected: VariantEnumRepr::ExternallyTagged,
-                            error: Box::new(FVE::InvalidLength {
-                                expected: 1,
-                                len: __keys.len() as usize,
-                            }),
-                        })
+                    quote! {
+                        {
+                            let __dict = ::gdnative::core_types::Dictionary::from_variant(#input_ident)
+                                .map_err(|__err| FVE::InvalidEnumRepr {
+                                    expected: VariantEnumRepr::InternallyTagged,
+                                    error: Box::new(__err),
+                                })?;
+
+                            let __tag = __dict
+                                .get(#tag_literal)
+                                .ok_or(FVE::MissingEnumTag { tag: #tag_literal })?;
+                            let __key = String::from_variant(&__tag)
+                                .map_err(|__err| FVE::InvalidEnumRepr {
+                                    expected: VariantEnumRepr::InternallyTagged,
+                                    error: Box::new(__err),
+                                })?;
+                            match __key.as_str() {
+                                #(
+                                    #ref_var_ident_string_literals => {
+                                        let #var_input_ident_iter = #input_ident;
+                                        (#var_from_variants).map_err(|err| FVE::InvalidEnumVariant {
+                                            variant: #ref_var_ident_string_literals,
+                                            error: Box::new(err),
+                                        })
+                                    },
+                                )*
+                                variant => Err(FVE::UnknownEnumVariant {
+                                    variant: variant.to_string(),
+                                    expected: &[#(#ref_var_ident_string_literals),*],
+                                }),
+                            }
+                        }
+                    }
+                }
+                EnumRepr::AdjacentlyTagged { tag, content } => {
+                    let tag_literal = Literal::string(tag);
+                    let content_literal = Literal::string(content);
+
+                    let var_from_contents = variants
+                        .iter()
+                        .zip(var_from_variants)
+                        .map(|((var_ident, var_repr), from_variant)| {
+                            if *var_repr == VariantRepr::Unit {
+                                quote! {
+                                    Ok(#ident::#var_ident)
+                                }
+                            } else {
+                                let var_ident_string_literal =
+                                    Literal::string(&attr.variant_name(var_ident));
+                                quote! {
+                                    {
+                                        let #var_input_ident = &__dict
+                                            .get(#content_literal)
+                                            .ok_or(FVE::MissingEnumContent { content: #content_literal })?;
+                                        (#from_variant).map_err(|err| FVE::InvalidEnumVariant {
+                                            variant: #var_ident_string_literal,
+                                            error: Box::new(err),
+                                        })
+                                    }
+                                }
+                            }
+                        });
+
+                    quote! {
+                        {
+                            let __dict = ::gdnative::core_types::Dictionary::from_variant(#input_ident)
+                                .map_err(|__err| FVE::InvalidEnumRepr {
+                                    expected: VariantEnumRepr::AdjacentlyTagged,
+                                    error: Box::new(__err),
+                                })?;
+
+                            let __tag = __dict
+                                .get(#tag_literal)
+                                .ok_or(FVE::MissingEnumTag { tag: #tag_literal })?;
+                            let __key = String::from_variant(&__tag)
+                                .map_err(|__err| FVE::InvalidEnumRepr {
+                                    expected: VariantEnumRepr::AdjacentlyTagged,
+                                    error: Box::new(__err),
+                                })?;
+                            match __key.as_str() {
+                                #(
+                                    #ref_var_ident_string_literals => #var_from_contents,
+                                )*
+                                variant => Err(FVE::UnknownEnumVariant {
+                                    variant: variant.to_string(),
+                                    expected: &[#(#ref_var_ident_string_literals),*],
+                                }),
+                            }
+                        }
                     }
-                    else {
-                        let __key = String::from_variant(&__keys.get(0))
-                            .map_err(|__err| FVE::InvalidEnumRepr {
-                                expected: VariantEnumRepr::ExternallyTagged,
-                                error: Box::new(__err),
-                            })?;
-                        match __key.as_str() {
+                }
+                EnumRepr::Untagged => {
+                    let var_from_variants = variants.iter().zip(var_from_variants).map(
+                        |((var_ident, var_repr), from_variant)| {
+                            if *var_repr == VariantRepr::Unit {
+                                quote! {
+                                    if #var_input_ident.is_nil() {
+                                        Ok(#ident::#var_ident)
+                                    } else {
+                                        Err(FVE::InvalidVariantType {
+                                            variant_type: #var_input_ident.get_type(),
+                                            expected: ::gdnative::core_types::VariantType::Nil,
+                                        })
+                                    }
+                                }
+                            } else {
+                                from_variant
+                            }
+                        },
+                    );
+
+                    quote! {
+                        {
+                            let #var_input_ident = #input_ident;
+                            let mut __errors = Vec::new();
                             #(
-                                #ref_var_ident_string_literals => {
-                                    let #var_input_ident_iter = &__dict.get_or_nil(&__keys.get(0));
-                                    (#var_from_variants).map_err(|err| FVE::InvalidEnumVariant {
-                                        variant: #ref_var_ident_string_literals,
-                                        error: Box::new(err),
-                                    })
-                                },
+                                match #var_from_variants {
+                                    Ok(__value) => return Ok(__value),
+                                    Err(__err) => __errors.push((#ref_var_ident_string_literals, __err)),
+                                }
                             )*
-                            variant => Err(FVE::UnknownEnumVariant {
-                                variant: variant.to_string(),
-                                expected: &[#(#ref_var_ident_string_literals),*],
-                            }),
+                            Err(FVE::NoMatchingEnumVariant { errors: __errors })
+                        }
+                    }
+                }
+                EnumRepr::ExternallyTagged | EnumRepr::Int => {
+                    quote! {
+                        {
+                            let __dict = ::gdnative::core_types::Dictionary::from_variant(#input_ident)
+                                .map_err(|__err| FVE::InvalidEnumRepr {
+                                    expected: VariantEnumRepr::ExternallyTagged,
+                                    error: Box::new(__err),
+                                })?;
+
+                            let __keys = __dict.keys();
+                            if __keys.len() != 1 {
+                                Err(FVE::InvalidEnumRepr {
+                                    expected: VariantEnumRepr::ExternallyTagged,
+                                    error: Box::new(FVE::InvalidLength {
+                                        expected: 1,
+                                        len: __keys.len() as usize,
+                                    }),
+                                })
+                            }
+                            else {
+                                let __key = String::from_variant(&__keys.get(0))
+                                    .map_err(|__err| FVE::InvalidEnumRepr {
+                                        expected: VariantEnumRepr::ExternallyTagged,
+                                        error: Box::new(__err),
+                                    })?;
+                                match __key.as_str() {
+                                    #(
+                                        #ref_var_ident_string_literals => {
+                                            let #var_input_ident_iter = &__dict.get_or_nil(&__keys.get(0));
+                                            (#var_from_variants).map_err(|err| FVE::InvalidEnumVariant {
+                                                variant: #ref_var_ident_string_literals,
+                                                error: Box::new(err),
+                                            })
+                                        },
+                                    )*
+                                    variant => Err(FVE::UnknownEnumVariant {
+                                        variant: variant.to_string(),
+                                        expected: &[#(#ref_var_ident_string_literals),*],
+                                    }),
+                                }
+                            }
                         }
                     }
                 }
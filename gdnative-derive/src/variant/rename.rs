@@ -0,0 +1,143 @@
+/// Case conventions accepted by `#[variant(rename_all = "...")]`.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(crate) enum RenameRule {
+    Lower,
+    Upper,
+    Pascal,
+    Camel,
+    Snake,
+    ScreamingSnake,
+    Kebab,
+    ScreamingKebab,
+}
+
+impl RenameRule {
+    pub(crate) const VALID_RULES: &'static str = "lowercase, UPPERCASE, PascalCase, camelCase, snake_case, SCREAMING_SNAKE_CASE, kebab-case, SCREAMING-KEBAB-CASE";
+
+    pub(crate) fn parse(rule: &str) -> Option<Self> {
+        let rule = match rule {
+            "lowercase" => RenameRule::Lower,
+            "UPPERCASE" => RenameRule::Upper,
+            "PascalCase" => RenameRule::Pascal,
+            "camelCase" => RenameRule::Camel,
+            "snake_case" => RenameRule::Snake,
+            "SCREAMING_SNAKE_CASE" => RenameRule::ScreamingSnake,
+            "kebab-case" => RenameRule::Kebab,
+            "SCREAMING-KEBAB-CASE" => RenameRule::ScreamingKebab,
+            _ => return None,
+        };
+
+        Some(rule)
+    }
+
+    /// Applies the rule to a field name, which is expected to be in snake_case.
+    pub(crate) fn apply_to_field(self, field: &str) -> String {
+        match self {
+            RenameRule::Lower | RenameRule::Snake => field.to_owned(),
+            RenameRule::Upper | RenameRule::ScreamingSnake => field.to_ascii_uppercase(),
+            RenameRule::Pascal => {
+                let mut pascal = String::with_capacity(field.len());
+                let mut capitalize = true;
+                for c in field.chars() {
+                    if c == '_' {
+                        capitalize = true;
+                    } else if capitalize {
+                        pascal.push(c.to_ascii_uppercase());
+                        capitalize = false;
+                    } else {
+                        pascal.push(c);
+                    }
+                }
+                pascal
+            }
+            RenameRule::Camel => {
+                let pascal = RenameRule::Pascal.apply_to_field(field);
+                lowercase_first(&pascal)
+            }
+            RenameRule::Kebab => field.replace('_', "-"),
+            RenameRule::ScreamingKebab => RenameRule::ScreamingSnake
+                .apply_to_field(field)
+                .replace('_', "-"),
+        }
+    }
+
+    /// Applies the rule to an enum variant name, which is expected to be in Pascal.
+    pub(crate) fn apply_to_variant(self, variant: &str) -> String {
+        match self {
+            RenameRule::Pascal => variant.to_owned(),
+            RenameRule::Lower => variant.to_ascii_lowercase(),
+            RenameRule::Upper => variant.to_ascii_uppercase(),
+            RenameRule::Camel => lowercase_first(variant),
+            RenameRule::Snake => {
+                let mut snake = String::with_capacity(variant.len());
+                for (i, c) in variant.char_indices() {
+                    if i > 0 && c.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(c.to_ascii_lowercase());
+                }
+                snake
+            }
+            RenameRule::ScreamingSnake => RenameRule::Snake
+                .apply_to_variant(variant)
+                .to_ascii_uppercase(),
+            RenameRule::Kebab => RenameRule::Snake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+            RenameRule::ScreamingKebab => RenameRule::ScreamingSnake
+                .apply_to_variant(variant)
+                .replace('_', "-"),
+        }
+    }
+}
+
+fn lowercase_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rename_fields() {
+        let cases = [
+            ("lowercase", "max_health"),
+            ("UPPERCASE", "MAX_HEALTH"),
+            ("PascalCase", "MaxHealth"),
+            ("camelCase", "maxHealth"),
+            ("snake_case", "max_health"),
+            ("SCREAMING_SNAKE_CASE", "MAX_HEALTH"),
+            ("kebab-case", "max-health"),
+            ("SCREAMING-KEBAB-CASE", "MAX-HEALTH"),
+        ];
+
+        for &(rule, expected) in cases.iter() {
+            let rule = RenameRule::parse(rule).expect("rule should be valid");
+            assert_eq!(expected, rule.apply_to_field("max_health"), "{:?}", rule);
+        }
+    }
+
+    #[test]
+    fn rename_variants() {
+        let cases = [
+            ("lowercase", "fastattack"),
+            ("UPPERCASE", "FASTATTACK"),
+            ("PascalCase", "FastAttack"),
+            ("camelCase", "fastAttack"),
+            ("snake_case", "fast_attack"),
+            ("SCREAMING_SNAKE_CASE", "FAST_ATTACK"),
+            ("kebab-case", "fast-attack"),
+            ("SCREAMING-KEBAB-CASE", "FAST-ATTACK"),
+        ];
+
+        for &(rule, expected) in cases.iter() {
+            let rule = RenameRule::parse(rule).expect("rule should be valid");
+            assert_eq!(expected, rule.apply_to_variant("FastAttack"), "{:?}", rule);
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use syn::spanned::Spanned;
 use syn::{Fields, Ident, Type};
 
-use super::attr::{Attr, AttrBuilder};
+use super::attr::{Attr, AttrBuilder, ContainerAttr, EnumRepr, FieldDefault};
+use super::rename::RenameRule;
 use super::ToVariantTrait;
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -22,6 +24,8 @@ pub(crate) struct Field {
     pub ident: Ident,
     pub ty: Type,
     pub attr: Attr,
+    /// Key of the field in `Dictionary` representations.
+    pub name: String,
 }
 
 fn improve_meta_error(err: syn::Error) -> syn::Error {
@@ -49,8 +53,91 @@ where
         .done()
 }
 
+fn insert_fields(fields: &[Field], trait_kind: ToVariantTrait) -> TokenStream2 {
+    let inserts = fields
+        .iter()
+        .filter(|f| !f.attr.skip_to_variant)
+        .map(|f| {
+            let expr = f.to_variant(trait_kind);
+            let name_string_literal = Literal::string(&f.name);
+            if f.attr.flatten {
+                // Values that turn out not to be dictionaries are kept under the field name.
+                quote! {
+                    {
+                        let __value = #expr;
+                        match __value.try_to_dictionary() {
+                            Some(__fields) => {
+                                for (__key, __value) in __fields.iter() {
+                                    __dict.insert(&__key, &__value);
+                                }
+                            }
+                            None => {
+                                let __key = ::gdnative::core_types::GodotString::from(#name_string_literal).to_variant();
+                                __dict.insert(&__key, &__value);
+                            }
+                        }
+                    }
+                }
+            } else {
+                quote! {
+                    {
+                        let __key = ::gdnative::core_types::GodotString::from(#name_string_literal).to_variant();
+                        __dict.insert(&__key, &#expr);
+                    }
+                }
+            }
+        });
+
+    quote! {
+        #( #inserts )*
+    }
+}
+
+/// Returns `false` for types that are known not to convert to a `Dictionary`, which can't be
+/// flattened. Other types are checked when they are converted.
+fn is_dictionary_like(ty: &Type) -> bool {
+    const NON_DICTIONARY_TYPES: &[&str] = &[
+        "bool",
+        "i8",
+        "i16",
+        "i32",
+        "i64",
+        "isize",
+        "u8",
+        "u16",
+        "u32",
+        "u64",
+        "usize",
+        "f32",
+        "f64",
+        "char",
+        "str",
+        "String",
+        "GodotString",
+        "Vec",
+        "VariantArray",
+    ];
+
+    match ty {
+        Type::Path(path) if path.qself.is_none() => match path.path.segments.last() {
+            Some(segment) => !NON_DICTIONARY_TYPES
+                .iter()
+                .any(|name| segment.ident == name),
+            None => true,
+        },
+        Type::Reference(reference) => is_dictionary_like(&reference.elem),
+        Type::Paren(paren) => is_dictionary_like(&paren.elem),
+        Type::Group(group) => is_dictionary_like(&group.elem),
+        Type::Array(_) | Type::Slice(_) | Type::Tuple(_) => false,
+        _ => true,
+    }
+}
+
 impl VariantRepr {
-    pub(crate) fn repr_for(fields: &Fields) -> Result<Self, syn::Error> {
+    pub(crate) fn repr_for(
+        fields: &Fields,
+        rename_all: Option<RenameRule>,
+    ) -> Result<Self, syn::Error> {
         let this = match fields {
             Fields::Named(fields) => VariantRepr::Struct(
                 fields
@@ -60,7 +147,32 @@ impl VariantRepr {
                         let ident = f.ident.clone().expect("fields should be named");
                         let ty = f.ty.clone();
                         let attr = parse_attrs(&f.attrs)?;
-                        Ok(Field { ident, ty, attr })
+                        if attr.flatten && (attr.rename.is_some() || attr.default.is_some()) {
+                            return Err(syn::Error::new(
+                                ident.span(),
+                                "#[variant(flatten)] cannot be used with rename or default",
+                            ));
+                        }
+                        if attr.flatten
+                            && attr.to_variant_with.is_none()
+                            && !is_dictionary_like(&ty)
+                        {
+                            return Err(syn::Error::new(
+                                ty.span(),
+                                "#[variant(flatten)] requires a type that converts to a Dictionary",
+                            ));
+                        }
+                        let name = match (&attr.rename, rename_all) {
+                            (Some(rename), _) => rename.clone(),
+                            (None, Some(rule)) => rule.apply_to_field(&ident.to_string()),
+                            (None, None) => format!("{}", ident),
+                        };
+                        Ok(Field {
+                            ident,
+                            ty,
+                            attr,
+                            name,
+                        })
                     })
                     .collect::<Result<Vec<_>, syn::Error>>()?,
             ),
@@ -73,7 +185,19 @@ impl VariantRepr {
                         let ident = Ident::new(&format!("__field_{}", n), Span::call_site());
                         let ty = f.ty.clone();
                         let attr = parse_attrs(&f.attrs)?;
-                        Ok(Field { ident, ty, attr })
+                        if attr.flatten || attr.rename.is_some() || attr.default.is_some() {
+                            return Err(syn::Error::new(
+                                f.span(),
+                                "rename, default and flatten are only valid for named fields",
+                            ));
+                        }
+                        let name = format!("{}", ident);
+                        Ok(Field {
+                            ident,
+                            ty,
+                            attr,
+                            name,
+                        })
                     })
                     .collect::<Result<_, syn::Error>>()?,
             ),
@@ -141,26 +265,12 @@ impl VariantRepr {
                 }
             }
             VariantRepr::Struct(fields) => {
-                let fields: Vec<&Field> =
-                    fields.iter().filter(|f| !f.attr.skip_to_variant).collect();
-
-                let name_strings: Vec<String> =
-                    fields.iter().map(|f| format!("{}", &f.ident)).collect();
-
-                let name_string_literals =
-                    name_strings.iter().map(|string| Literal::string(&string));
-
-                let exprs = fields.iter().map(|f| f.to_variant(trait_kind));
+                let inserts = insert_fields(fields, trait_kind);
 
                 quote! {
                     {
                         let __dict = ::gdnative::core_types::Dictionary::new();
-                        #(
-                            {
-                                let __key = ::gdnative::core_types::GodotString::from(#name_string_literals).to_variant();
-                                __dict.insert(&__key, &#exprs);
-                            }
-                        )*
+                        #inserts
                         __dict.into_shared().to_variant()
                     }
                 }
@@ -170,10 +280,27 @@ impl VariantRepr {
         Ok(tokens)
     }
 
+    /// Inserts the fields of a unit or struct variant into an existing `__dict`, in the same
+    /// way as `to_variant` does for its own dictionary.
+    pub(crate) fn insert_into_dict(
+        &self,
+        trait_kind: ToVariantTrait,
+    ) -> Result<TokenStream2, syn::Error> {
+        match self {
+            VariantRepr::Unit => Ok(quote! {}),
+            VariantRepr::Struct(fields) => Ok(insert_fields(fields, trait_kind)),
+            VariantRepr::Tuple(_) => Err(syn::Error::new(
+                Span::call_site(),
+                "fields of tuple variants can't be inserted into a Dictionary",
+            )),
+        }
+    }
+
     pub(crate) fn from_variant(
         &self,
         variant: &Ident,
         ctor: &TokenStream2,
+        container_attr: &ContainerAttr,
     ) -> Result<TokenStream2, syn::Error> {
         let tokens = match self {
             VariantRepr::Unit => {
@@ -259,31 +386,88 @@ impl VariantRepr {
                 }
             }
             VariantRepr::Struct(fields) => {
-                let skipped_fields: Vec<&Field> =
-                    fields.iter().filter(|f| f.attr.skip_from_variant).collect();
+                let field_exprs = fields.iter().map(|f| {
+                    let ident = &f.ident;
+                    let name_string_literal = Literal::string(&f.name);
+                    let default = f.attr.default.as_ref().map(|default| match default {
+                        FieldDefault::Default => quote!(::std::default::Default::default()),
+                        FieldDefault::Path(path) => quote!(#path()),
+                    });
 
-                let non_skipped_fields: Vec<&Field> = fields
-                    .iter()
-                    .filter(|f| !f.attr.skip_from_variant)
-                    .collect();
+                    if f.attr.skip_from_variant {
+                        let default =
+                            default.unwrap_or_else(|| quote!(::std::default::Default::default()));
+                        return quote! {
+                            let #ident = #default;
+                        };
+                    }
 
-                let skipped_idents = skipped_fields.iter().map(|f| &f.ident);
-                let non_skipped_idents: Vec<&Ident> =
-                    non_skipped_fields.iter().map(|f| &f.ident).collect();
-                let ctor_idents = fields.iter().map(|f| &f.ident);
+                    let map_err = quote! {
+                        .map_err(|err| FVE::InvalidField {
+                            field_name: #name_string_literal,
+                            error: Box::new(err),
+                        })?
+                    };
 
-                let name_strings: Vec<String> = non_skipped_idents
-                    .iter()
-                    .map(|ident| format!("{}", ident))
-                    .collect();
+                    if f.attr.flatten {
+                        let expr = f.from_variant(&quote!(#variant));
+                        return quote! {
+                            let #ident = #expr #map_err;
+                        };
+                    }
 
-                let name_string_literals =
-                    name_strings.iter().map(|string| Literal::string(&string));
+                    let key = quote! {
+                        ::gdnative::core_types::GodotString::from(#name_string_literal).to_variant()
+                    };
 
-                let expr_variant = &quote!(&__dict.get_or_nil(&__key));
-                let exprs = non_skipped_fields
-                    .iter()
-                    .map(|f| f.from_variant(expr_variant));
+                    match default {
+                        Some(default) => {
+                            let expr = f.from_variant(&quote!(&__value));
+                            quote! {
+                                let #ident = match __dict.get(&#key) {
+                                    Some(__value) => #expr #map_err,
+                                    None => #default,
+                                };
+                            }
+                        }
+                        None => {
+                            let expr = f.from_variant(&quote!(&__dict.get_or_nil(&#key)));
+                            quote! {
+                                let #ident = #expr #map_err;
+                            }
+                        }
+                    }
+                });
+
+                let deny_unknown_fields = if container_attr.deny_unknown_fields {
+                    let tag = match &container_attr.enum_repr {
+                        EnumRepr::InternallyTagged { tag } => Some(tag),
+                        _ => None,
+                    };
+                    let expected_literals = fields
+                        .iter()
+                        .filter(|f| !f.attr.skip_from_variant)
+                        .map(|f| &f.name)
+                        .chain(tag)
+                        .map(|name| Literal::string(name));
+
+                    quote! {
+                        const __EXPECTED: &[&str] = &[#( #expected_literals ),*];
+                        for (__key, _) in __dict.iter() {
+                            let __key = __key.to_string();
+                            if !__EXPECTED.contains(&__key.as_str()) {
+                                return Err(FVE::UnknownField {
+                                    field_name: __key,
+                                    expected: __EXPECTED,
+                                });
+                            }
+                        }
+                    }
+                } else {
+                    quote! {}
+                };
+
+                let ctor_idents = fields.iter().map(|f| &f.ident);
 
                 quote! {
                     {
@@ -293,18 +477,8 @@ impl VariantRepr {
                                 error: Box::new(__err),
                             })
                             .and_then(|__dict| {
-                                #(
-                                    let __field_name = #name_string_literals;
-                                    let __key = ::gdnative::core_types::GodotString::from(__field_name).to_variant();
-                                    let #non_skipped_idents = #exprs
-                                        .map_err(|err| FVE::InvalidField {
-                                            field_name: __field_name,
-                                            error: Box::new(err),
-                                        })?;
-                                )*
-                                #(
-                                    let #skipped_idents = ::std::default::Default::default();
-                                )*
+                                #deny_unknown_fields
+                                #( #field_exprs )*
                                 Ok(#ctor { #( #ctor_idents ),* })
                             })
                     }
@@ -1,6 +1,7 @@
 use proc_macro2::{Literal, TokenStream as TokenStream2};
 
-use super::repr::Repr;
+use super::attr::EnumRepr;
+use super::repr::{Repr, VariantRepr};
 use super::{DeriveData, ToVariantTrait};
 
 pub(crate) fn expand_to_variant(
@@ -10,6 +11,7 @@ pub(crate) fn expand_to_variant(
     let DeriveData {
         ident,
         repr,
+        attr,
         mut generics,
     } = derive_data;
 
@@ -38,23 +40,81 @@ pub(crate) fn expand_to_variant(
                 quote! {
                     unreachable!("this is an uninhabitable enum");
                 }
+            } else if attr.enum_repr == EnumRepr::Int {
+                let var_idents = variants.iter().map(|(var_ident, _)| var_ident);
+                quote! {
+                    match #to_variant_receiver {
+                        #(
+                            #ident::#var_idents => ::gdnative::core_types::Variant::from_i64(
+                                #ident::#var_idents as i64
+                            ),
+                        )*
+                    }
+                }
             } else {
                 let match_arms = variants
                     .iter()
                     .map(|(var_ident, var_repr)| {
                         let destructure_pattern = var_repr.destructure_pattern();
                         let to_variant = var_repr.to_variant(trait_kind)?;
-                        let var_ident_string = format!("{}", var_ident);
+                        let var_ident_string = attr.variant_name(var_ident);
                         let var_ident_string_literal = Literal::string(&var_ident_string);
+                        let body = match &attr.enum_repr {
+                            EnumRepr::InternallyTagged { tag } => {
+                                let tag_literal = Literal::string(tag);
+                                let insert_fields = var_repr.insert_into_dict(trait_kind)?;
+                                // The tag is inserted last, so flattened fields can't overwrite it.
+                                quote! {
+                                    let __dict = ::gdnative::core_types::Dictionary::new();
+                                    #insert_fields
+                                    let __key = ::gdnative::core_types::ToVariant::to_variant(&::gdnative::core_types::GodotString::from(#tag_literal));
+                                    let __tag = ::gdnative::core_types::ToVariant::to_variant(&::gdnative::core_types::GodotString::from(#var_ident_string_literal));
+                                    __dict.insert(&__key, &__tag);
+                                    ::gdnative::core_types::ToVariant::to_variant(&__dict.into_shared())
+                                }
+                            }
+                            EnumRepr::AdjacentlyTagged { tag, content } => {
+                                let tag_literal = Literal::string(tag);
+                                let content_literal = Literal::string(content);
+                                let insert_content = if *var_repr == VariantRepr::Unit {
+                                    quote! {}
+                                } else {
+                                    quote! {
+                                        let __key = ::gdnative::core_types::ToVariant::to_variant(&::gdnative::core_types::GodotString::from(#content_literal));
+                                        let __value = #to_variant;
+                                        __dict.insert(&__key, &__value);
+                                    }
+                                };
+                                quote! {
+                                    let __dict = ::gdnative::core_types::Dictionary::new();
+                                    let __key = ::gdnative::core_types::ToVariant::to_variant(&::gdnative::core_types::GodotString::from(#tag_literal));
+                                    let __tag = ::gdnative::core_types::ToVariant::to_variant(&::gdnative::core_types::GodotString::from(#var_ident_string_literal));
+                                    __dict.insert(&__key, &__tag);
+                                    #insert_content
+                                    ::gdnative::core_types::ToVariant::to_variant(&__dict.into_shared())
+                                }
+                            }
+                            EnumRepr::Untagged if *var_repr == VariantRepr::Unit => {
+                                quote! {
+                                    ::gdnative::core_types::Variant::new()
+                                }
+                            }
+                            EnumRepr::Untagged => to_variant,
+                            EnumRepr::ExternallyTagged | EnumRepr::Int => {
+                                quote! {
+                                    let __dict = ::gdnative::core_types::Dictionary::new();
+                                    let __key = ::gdnative::core_types::ToVariant::to_variant(
+                                        &::gdnative::core_types::GodotString::from(#var_ident_string_literal)
+                                    );
+                                    let __value = #to_variant;
+                                    __dict.insert(&__key, &__value);
+                                    ::gdnative::core_types::ToVariant::to_variant(&__dict.into_shared())
+                                }
+                            }
+                        };
                         let tokens = quote! {
                             #ident::#var_ident #destructure_pattern => {
-                                let __dict = ::gdnative::core_types::Dictionary::new();
-                                let __key = ::gdnative::core_types::ToVariant::to_variant(
-                                    &::gdnative::core_types::GodotString::from(#var_ident_string_literal)
-                                );
-                                let __value = #to_variant;
-                                __dict.insert(&__key, &__value);
-                                ::gdnative::core_types::ToVariant::to_variant(&__dict.into_shared())
+                                #body
                             }
                         };
                         Ok(tokens)
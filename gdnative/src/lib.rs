@@ -45,6 +45,39 @@
 //!
 //! *Enabled* by default. Includes the crates.io version of the bindings in the `api` module.
 //!
+//! ### `no_global_type_tags`
+//!
+//! *Disabled* by default. Skips registering the global type tags of the classes in `api`, so
+//! casts to engine classes always call `Object::is_class`. Only useful to compare the
+//! performance of casts.
+//!
+//! ### `inventory`
+//!
+//! *Disabled* by default. Makes `#[methods]` collect all classes at link time, so they can be
+//! registered with `godot_init!(auto)` instead of listing every type in the init callback.
+//! Relies on the [`inventory`](https://docs.rs/inventory) crate, which isn't supported on all
+//! platforms. Depending on `inventory` directly is not necessary.
+//!
+//! ### `backtrace`
+//!
+//! *Disabled* by default. Captures backtraces of panics in exported callbacks, so they can be
+//! included in reports. See the `panic` module. Current versions of the `backtrace` crate
+//! require a newer Rust version than the rest of the crate.
+//!
+//! ### `instance_tracking`
+//!
+//! *Disabled* by default. Records the construction and destruction of every script instance,
+//! so live instances can be listed with `Instance::live_count` and `Instance::live_instances`.
+//! Instances that are still alive when the library is unloaded are reported as leaks.
+//!
+//! ### `serde`
+//!
+//! *Disabled* by default. Implements `Serialize` and `Deserialize` from
+//! [`serde`](https://docs.rs/serde) for `Variant`, the collection and math types in
+//! `core_types`, and adds `core_types::variant_serde` to convert any serde type to and from
+//! `Variant`. Current versions of serde require a newer Rust version than the rest of the
+//! crate.
+//!
 //! [thread-safety]: https://docs.godotengine.org/en/stable/tutorials/threads/thread_safe_apis.html
 //! [custom-version]: https://github.com/godot-rust/godot-rust/#other-versions-or-custom-builds
 //!
@@ -55,11 +88,16 @@
 //             re-exported. Manually re-exporting the public items works around that.
 #[doc(inline)]
 pub use gdnative_core::{
-    core_types, godot_dbg, godot_error, godot_gdnative_init, godot_gdnative_terminate, godot_init,
-    godot_nativescript_init, godot_print, godot_warn, godot_wrap_method, nativescript, object,
-    ref_kind, thread_access, GodotObject, GodotResult, NewRef, Null, Ref, TRef,
+    core_types, godot_dbg, godot_error, godot_gdnative_init, godot_gdnative_terminate, godot_print,
+    godot_warn, godot_wrap_method, nativescript, object, panic, ref_kind, thread_access,
+    GodotObject, GodotResult, NewRef, Null, Ref, TRef,
 };
 
+#[doc(hidden)]
+pub use gdnative_core::godot_nativescript_init as __core_godot_nativescript_init;
+
+mod macros;
+
 #[doc(hidden)]
 pub use gdnative_core::*;
 
@@ -73,3 +111,14 @@ pub mod prelude;
 #[cfg(feature = "bindings")]
 /// Bindings for the Godot Class API.
 pub use gdnative_bindings as api;
+
+/// Registers global type tags for the classes in the bindings, if enabled. Called by
+/// `godot_nativescript_init` through the hook of `gdnative_core::godot_nativescript_init`.
+///
+/// This is intended to be an internal interface.
+#[doc(hidden)]
+#[inline]
+pub fn register_global_type_tags() {
+    #[cfg(all(feature = "bindings", not(feature = "no_global_type_tags")))]
+    gdnative_bindings::register_global_type_tags();
+}
@@ -0,0 +1,59 @@
+/// Declare the API endpoint to initialize nativescript classes on startup.
+///
+/// By default this declares an extern function named `godot_nativescript_init`.
+/// This can be overridden, for example:
+///
+/// ```ignore
+/// // Declares an extern function named custom_nativescript_init instead of
+/// // godot_nativescript_init.
+/// godot_nativescript_init!(my_registration_callback as custom_nativescript_init);
+/// ```
+///
+/// Overriding the default entry point names can be useful if several gdnative
+/// libraries are linked statically  to avoid name clashes.
+///
+/// With the `inventory` feature, `auto` can be used in place of the callback to register
+/// all classes with a `#[methods]` impl block automatically:
+///
+/// ```ignore
+/// godot_nativescript_init!(auto);
+/// ```
+///
+/// In addition to `gdnative_core::godot_nativescript_init`, this registers the global type
+/// tags of the classes in `api`, if the `bindings` feature is enabled.
+#[macro_export]
+macro_rules! godot_nativescript_init {
+    ($($args:tt)*) => {
+        $crate::__core_godot_nativescript_init!(
+            @hook { $crate::register_global_type_tags(); }
+            $($args)*
+        );
+    };
+}
+
+/// Declare all the API endpoints necessary to initialize a NativeScript library.
+///
+/// `godot_init!(init)` is a shorthand for:
+///
+/// ```ignore
+/// godot_gdnative_init!();
+/// godot_nativescript_init!(init);
+/// godot_gdnative_terminate!();
+/// ```
+///
+/// This declares three extern functions, named `godot_gdnative_init`,
+/// `godot_nativescript_init`, and `godot_gdnative_terminate`. If you need different prefixes
+/// to avoid name clashes when multiple GDNative libraries are linked statically, please use
+/// the respective macros directly.
+///
+/// With the `inventory` feature, `godot_init!(auto)` registers all classes with a
+/// `#[methods]` impl block without an init callback. See
+/// [`InitHandle::add_auto_registered_classes`](nativescript/init/struct.InitHandle.html#method.add_auto_registered_classes).
+#[macro_export]
+macro_rules! godot_init {
+    ($callback:tt) => {
+        $crate::godot_gdnative_init!();
+        $crate::godot_nativescript_init!($callback);
+        $crate::godot_gdnative_terminate!();
+    };
+}
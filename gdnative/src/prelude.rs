@@ -1,8 +1,8 @@
 pub use gdnative_core::core_types::{
     self, error::GodotError, Aabb, Basis, ByteArray, Color, ColorArray, Dictionary, Float32Array,
     GodotString, Int32Array, NodePath, Plane, Quat, Rect2, Rid, StringArray, StringName, Transform,
-    Transform2D, TypedArray, Variant, VariantArray, VariantDispatch, VariantOperator, VariantType,
-    Vector2, Vector2Array, Vector3, Vector3Array,
+    Transform2D, TypedArray, TypedDictionary, TypedVariantArray, Variant, VariantArray,
+    VariantDispatch, VariantOperator, VariantType, Vector2, Vector2Array, Vector3, Vector3Array,
 };
 pub use gdnative_core::core_types::{
     FromVariant, FromVariantError, OwnedToVariant, ToVariant, ToVariantEq,
@@ -17,15 +17,19 @@ pub use gdnative_core::NewRef;
 
 pub use gdnative_core::nativescript::{
     self,
-    class::{Instance, RefInstance},
+    class::{Instance, ParentScript, RefInstance},
     init::{ClassBuilder, InitHandle, Method, MethodBuilder, Signal, SignalArgument},
-    user_data::{self, Aether, ArcData, LocalCellData, MutexData, RwLockData},
-    ExportInfo, NativeClass, NativeClassMethods, PropertyUsage,
+    signal::connect_fn,
+    task::{spawn_local, AwaitSignal},
+    user_data::{self, Aether, ArcData, AtomicCellData, LocalCellData, MutexData, RwLockData},
+    DynamicProperties, ExportInfo, InstanceBinding, NativeClass, NativeClassMethods, PropertyInfo,
+    PropertyUsage,
 };
 
+pub use crate::{godot_init, godot_nativescript_init};
 pub use gdnative_core::{
-    godot_dbg, godot_error, godot_gdnative_init, godot_gdnative_terminate, godot_init,
-    godot_nativescript_init, godot_print, godot_site, godot_warn,
+    godot_dbg, godot_error, godot_gdnative_init, godot_gdnative_terminate, godot_print, godot_site,
+    godot_warn,
 };
 
 pub use gdnative_derive::*;
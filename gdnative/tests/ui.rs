@@ -25,6 +25,7 @@ fn ui_tests() {
     t.compile_fail("tests/ui/to_variant_fail_05.rs");
     t.compile_fail("tests/ui/to_variant_fail_06.rs");
     t.compile_fail("tests/ui/to_variant_fail_07.rs");
+    t.compile_fail("tests/ui/to_variant_fail_08.rs");
 
     // FromVariant
     t.compile_fail("tests/ui/from_variant_fail_01.rs");
@@ -0,0 +1,9 @@
+use gdnative::prelude::*;
+
+#[derive(ToVariant)]
+pub struct Foo {
+    #[variant(flatten)]
+    bar: i64,
+}
+
+fn main() {}
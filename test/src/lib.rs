@@ -2,12 +2,23 @@
 
 use gdnative::prelude::*;
 
+mod test_async;
+mod test_atomic_cell_data;
 mod test_constructor;
 mod test_derive;
 mod test_free_ub;
+mod test_inherit_script;
+mod test_instance_binding;
+#[cfg(feature = "instance_tracking")]
+mod test_instance_tracking;
+mod test_lock_diagnostics;
 mod test_map_owned;
 mod test_register;
 mod test_return_leak;
+mod test_rpc;
+#[cfg(feature = "serde_tests")]
+mod test_serde;
+mod test_type_tag_cast;
 mod test_vararray_return;
 mod test_variant_call_args;
 mod test_variant_ops;
@@ -26,6 +37,8 @@ pub extern "C" fn run_tests(
     status &= gdnative::core_types::test_array();
     status &= gdnative::core_types::test_array_debug();
     // status &= gdnative::test_array_clone_clear();
+    status &= gdnative::core_types::typed_dictionary::test_typed_dictionary();
+    status &= gdnative::core_types::typed_variant_array::test_typed_variant_array();
 
     status &= gdnative::core_types::test_variant_nil();
     status &= gdnative::core_types::test_variant_i64();
@@ -39,6 +52,8 @@ pub extern "C" fn run_tests(
     status &= gdnative::core_types::test_variant_result();
     status &= gdnative::core_types::test_to_variant_iter();
     status &= gdnative::core_types::test_variant_tuple();
+    status &= gdnative::core_types::test_variant_collections();
+    status &= gdnative::core_types::test_variant_wrappers();
     status &= gdnative::core_types::test_variant_dispatch();
 
     status &= gdnative::core_types::test_byte_array_access();
@@ -60,12 +75,27 @@ pub extern "C" fn run_tests(
     status &= test_rust_class_construction();
     status &= test_from_instance_id();
 
+    status &= test_async::run_tests();
+    status &= test_atomic_cell_data::run_tests();
     status &= test_derive::run_tests();
     status &= test_free_ub::run_tests();
+    status &= test_inherit_script::run_tests();
+    status &= test_instance_binding::run_tests();
+    #[cfg(feature = "instance_tracking")]
+    {
+        status &= test_instance_tracking::run_tests();
+    }
+    status &= test_lock_diagnostics::run_tests();
     status &= test_constructor::run_tests();
     status &= test_map_owned::run_tests();
     status &= test_register::run_tests();
+    #[cfg(feature = "serde_tests")]
+    {
+        status &= test_serde::run_tests();
+    }
     status &= test_return_leak::run_tests();
+    status &= test_rpc::run_tests();
+    status &= test_type_tag_cast::run_tests();
     status &= test_variant_call_args::run_tests();
     status &= test_variant_ops::run_tests();
     status &= test_vararray_return::run_tests();
@@ -251,19 +281,40 @@ fn test_from_instance_id() -> bool {
     ok
 }
 
+/// Registers a class with a `#[methods]` impl block. With the `inventory` feature, these
+/// classes are registered by `add_auto_registered_classes` at the end of `init` instead.
+fn add_derived_class<C: NativeClassMethods>(handle: InitHandle) {
+    #[cfg(not(feature = "inventory"))]
+    handle.add_class::<C>();
+    #[cfg(feature = "inventory")]
+    let _ = handle;
+}
+
 fn init(handle: InitHandle) {
     handle.add_class::<Foo>();
     handle.add_class::<OptionalArgs>();
 
+    test_async::register(handle);
+    test_atomic_cell_data::register(handle);
     test_derive::register(handle);
     test_free_ub::register(handle);
+    test_inherit_script::register(handle);
+    test_instance_binding::register(handle);
+    #[cfg(feature = "instance_tracking")]
+    test_instance_tracking::register(handle);
+    test_lock_diagnostics::register(handle);
     test_constructor::register(handle);
     test_map_owned::register(handle);
     test_register::register(handle);
     test_return_leak::register(handle);
+    test_rpc::register(handle);
+    test_type_tag_cast::register(handle);
     test_variant_call_args::register(handle);
     test_variant_ops::register(handle);
     test_vararray_return::register(handle);
+
+    #[cfg(feature = "inventory")]
+    handle.add_auto_registered_classes();
 }
 
 gdnative::godot_init!(init);
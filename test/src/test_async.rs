@@ -0,0 +1,179 @@
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use gdnative::api::{Engine, SceneTree};
+use gdnative::prelude::*;
+
+pub(crate) fn run_tests() -> bool {
+    let mut status = true;
+
+    status &= test_await_signal();
+    status &= test_spawn_local();
+    status &= test_task_cancellation();
+
+    status
+}
+
+pub(crate) fn register(handle: InitHandle) {
+    crate::add_derived_class::<AsyncEmitter>(handle);
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct AsyncEmitter;
+
+#[methods]
+impl AsyncEmitter {
+    fn new(_owner: &Reference) -> Self {
+        AsyncEmitter
+    }
+
+    #[signal]
+    fn done(value: i64);
+}
+
+fn test_await_signal() -> bool {
+    println!(" -- test_await_signal");
+
+    let ok = std::panic::catch_unwind(|| {
+        let emitter = Instance::<AsyncEmitter, _>::new().into_shared();
+        let emitter = unsafe { emitter.base().assume_safe() };
+
+        let mut done = emitter.signal::<(i64,)>("done");
+        let mut unit = emitter.signal::<()>("done");
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(Poll::Pending, Pin::new(&mut done).poll(&mut cx));
+
+        AsyncEmitter::emit_done(&emitter, 42);
+        assert_eq!(Poll::Ready((42,)), Pin::new(&mut done).poll(&mut cx));
+        assert_eq!(Poll::Ready(()), Pin::new(&mut unit).poll(&mut cx));
+
+        // Connections made by `signal` only receive one emission.
+        let mut again = emitter.signal::<(i64,)>("done");
+        AsyncEmitter::emit_done(&emitter, 1);
+        AsyncEmitter::emit_done(&emitter, 2);
+        assert_eq!(Poll::Ready((1,)), Pin::new(&mut again).poll(&mut cx));
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_await_signal failed");
+    }
+
+    ok
+}
+
+fn test_spawn_local() -> bool {
+    println!(" -- test_spawn_local");
+
+    let ok = std::panic::catch_unwind(|| {
+        let tree = scene_tree();
+        let owner = Reference::new();
+        let emitter = Instance::<AsyncEmitter, _>::new().into_shared();
+        let emitter = unsafe { emitter.base().assume_safe() };
+
+        let result = Rc::new(Cell::new(0));
+        let done = emitter.signal::<(i64,)>("done");
+        let task_result = result.clone();
+        spawn_local(&*owner, async move {
+            let (value,) = done.await;
+            task_result.set(value);
+        });
+
+        // Tasks are first polled on the next idle frame, by the driver connected to the
+        // `SceneTree`.
+        assert_eq!(2, Rc::strong_count(&result));
+        idle_frame(&tree);
+        assert_eq!(0, result.get());
+
+        AsyncEmitter::emit_done(&emitter, 42);
+        assert_eq!(0, result.get());
+
+        idle_frame(&tree);
+        assert_eq!(42, result.get());
+
+        // The task is dropped once it completes.
+        assert_eq!(1, Rc::strong_count(&result));
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_spawn_local failed");
+    }
+
+    ok
+}
+
+fn test_task_cancellation() -> bool {
+    println!(" -- test_task_cancellation");
+
+    let ok = std::panic::catch_unwind(|| {
+        let tree = scene_tree();
+        let owner = Node::new();
+        let emitter = Instance::<AsyncEmitter, _>::new().into_shared();
+        let emitter = unsafe { emitter.base().assume_safe() };
+
+        let dropped = Rc::new(Cell::new(false));
+        let guard = DropGuard(dropped.clone());
+        let done = emitter.signal::<(i64,)>("done");
+        spawn_local(&*owner, async move {
+            let _guard = guard;
+            done.await;
+            panic!("the task should be cancelled");
+        });
+
+        idle_frame(&tree);
+        assert!(!dropped.get());
+
+        // Freeing the owner cancels the task on the next idle frame, even if it was woken.
+        owner.free();
+        AsyncEmitter::emit_done(&emitter, 42);
+        idle_frame(&tree);
+        assert!(dropped.get());
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_task_cancellation failed");
+    }
+
+    ok
+}
+
+struct DropGuard(Rc<Cell<bool>>);
+
+impl Drop for DropGuard {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+fn scene_tree() -> TRef<'static, SceneTree> {
+    let main_loop = Engine::godot_singleton()
+        .get_main_loop()
+        .expect("main loop should exist");
+
+    unsafe { main_loop.assume_safe() }
+        .cast::<SceneTree>()
+        .expect("main loop should be a SceneTree")
+}
+
+/// Emits `idle_frame` on `tree`, which polls the tasks that are ready.
+fn idle_frame(tree: &SceneTree) {
+    tree.emit_signal("idle_frame", &[]);
+}
+
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+    unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+}
@@ -0,0 +1,77 @@
+use gdnative::nativescript::user_data::AtomicCellError;
+use gdnative::prelude::*;
+
+pub(crate) fn run_tests() -> bool {
+    let mut status = true;
+
+    status &= test_atomic_cell_data();
+
+    status
+}
+
+pub(crate) fn register(handle: InitHandle) {
+    crate::add_derived_class::<Counter>(handle);
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+#[user_data(AtomicCellData<Counter>)]
+struct Counter {
+    value: i64,
+}
+
+#[methods]
+impl Counter {
+    fn new(_owner: &Reference) -> Self {
+        Counter { value: 0 }
+    }
+}
+
+fn test_atomic_cell_data() -> bool {
+    println!(" -- test_atomic_cell_data");
+
+    let ok = std::panic::catch_unwind(|| {
+        let instance = Counter::new_instance().into_shared();
+        let instance = unsafe { instance.assume_safe() };
+
+        instance.map_mut(|this, _| this.value += 1).unwrap();
+
+        instance
+            .map(|_, _| {
+                assert_eq!(Ok(1), instance.map(|this, _| this.value));
+                assert_eq!(
+                    Err(AtomicCellError::Borrowed),
+                    instance.map_mut(|_, _| panic!("should never be called")),
+                );
+            })
+            .unwrap();
+
+        instance
+            .map_mut(|_, _| {
+                assert_eq!(
+                    Err(AtomicCellError::MutablyBorrowed),
+                    instance.map(|_, _| panic!("should never be called")),
+                );
+            })
+            .unwrap();
+
+        // Borrows are released when the closure panics.
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            instance.map_mut(|_, _| panic!("expected panic")).unwrap();
+        }));
+        assert!(result.is_err());
+
+        assert_eq!(Ok(1), instance.map_owned(|this, _| this.value));
+        assert_eq!(
+            Err(AtomicCellError::Taken),
+            instance.map(|_, _| panic!("should never be called")),
+        );
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_atomic_cell_data failed");
+    }
+
+    ok
+}
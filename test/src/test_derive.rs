@@ -9,6 +9,11 @@ pub(crate) fn run_tests() -> bool {
     status &= test_derive_owned_to_variant();
     status &= test_derive_nativeclass_with_property_hooks();
     status &= test_derive_nativeclass_without_constructor();
+    status &= test_derive_export_enum();
+    status &= test_derive_export_typed_array();
+    status &= test_derive_enum_tagging();
+    status &= test_derive_field_attributes();
+    status &= test_derive_property_group();
 
     status
 }
@@ -16,6 +21,9 @@ pub(crate) fn run_tests() -> bool {
 pub(crate) fn register(handle: InitHandle) {
     handle.add_class::<PropertyHooks>();
     handle.add_class::<EmplacementOnly>();
+    crate::add_derived_class::<ExportEnum>(handle);
+    crate::add_derived_class::<ExportTypedArray>(handle);
+    crate::add_derived_class::<PropertyGroups>(handle);
 }
 
 fn test_derive_to_variant() -> bool {
@@ -320,3 +328,396 @@ fn test_derive_nativeclass_without_constructor() -> bool {
 
     ok
 }
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, ToVariant, FromVariant, Export)]
+#[variant(repr = "int")]
+enum Mode {
+    Idle,
+    Patrol = 5,
+    Chase,
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct ExportEnum {
+    #[property]
+    mode: Mode,
+}
+
+#[methods]
+impl ExportEnum {
+    fn new(_owner: &Reference) -> Self {
+        ExportEnum { mode: Mode::Idle }
+    }
+}
+
+fn test_derive_export_enum() -> bool {
+    println!(" -- test_derive_export_enum");
+
+    let ok = std::panic::catch_unwind(|| {
+        assert_eq!(Some(5), Mode::Patrol.to_variant().try_to_i64());
+        assert_eq!(Ok(Mode::Chase), Mode::from_variant(&6.to_variant()));
+        assert!(Mode::from_variant(&1.to_variant()).is_err());
+        assert!(Mode::from_variant(&"Chase".to_variant()).is_err());
+
+        let instance = Instance::<ExportEnum, _>::new();
+        let base = instance.base();
+
+        let property = base
+            .get_property_list()
+            .iter()
+            .map(|info| Dictionary::from_variant(&info).unwrap())
+            .find(|info| info.get("name").map(|name| name.to_string()).as_deref() == Some("mode"))
+            .expect("property should be registered");
+
+        assert_eq!(
+            Some("Idle:0,Patrol:5,Chase:6".into()),
+            property.get("hint_string").and_then(|v| v.try_to_string())
+        );
+
+        base.set("mode", 5);
+        assert_eq!(Ok(Mode::Patrol), instance.map(|this, _| this.mode));
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_derive_export_enum failed");
+    }
+
+    ok
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct ExportTypedArray {
+    #[property]
+    levels: TypedVariantArray<i64>,
+}
+
+#[methods]
+impl ExportTypedArray {
+    fn new(_owner: &Reference) -> Self {
+        ExportTypedArray {
+            levels: TypedVariantArray::new().into_shared(),
+        }
+    }
+}
+
+fn test_derive_export_typed_array() -> bool {
+    println!(" -- test_derive_export_typed_array");
+
+    let ok = std::panic::catch_unwind(|| {
+        let instance = Instance::<ExportTypedArray, _>::new();
+        let base = instance.base();
+
+        let property = base
+            .get_property_list()
+            .iter()
+            .map(|info| Dictionary::from_variant(&info).unwrap())
+            .find(|info| info.get("name").map(|name| name.to_string()).as_deref() == Some("levels"))
+            .expect("property should be registered");
+
+        assert_eq!(
+            Some(VariantType::VariantArray as i64),
+            property.get("type").and_then(|v| v.try_to_i64())
+        );
+        assert_eq!(
+            Some(format!("{}:", VariantType::I64 as u32)),
+            property.get("hint_string").and_then(|v| v.try_to_string())
+        );
+
+        let levels = VariantArray::new();
+        levels.push(3);
+        base.set("levels", levels.into_shared());
+        assert_eq!(Ok(3), instance.map(|this, _| this.levels.get(0)));
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_derive_export_typed_array failed");
+    }
+
+    ok
+}
+
+#[derive(Clone, PartialEq, Debug, ToVariant, FromVariant)]
+#[variant(tag = "type")]
+enum Internal {
+    Start,
+    Move { x: i64, y: i64 },
+}
+
+#[derive(ToVariant)]
+#[variant(tag = "type")]
+enum InternalFlattened {
+    Spawn {
+        #[variant(flatten)]
+        extra: std::collections::HashMap<String, i64>,
+    },
+}
+
+#[derive(Clone, PartialEq, Debug, ToVariant, FromVariant)]
+#[variant(tag = "t", content = "c")]
+enum Adjacent {
+    Start,
+    Move(i64, i64),
+}
+
+#[derive(Clone, PartialEq, Debug, ToVariant, FromVariant)]
+#[variant(untagged)]
+enum Untagged {
+    Nothing,
+    Number(i64),
+    Text(String),
+}
+
+fn test_derive_enum_tagging() -> bool {
+    println!(" -- test_derive_enum_tagging");
+
+    let ok = std::panic::catch_unwind(|| {
+        let variant = Internal::Move { x: 1, y: 2 }.to_variant();
+        let dict = variant.try_to_dictionary().expect("should be a Dictionary");
+        assert_eq!(3, dict.len());
+        assert_eq!(
+            Some("Move".into()),
+            dict.get("type").and_then(|v| v.try_to_string())
+        );
+        assert_eq!(Some(2), dict.get("y").and_then(|v| v.try_to_i64()));
+        assert_eq!(
+            Ok(Internal::Move { x: 1, y: 2 }),
+            Internal::from_variant(&variant)
+        );
+        assert_eq!(
+            Ok(Internal::Start),
+            Internal::from_variant(&Internal::Start.to_variant())
+        );
+        assert_eq!(
+            Err(FromVariantError::MissingEnumTag { tag: "type" }),
+            Internal::from_variant(&Dictionary::new().into_shared().to_variant())
+        );
+
+        // Flattened fields can't overwrite the tag.
+        let extra = vec![("type".to_string(), 1), ("count".to_string(), 2)];
+        let variant = InternalFlattened::Spawn {
+            extra: extra.into_iter().collect(),
+        }
+        .to_variant();
+        let dict = variant.try_to_dictionary().expect("should be a Dictionary");
+        assert_eq!(
+            Some("Spawn".into()),
+            dict.get("type").and_then(|v| v.try_to_string())
+        );
+        assert_eq!(Some(2), dict.get("count").and_then(|v| v.try_to_i64()));
+
+        let variant = Adjacent::Move(3, 4).to_variant();
+        let dict = variant.try_to_dictionary().expect("should be a Dictionary");
+        assert_eq!(
+            Some("Move".into()),
+            dict.get("t").and_then(|v| v.try_to_string())
+        );
+        assert_eq!(
+            Some(2),
+            dict.get("c")
+                .and_then(|v| v.try_to_array())
+                .map(|a| a.len())
+        );
+        assert_eq!(Ok(Adjacent::Move(3, 4)), Adjacent::from_variant(&variant));
+        assert_eq!(
+            Ok(Adjacent::Start),
+            Adjacent::from_variant(&Adjacent::Start.to_variant())
+        );
+
+        let missing_content = Dictionary::new();
+        missing_content.insert("t", "Move");
+        assert_eq!(
+            Err(FromVariantError::MissingEnumContent { content: "c" }),
+            Adjacent::from_variant(&missing_content.into_shared().to_variant())
+        );
+
+        assert!(Untagged::Nothing.to_variant().is_nil());
+        assert_eq!(Some(5), Untagged::Number(5).to_variant().try_to_i64());
+        for value in &[
+            Untagged::Nothing,
+            Untagged::Number(5),
+            Untagged::Text("five".into()),
+        ] {
+            assert_eq!(
+                Ok(value),
+                Untagged::from_variant(&value.to_variant()).as_ref()
+            );
+        }
+
+        match Untagged::from_variant(&Vector2::new(1.0, 2.0).to_variant()) {
+            Err(FromVariantError::NoMatchingEnumVariant { errors }) => {
+                assert_eq!(3, errors.len());
+                assert_eq!("Text", errors[2].0);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_derive_enum_tagging failed");
+    }
+
+    ok
+}
+
+#[derive(Clone, PartialEq, Debug, ToVariant, FromVariant)]
+#[variant(rename_all = "camelCase")]
+struct SaveFile {
+    max_health: i64,
+    #[variant(rename = "pos")]
+    position: Vector2,
+    #[variant(default)]
+    unlocked_levels: Vec<String>,
+    #[variant(default = "default_difficulty")]
+    difficulty: i64,
+    #[variant(flatten)]
+    settings: Settings,
+}
+
+#[derive(Clone, PartialEq, Debug, ToVariant, FromVariant)]
+struct Settings {
+    volume: f64,
+}
+
+#[derive(Clone, PartialEq, Debug, FromVariant)]
+#[variant(deny_unknown_fields)]
+struct StrictSettings {
+    volume: f64,
+}
+
+#[derive(ToVariant)]
+struct FlattenedScalar {
+    #[variant(flatten)]
+    value: Variant,
+}
+
+fn default_difficulty() -> i64 {
+    2
+}
+
+fn test_derive_field_attributes() -> bool {
+    println!(" -- test_derive_field_attributes");
+
+    let ok = std::panic::catch_unwind(|| {
+        let save = SaveFile {
+            max_health: 100,
+            position: Vector2::new(1.0, 2.0),
+            unlocked_levels: vec!["forest".into()],
+            difficulty: 3,
+            settings: Settings { volume: 0.5 },
+        };
+
+        let variant = save.to_variant();
+        let dict = variant.try_to_dictionary().expect("should be a Dictionary");
+        assert_eq!(5, dict.len());
+        assert_eq!(
+            Some(100),
+            dict.get("maxHealth").and_then(|v| v.try_to_i64())
+        );
+        assert_eq!(
+            Some(Vector2::new(1.0, 2.0)),
+            dict.get("pos").and_then(|v| v.try_to_vector2())
+        );
+        assert_eq!(Some(0.5), dict.get("volume").and_then(|v| v.try_to_f64()));
+        assert_eq!(Ok(save), SaveFile::from_variant(&variant));
+
+        // Keys added in later versions are filled in from their defaults.
+        let old_save = Dictionary::new();
+        old_save.insert("maxHealth", 50);
+        old_save.insert("pos", Vector2::new(0.0, 0.0));
+        old_save.insert("volume", 1.0);
+        let old_save = SaveFile::from_variant(&old_save.into_shared().to_variant()).unwrap();
+        assert!(old_save.unlocked_levels.is_empty());
+        assert_eq!(2, old_save.difficulty);
+
+        let settings = Dictionary::new();
+        settings.insert("volume", 1.0);
+        settings.insert("brightness", 1.0);
+        assert_eq!(
+            Err(FromVariantError::UnknownField {
+                field_name: "brightness".into(),
+                expected: &["volume"],
+            }),
+            StrictSettings::from_variant(&settings.into_shared().to_variant())
+        );
+
+        // Flattened values that aren't dictionaries are kept under the field name.
+        let scalar = FlattenedScalar {
+            value: Variant::from_i64(1),
+        }
+        .to_variant();
+        let scalar = scalar.try_to_dictionary().expect("should be a Dictionary");
+        assert_eq!(Some(1), scalar.get("value").and_then(|v| v.try_to_i64()));
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_derive_field_attributes failed");
+    }
+
+    ok
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct PropertyGroups {
+    #[property]
+    health: i64,
+    #[property(group = "Movement")]
+    speed: f32,
+    #[property(group = "Movement")]
+    acceleration: f32,
+}
+
+#[methods]
+impl PropertyGroups {
+    fn new(_owner: &Reference) -> Self {
+        PropertyGroups {
+            health: 100,
+            speed: 1.0,
+            acceleration: 2.0,
+        }
+    }
+}
+
+fn test_derive_property_group() -> bool {
+    println!(" -- test_derive_property_group");
+
+    let ok = std::panic::catch_unwind(|| {
+        let instance = Instance::<PropertyGroups, _>::new();
+        let base = instance.base();
+
+        let script_properties = base
+            .get_property_list()
+            .iter()
+            .map(|info| Dictionary::from_variant(&info).unwrap())
+            .map(|info| {
+                let name = info.get("name").map(|name| name.to_string());
+                let usage = info.get("usage").and_then(|usage| usage.try_to_i64());
+                (name.unwrap_or_default(), usage.unwrap_or_default())
+            })
+            .skip_while(|(name, _)| name != "health")
+            .take(4)
+            .collect::<Vec<_>>();
+
+        let group = PropertyUsage::GROUP.bits() as i64;
+        let names = script_properties
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec!["health", "Movement", "speed", "acceleration"], names);
+        assert_eq!(group, script_properties[1].1);
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_derive_property_group failed");
+    }
+
+    ok
+}
@@ -0,0 +1,131 @@
+use gdnative::prelude::*;
+
+pub(crate) fn run_tests() -> bool {
+    let mut status = true;
+
+    status &= test_inherit_script_members();
+    status &= test_inherit_script_upcast();
+
+    status
+}
+
+pub(crate) fn register(handle: InitHandle) {
+    crate::add_derived_class::<Enemy>(handle);
+    crate::add_derived_class::<FlyingEnemy>(handle);
+}
+
+/// An enemy with health.
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct Enemy {
+    /// Remaining health of the enemy.
+    #[property]
+    health: i64,
+}
+
+#[methods]
+impl Enemy {
+    fn new(_owner: &Reference) -> Self {
+        Enemy { health: 100 }
+    }
+
+    /// Deals damage to the enemy, and returns the remaining health.
+    #[export]
+    fn damage(&mut self, _owner: &Reference, amount: i64) -> i64 {
+        self.health -= amount;
+        self.health
+    }
+
+    #[export]
+    fn kind(&self, _owner: &Reference) -> &'static str {
+        "enemy"
+    }
+}
+
+#[derive(NativeClass)]
+#[inherit_script(Enemy)]
+struct FlyingEnemy {
+    #[parent_script]
+    enemy: ParentScript<Enemy>,
+    #[property]
+    altitude: f64,
+}
+
+#[methods]
+impl FlyingEnemy {
+    fn new(owner: &Reference) -> Self {
+        FlyingEnemy {
+            enemy: ParentScript::new(Enemy::new(owner)),
+            altitude: 10.0,
+        }
+    }
+
+    #[export]
+    fn kind(&self, _owner: &Reference) -> &'static str {
+        "flying"
+    }
+}
+
+fn test_inherit_script_members() -> bool {
+    println!(" -- test_inherit_script_members");
+
+    let ok = std::panic::catch_unwind(|| {
+        let base = Instance::<FlyingEnemy, _>::new().into_base();
+
+        assert_eq!(Some(90), unsafe {
+            base.call("damage", &[10.to_variant()]).try_to_i64()
+        });
+        assert_eq!(Some(90), base.get("health").try_to_i64());
+        assert_eq!(Some(10.0), base.get("altitude").try_to_f64());
+
+        base.set("health", 42);
+        assert_eq!(Some(32), unsafe {
+            base.call("damage", &[10.to_variant()]).try_to_i64()
+        });
+
+        assert_eq!(Some("flying".into()), unsafe {
+            base.call("kind", &[]).try_to_string()
+        });
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_inherit_script_members failed");
+    }
+
+    ok
+}
+
+fn test_inherit_script_upcast() -> bool {
+    println!(" -- test_inherit_script_upcast");
+
+    let ok = std::panic::catch_unwind(|| {
+        let flying = Instance::<FlyingEnemy, _>::new().into_thread_local();
+        let enemy = flying
+            .clone()
+            .upcast::<Enemy>()
+            .expect("should be accessible");
+
+        enemy
+            .map_mut(|enemy, owner| enemy.damage(&*owner, 25))
+            .unwrap();
+        assert_eq!(
+            Ok(75),
+            flying.map(|flying, _| flying.enemy.map(|enemy| enemy.health).unwrap())
+        );
+
+        let enemy = Instance::<Enemy, _>::try_from_base(flying.into_base())
+            .expect("should be able to downcast to the parent script");
+        assert_eq!(Ok(75), enemy.map(|enemy, _| enemy.health));
+
+        let not_flying = Instance::<Enemy, _>::new().into_base();
+        assert!(Instance::<FlyingEnemy, _>::try_from_base(not_flying).is_err());
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_inherit_script_upcast failed");
+    }
+
+    ok
+}
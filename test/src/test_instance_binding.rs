@@ -0,0 +1,102 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use gdnative::prelude::*;
+
+pub(crate) fn run_tests() -> bool {
+    let mut status = true;
+
+    status &= test_instance_binding();
+    status &= test_cast_instance_after_script_change();
+
+    status
+}
+
+pub(crate) fn register(handle: InitHandle) {
+    handle.register_instance_binding::<AccessCount>();
+    crate::add_derived_class::<BindingScript>(handle);
+}
+
+static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+struct AccessCount(AtomicUsize);
+
+impl InstanceBinding for AccessCount {
+    fn new() -> Self {
+        CREATED.fetch_add(1, Ordering::AcqRel);
+        AccessCount(AtomicUsize::new(0))
+    }
+}
+
+fn test_instance_binding() -> bool {
+    println!(" -- test_instance_binding");
+
+    let ok = std::panic::catch_unwind(|| {
+        let created = CREATED.load(Ordering::Acquire);
+
+        let foo = Reference::new();
+        let bar = Reference::new();
+
+        let binding = foo
+            .binding::<AccessCount>()
+            .expect("binding should be registered");
+        binding.0.fetch_add(1, Ordering::AcqRel);
+        assert_eq!(created + 1, CREATED.load(Ordering::Acquire));
+
+        let binding = foo.as_ref().binding::<AccessCount>().unwrap();
+        assert_eq!(1, binding.0.fetch_add(1, Ordering::AcqRel));
+        assert_eq!(created + 1, CREATED.load(Ordering::Acquire));
+
+        let binding = bar.binding::<AccessCount>().unwrap();
+        assert_eq!(0, binding.0.load(Ordering::Acquire));
+        assert_eq!(created + 2, CREATED.load(Ordering::Acquire));
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_instance_binding failed");
+    }
+
+    ok
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct BindingScript {
+    value: i64,
+}
+
+#[methods]
+impl BindingScript {
+    fn new(_owner: &Reference) -> Self {
+        BindingScript { value: 42 }
+    }
+}
+
+fn test_cast_instance_after_script_change() -> bool {
+    println!(" -- test_cast_instance_after_script_change");
+
+    let ok = std::panic::catch_unwind(|| {
+        let base = BindingScript::new_instance().into_base().into_shared();
+        let base = unsafe { base.assume_safe() };
+
+        for _ in 0..2 {
+            let instance = base.cast_instance::<BindingScript>().unwrap();
+            assert_eq!(Ok(42), instance.map(|this, _| this.value));
+        }
+
+        let script = base.get_script().expect("should have a script");
+        base.set_script(Null::null());
+        assert!(base.cast_instance::<BindingScript>().is_none());
+
+        base.set_script(script);
+        let instance = base.cast_instance::<BindingScript>().unwrap();
+        assert_eq!(Ok(42), instance.map(|this, _| this.value));
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_cast_instance_after_script_change failed");
+    }
+
+    ok
+}
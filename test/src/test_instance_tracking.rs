@@ -0,0 +1,60 @@
+use gdnative::prelude::*;
+
+pub(crate) fn run_tests() -> bool {
+    let mut status = true;
+
+    status &= test_instance_tracking();
+
+    status
+}
+
+pub(crate) fn register(handle: InitHandle) {
+    crate::add_derived_class::<Tracked>(handle);
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct Tracked {
+    value: i64,
+}
+
+#[methods]
+impl Tracked {
+    fn new(_owner: &Reference) -> Self {
+        Tracked { value: 42 }
+    }
+}
+
+fn test_instance_tracking() -> bool {
+    println!(" -- test_instance_tracking");
+
+    let ok = std::panic::catch_unwind(|| {
+        assert_eq!(0, Instance::<Tracked, Shared>::live_count());
+
+        let foo = Tracked::new_instance();
+        let bar = Tracked::new_instance();
+        assert_eq!(2, Instance::<Tracked, Shared>::live_count());
+
+        let live = unsafe { Instance::<Tracked, Shared>::live_instances() };
+        assert_eq!(2, live.len());
+        for instance in &live {
+            let instance = unsafe { instance.assume_safe() };
+            assert_eq!(Ok(42), instance.map(|this, _| this.value));
+        }
+
+        drop(live);
+        drop(foo);
+        assert_eq!(1, Instance::<Tracked, Shared>::live_count());
+
+        drop(bar);
+        assert_eq!(0, Instance::<Tracked, Shared>::live_count());
+        assert!(unsafe { Instance::<Tracked, Shared>::live_instances() }.is_empty());
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_instance_tracking failed");
+    }
+
+    ok
+}
@@ -0,0 +1,89 @@
+use gdnative::nativescript::user_data::diagnostics::{self, LockAccess};
+use gdnative::nativescript::user_data::{DeadlockPolicy, LockOptions};
+use gdnative::prelude::*;
+
+pub(crate) fn run_tests() -> bool {
+    let mut status = true;
+
+    status &= test_lock_diagnostics();
+
+    status
+}
+
+pub(crate) fn register(handle: InitHandle) {
+    crate::add_derived_class::<Contended>(handle);
+}
+
+struct FailFast;
+
+impl LockOptions for FailFast {
+    const DEADLOCK_POLICY: DeadlockPolicy = DeadlockPolicy::Pessimistic;
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+#[user_data(user_data::MutexData<Contended, FailFast>)]
+struct Contended;
+
+#[methods]
+impl Contended {
+    fn new(_owner: &Reference) -> Self {
+        Contended
+    }
+}
+
+fn contended_stats() -> Option<diagnostics::LockStats> {
+    diagnostics::lock_stats()
+        .into_iter()
+        .find(|stats| stats.class_name() == "Contended")
+}
+
+fn test_lock_diagnostics() -> bool {
+    println!(" -- test_lock_diagnostics");
+
+    let ok = std::panic::catch_unwind(|| {
+        let instance = Contended::new_instance().into_shared();
+        let instance = unsafe { instance.assume_safe() };
+
+        // Nothing is recorded while disabled.
+        assert!(!diagnostics::is_enabled());
+        instance.map_mut(|_, _| {}).unwrap();
+        assert!(contended_stats().is_none());
+
+        diagnostics::set_enabled(true);
+
+        instance
+            .map_mut(|_, _| {
+                let holder = diagnostics::lock_holders()
+                    .into_iter()
+                    .find(|holder| holder.class_name() == "Contended")
+                    .expect("lock should have a holder");
+                assert_eq!(LockAccess::Write, holder.access());
+
+                // The lock is already held by this thread, so the pessimistic policy fails.
+                assert!(instance.map(|_, _| {}).is_err());
+            })
+            .unwrap();
+
+        assert!(diagnostics::lock_holders()
+            .iter()
+            .all(|holder| holder.class_name() != "Contended"));
+
+        let stats = contended_stats().expect("stats should be recorded");
+        assert_eq!(1, stats.acquisitions());
+        assert_eq!(1, stats.failures());
+        assert_eq!(1, stats.wait_histogram().iter().sum::<u64>());
+
+        diagnostics::reset();
+        assert!(contended_stats().is_none());
+    })
+    .is_ok();
+
+    diagnostics::set_enabled(false);
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_lock_diagnostics failed");
+    }
+
+    ok
+}
@@ -1,6 +1,11 @@
+use std::cell::Cell;
 use std::ops::Add;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
+use gdnative::api::{NativeScript, Script};
 use gdnative::nativescript::init::method::{StaticArgs, StaticArgsMethod};
+use gdnative::nativescript::init::MethodArg;
 use gdnative::prelude::*;
 
 pub(crate) fn run_tests() -> bool {
@@ -8,14 +13,29 @@ pub(crate) fn run_tests() -> bool {
 
     status &= test_register_property();
     status &= test_advanced_methods();
+    status &= test_method_args();
+    status &= test_signal_default_args();
+    status &= test_declare_signal();
+    status &= test_connect_fn();
+    status &= test_fallible_methods();
+    status &= test_panic_hook();
+    status &= test_dynamic_properties();
+    status &= test_documentation();
 
     status
 }
 
 pub(crate) fn register(handle: InitHandle) {
     handle.add_class::<RegisterSignal>();
+    handle.add_class::<SignalDefaults>();
     handle.add_class::<RegisterProperty>();
     handle.add_class::<AdvancedMethods>();
+    crate::add_derived_class::<DeclareSignal>(handle);
+    crate::add_derived_class::<SignalReceiver>(handle);
+    crate::add_derived_class::<FallibleMethods>(handle);
+    crate::add_derived_class::<PanickingMethods>(handle);
+    crate::add_derived_class::<DynamicProps>(handle);
+    crate::add_derived_class::<Documented>(handle);
 }
 
 #[derive(Copy, Clone, Debug, Default)]
@@ -39,6 +59,7 @@ impl NativeClass for RegisterSignal {
                 export_info: ExportInfo::new(VariantType::I64),
                 usage: PropertyUsage::DEFAULT,
             }],
+            num_default_args: 0,
         });
     }
 }
@@ -46,6 +67,43 @@ impl NativeClass for RegisterSignal {
 #[methods]
 impl RegisterSignal {}
 
+struct SignalDefaults;
+
+impl NativeClass for SignalDefaults {
+    type Base = Reference;
+    type UserData = user_data::Aether<SignalDefaults>;
+    fn class_name() -> &'static str {
+        "SignalDefaults"
+    }
+    fn init(_owner: TRef<Reference>) -> SignalDefaults {
+        SignalDefaults
+    }
+    fn register_properties(builder: &ClassBuilder<Self>) {
+        builder.add_signal(Signal {
+            name: "progress",
+            args: &[
+                SignalArgument {
+                    name: "amount",
+                    default: Variant::new(),
+                    export_info: ExportInfo::new(VariantType::I64),
+                    usage: PropertyUsage::DEFAULT,
+                },
+                SignalArgument {
+                    name: "total",
+                    default: Variant::from_i64(100),
+                    export_info: ExportInfo::new(VariantType::I64),
+                    usage: PropertyUsage::DEFAULT,
+                },
+            ],
+            // `amount` defaults to nil.
+            num_default_args: 2,
+        });
+    }
+}
+
+#[methods]
+impl SignalDefaults {}
+
 struct RegisterProperty {
     value: i64,
 }
@@ -162,6 +220,11 @@ where
 fn register_methods(builder: &ClassBuilder<AdvancedMethods>) {
     builder
         .build_method("add_ints", StaticArgs::new(StatefulMixin { d: 42 }))
+        .with_args(vec![
+            MethodArg::of::<i32, _>("a"),
+            MethodArg::of::<i32, _>("b"),
+            MethodArg::of::<i32, _>("c"),
+        ])
         .done();
 
     builder
@@ -241,3 +304,456 @@ fn test_advanced_methods() -> bool {
 
     ok
 }
+
+fn test_method_args() -> bool {
+    println!(" -- test_method_args");
+
+    let ok = std::panic::catch_unwind(|| {
+        let base = RegisterProperty::new_instance().into_base();
+        assert_eq!(
+            vec![("value".to_string(), VariantType::I64)],
+            script_method_args(&base, "set_value"),
+        );
+
+        let base = Instance::<AdvancedMethods, _>::new().into_base();
+        assert_eq!(
+            vec![
+                ("a".to_string(), VariantType::I64),
+                ("b".to_string(), VariantType::I64),
+                ("c".to_string(), VariantType::I64),
+            ],
+            script_method_args(&base, "add_ints"),
+        );
+        assert!(script_method_args(&base, "add_floats").is_empty());
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_method_args failed");
+    }
+
+    ok
+}
+
+fn script_method_args(base: &Reference, method: &str) -> Vec<(String, VariantType)> {
+    let script = base.get_script().expect("should have a script");
+    let script = unsafe { script.assume_safe() }
+        .cast::<Script>()
+        .expect("should be a script");
+
+    let info = script
+        .get_script_method_list()
+        .iter()
+        .map(|info| Dictionary::from_variant(&info).unwrap())
+        .find(|info| info.get("name").map(|name| name.to_string()).as_deref() == Some(method))
+        .expect("method should be registered");
+
+    VariantArray::from_variant(&info.get("args").unwrap())
+        .unwrap()
+        .iter()
+        .map(|arg| {
+            let arg = Dictionary::from_variant(&arg).unwrap();
+            let name = arg.get("name").unwrap().to_string();
+            let ty = VariantType::from_sys(arg.get("type").unwrap().to_i64() as _);
+            (name, ty)
+        })
+        .collect()
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct DeclareSignal;
+
+#[methods]
+impl DeclareSignal {
+    fn new(_owner: &Reference) -> Self {
+        DeclareSignal
+    }
+
+    /// Emitted when something hits the object.
+    #[signal]
+    fn hit(damage: i64, source: Ref<Reference>);
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct SignalReceiver {
+    damage: i64,
+}
+
+#[methods]
+impl SignalReceiver {
+    fn new(_owner: &Reference) -> Self {
+        SignalReceiver { damage: 0 }
+    }
+
+    #[export]
+    fn on_hit(&mut self, _owner: &Reference, damage: i64, _source: Ref<Reference>) {
+        self.damage = damage;
+    }
+}
+
+fn test_signal_default_args() -> bool {
+    println!(" -- test_signal_default_args");
+
+    let ok = std::panic::catch_unwind(|| {
+        let base = SignalDefaults::new_instance().into_base();
+
+        let signal = base
+            .get_signal_list()
+            .iter()
+            .map(|signal| signal.to_dictionary())
+            .find(|signal| {
+                signal.get("name").map(|name| name.to_string()) == Some("progress".into())
+            })
+            .expect("signal should be registered");
+
+        let default_args = signal
+            .get("default_args")
+            .expect("signal should have default arguments")
+            .to_array();
+
+        assert_eq!(2, default_args.len());
+        assert!(default_args.get(0).is_nil());
+        assert_eq!(Some(100), default_args.get(1).try_to_i64());
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_signal_default_args failed");
+    }
+
+    ok
+}
+
+fn test_declare_signal() -> bool {
+    println!(" -- test_declare_signal");
+
+    let ok = std::panic::catch_unwind(|| {
+        let emitter = Instance::<DeclareSignal, _>::new().into_shared();
+        let receiver = Instance::<SignalReceiver, _>::new().into_shared();
+
+        let emitter_base = unsafe { emitter.base().assume_safe() };
+        emitter_base
+            .connect(
+                "hit",
+                receiver.base(),
+                "on_hit",
+                VariantArray::new_shared(),
+                0,
+            )
+            .unwrap();
+
+        DeclareSignal::emit_hit(&emitter_base, 42, Reference::new().into_shared());
+
+        let receiver = unsafe { receiver.assume_safe() };
+        assert_eq!(Ok(42), receiver.map(|receiver, _| receiver.damage));
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_declare_signal failed");
+    }
+
+    ok
+}
+
+fn test_connect_fn() -> bool {
+    println!(" -- test_connect_fn");
+
+    let ok = std::panic::catch_unwind(|| {
+        let damage = Rc::new(Cell::new(0));
+        let emitter = Instance::<DeclareSignal, _>::new().into_shared();
+
+        {
+            let emitter_base = unsafe { emitter.base().assume_safe() };
+
+            let closure_damage = damage.clone();
+            connect_fn(&*emitter_base, "hit", move |mut args| {
+                closure_damage.set(args.read::<i64>().get().unwrap());
+            })
+            .unwrap();
+
+            assert!(connect_fn(&*emitter_base, "no_such_signal", |_| {}).is_err());
+
+            DeclareSignal::emit_hit(&emitter_base, 42, Reference::new().into_shared());
+            assert_eq!(42, damage.get());
+        }
+
+        // The closure should be dropped along with the emitter.
+        drop(emitter);
+        assert_eq!(1, Rc::strong_count(&damage));
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_connect_fn failed");
+    }
+
+    ok
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct FallibleMethods;
+
+#[methods]
+impl FallibleMethods {
+    fn new(_owner: &Reference) -> Self {
+        FallibleMethods
+    }
+
+    #[export]
+    fn parse(&self, _owner: &Reference, s: String) -> Result<i64, std::num::ParseIntError> {
+        s.parse()
+    }
+
+    #[export(err = "nil")]
+    fn parse_nil(&self, _owner: &Reference, s: String) -> Result<i64, std::num::ParseIntError> {
+        s.parse()
+    }
+
+    #[export(err = "dict")]
+    fn parse_dict(&self, _owner: &Reference, s: String) -> Result<i64, std::num::ParseIntError> {
+        s.parse()
+    }
+}
+
+fn test_fallible_methods() -> bool {
+    println!(" -- test_fallible_methods");
+
+    let ok = std::panic::catch_unwind(|| {
+        let instance = Instance::<FallibleMethods, _>::new();
+        let base = instance.base();
+
+        let call = |method: &str, s: &str| unsafe { base.call(method, &[s.to_variant()]) };
+
+        assert_eq!(Some(42), call("parse", "42").try_to_i64());
+        assert!(call("parse", "foo").is_nil());
+
+        assert_eq!(Some(42), call("parse_nil", "42").try_to_i64());
+        assert!(call("parse_nil", "foo").is_nil());
+
+        assert_eq!(Some(42), call("parse_dict", "42").try_to_i64());
+        let err = call("parse_dict", "foo")
+            .try_to_dictionary()
+            .expect("should return a dictionary");
+        assert_eq!(
+            Some("invalid digit found in string".into()),
+            err.get("error").and_then(|e| e.try_to_string())
+        );
+        assert!(err.contains("site"));
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_fallible_methods failed");
+    }
+
+    ok
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct PanickingMethods;
+
+#[methods]
+impl PanickingMethods {
+    fn new(_owner: &Reference) -> Self {
+        PanickingMethods
+    }
+
+    #[export]
+    fn explode(&self, _owner: &Reference) -> i64 {
+        panic!("boom")
+    }
+}
+
+fn test_panic_hook() -> bool {
+    println!(" -- test_panic_hook");
+
+    let ok = std::panic::catch_unwind(|| {
+        let caught = Arc::new(Mutex::new(None));
+
+        let hook_caught = caught.clone();
+        gdnative::panic::set_hook(move |ctx| {
+            *hook_caught.lock().unwrap() = Some((
+                ctx.class_name().to_string(),
+                ctx.callback(),
+                ctx.message().map(String::from),
+                ctx.location().is_some(),
+            ));
+        });
+
+        let instance = Instance::<PanickingMethods, _>::new();
+        let ret = unsafe { instance.base().call("explode", &[]) };
+        gdnative::panic::take_hook();
+
+        assert!(ret.is_nil());
+        assert_eq!(
+            Some((
+                "PanickingMethods".to_string(),
+                gdnative::panic::Callback::Method,
+                Some("boom".to_string()),
+                true,
+            )),
+            caught.lock().unwrap().take()
+        );
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_panic_hook failed");
+    }
+
+    ok
+}
+
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct DynamicProps {
+    params: Vec<(String, f64)>,
+}
+
+#[methods]
+impl DynamicProps {
+    fn new(_owner: &Reference) -> Self {
+        DynamicProps {
+            params: vec![("roughness".into(), 0.5), ("metallic".into(), 1.0)],
+        }
+    }
+}
+
+impl DynamicProperties for DynamicProps {
+    fn property_list(&self, _owner: TRef<Reference>) -> Vec<PropertyInfo> {
+        self.params
+            .iter()
+            .map(|(name, _)| PropertyInfo::of::<f64>(format!("params/{}", name), None))
+            .collect()
+    }
+
+    fn get_property(&self, _owner: TRef<Reference>, name: &str) -> Option<Variant> {
+        let name = name.strip_prefix("params/")?;
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.to_variant())
+    }
+
+    fn set_property(&mut self, _owner: TRef<Reference>, name: &str, value: Variant) -> bool {
+        let name = match name.strip_prefix("params/") {
+            Some(name) => name,
+            None => return false,
+        };
+
+        match (
+            self.params.iter_mut().find(|(param, _)| param == name),
+            value.try_to_f64(),
+        ) {
+            (Some((_, param)), Some(value)) => {
+                *param = value;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn test_dynamic_properties() -> bool {
+    println!(" -- test_dynamic_properties");
+
+    let ok = std::panic::catch_unwind(|| {
+        let instance = Instance::<DynamicProps, _>::new();
+        let base = instance.base();
+
+        let property = base
+            .get_property_list()
+            .iter()
+            .map(|info| Dictionary::from_variant(&info).unwrap())
+            .find(|info| {
+                info.get("name").map(|name| name.to_string()).as_deref() == Some("params/metallic")
+            })
+            .expect("dynamic property should be listed");
+
+        assert_eq!(
+            Some(VariantType::F64 as i64),
+            property.get("type").and_then(|t| t.try_to_i64())
+        );
+
+        assert_eq!(Some(0.5), base.get("params/roughness").try_to_f64());
+        assert!(base.get("params/unknown").is_nil());
+
+        base.set("params/roughness", 0.25);
+        assert_eq!(Some(0.25), base.get("params/roughness").try_to_f64());
+        assert_eq!(Ok(0.25), instance.map(|this, _| this.params[0].1));
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_dynamic_properties failed");
+    }
+
+    ok
+}
+
+/// A class with documentation.
+///
+/// Spanning several lines.
+#[derive(NativeClass)]
+#[inherit(Reference)]
+struct Documented {
+    /// The value of the thing.
+    #[property]
+    value: i64,
+}
+
+#[methods]
+impl Documented {
+    fn new(_owner: &Reference) -> Self {
+        Documented { value: 0 }
+    }
+
+    /// Does the thing.
+    #[export]
+    fn do_thing(&self, _owner: &Reference) {}
+
+    /// Emitted when the thing is done.
+    #[signal]
+    fn thing_done();
+}
+
+fn test_documentation() -> bool {
+    println!(" -- test_documentation");
+
+    let ok = std::panic::catch_unwind(|| {
+        let base = Documented::new_instance().into_base();
+        let script = base.get_script().expect("should have a script");
+        let script = unsafe { script.assume_safe() }
+            .cast::<NativeScript>()
+            .expect("should be a NativeScript");
+
+        assert_eq!(
+            "A class with documentation.\n\nSpanning several lines.",
+            script.get_class_documentation().to_string()
+        );
+        assert_eq!(
+            "Does the thing.",
+            script.get_method_documentation("do_thing").to_string()
+        );
+        assert_eq!(
+            "The value of the thing.",
+            script.get_property_documentation("value").to_string()
+        );
+        assert_eq!(
+            "Emitted when the thing is done.",
+            script.get_signal_documentation("thing_done").to_string()
+        );
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_documentation failed");
+    }
+
+    ok
+}
@@ -0,0 +1,152 @@
+use gdnative::api::{Engine, NetworkedMultiplayerENet, SceneTree};
+use gdnative::prelude::*;
+
+/// First port tried for the server created to call RPCs locally.
+const PORT: i64 = 27816;
+/// Number of ports tried, in case some of them are in use.
+const PORT_ATTEMPTS: i64 = 32;
+
+pub(crate) fn run_tests() -> bool {
+    let mut status = true;
+
+    status &= test_rpc_helpers();
+    status &= test_property_rset_mode();
+
+    status
+}
+
+pub(crate) fn register(handle: InitHandle) {
+    crate::add_derived_class::<RpcReceiver>(handle);
+}
+
+#[derive(NativeClass)]
+#[inherit(Node)]
+struct RpcReceiver {
+    calls: Vec<(String, i64)>,
+    #[property(rpc = "remote_sync")]
+    synced: i64,
+    #[property]
+    unsynced: i64,
+}
+
+#[methods]
+impl RpcReceiver {
+    fn new(_owner: &Node) -> Self {
+        RpcReceiver {
+            calls: Vec::new(),
+            synced: 0,
+            unsynced: 0,
+        }
+    }
+
+    #[export(rpc = "remote_sync")]
+    fn add(&mut self, _owner: &Node, a: i64, #[opt] b: i64) {
+        self.calls.push(("add".into(), a + b));
+    }
+
+    #[export(rpc = "remote_sync", name = "renamed")]
+    fn set_value(&mut self, _owner: &Node, value: i64) {
+        self.calls.push(("renamed".into(), value));
+    }
+}
+
+/// Adds a new `RpcReceiver` to the current scene, with an active network peer, and calls `f`
+/// with it. `remote_sync` methods and properties are then also called and set locally.
+fn with_receiver<F>(f: F)
+where
+    F: FnOnce(TRef<'_, Node>, RefInstance<'_, RpcReceiver, Shared>),
+{
+    let tree = Engine::godot_singleton()
+        .get_main_loop()
+        .expect("main loop should exist");
+    let tree = unsafe { tree.assume_safe() }
+        .cast::<SceneTree>()
+        .expect("main loop should be a SceneTree");
+    let scene = tree.current_scene().expect("current scene should exist");
+    let scene = unsafe { scene.assume_safe() };
+
+    let peer = NetworkedMultiplayerENet::new().into_shared();
+    let created = (PORT..PORT + PORT_ATTEMPTS).any(|port| {
+        unsafe { peer.assume_safe() }
+            .create_server(port, 1, 0, 0)
+            .is_ok()
+    });
+    assert!(created, "server should be created on a free port");
+    tree.set_network_peer(peer.clone());
+
+    let receiver = RpcReceiver::new_instance().into_shared();
+    let base = receiver.base().clone();
+    scene.add_child(&base, false);
+
+    let node = unsafe { base.assume_safe() };
+    let instance = unsafe { receiver.assume_safe() };
+    f(node, instance);
+
+    scene.remove_child(&base);
+    unsafe { base.assume_unique().free() };
+
+    tree.set_network_peer(Null::null());
+    unsafe { peer.assume_safe() }.close_connection(0);
+}
+
+fn test_rpc_helpers() -> bool {
+    println!(" -- test_rpc_helpers");
+
+    let ok = std::panic::catch_unwind(|| {
+        let mut calls = Vec::new();
+
+        with_receiver(|node, receiver| {
+            RpcReceiver::rpc_add(&node, 1, None);
+            RpcReceiver::rpc_add(&node, 1, Some(2));
+            RpcReceiver::rpc_set_value(&node, 42);
+            RpcReceiver::rpc_id_set_value(&node, 1, 7);
+
+            calls = receiver.map(|receiver, _| receiver.calls.clone()).unwrap();
+        });
+
+        assert_eq!(
+            vec![
+                ("add".to_string(), 1),
+                ("add".to_string(), 3),
+                ("renamed".to_string(), 42),
+                ("renamed".to_string(), 7),
+            ],
+            calls,
+        );
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_rpc_helpers failed");
+    }
+
+    ok
+}
+
+fn test_property_rset_mode() -> bool {
+    println!(" -- test_property_rset_mode");
+
+    let ok = std::panic::catch_unwind(|| {
+        let mut values = (0, 0);
+
+        with_receiver(|node, receiver| {
+            // `rset` only sets `remote_sync` properties locally. Properties without an RPC mode
+            // are left unchanged.
+            node.rset("synced", 5);
+            node.rset("unsynced", 5);
+
+            values = receiver
+                .map(|receiver, _| (receiver.synced, receiver.unsynced))
+                .unwrap();
+        });
+
+        assert_eq!((5, 0), values);
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_property_rset_mode failed");
+    }
+
+    ok
+}
@@ -0,0 +1,144 @@
+use gdnative::core_types::variant_serde::{self, Error};
+use gdnative::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub(crate) fn run_tests() -> bool {
+    let mut status = true;
+
+    status &= test_variant_serde_round_trip();
+    status &= test_variant_serde_pass_through();
+
+    status
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Item {
+    Key,
+    Coins(u32),
+    Scroll { title: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct SaveData {
+    level: String,
+    position: Vector2,
+    health: Option<f32>,
+    items: Vec<Item>,
+    extra: Variant,
+}
+
+fn test_variant_serde_round_trip() -> bool {
+    println!(" -- test_variant_serde_round_trip");
+
+    let ok = std::panic::catch_unwind(|| {
+        let save = SaveData {
+            level: "forest".into(),
+            position: Vector2::new(1.0, 2.0),
+            health: None,
+            items: vec![
+                Item::Key,
+                Item::Coins(42),
+                Item::Scroll {
+                    title: "fireball".into(),
+                },
+            ],
+            extra: Vector3::new(1.0, 2.0, 3.0).to_variant(),
+        };
+
+        let variant = variant_serde::to_variant(&save).unwrap();
+        let dict = variant.try_to_dictionary().expect("should be a Dictionary");
+        assert_eq!(
+            Some("forest".into()),
+            dict.get("level").unwrap().try_to_string()
+        );
+        assert!(dict.get("health").unwrap().is_nil());
+
+        // Core types and variants are passed through as they are.
+        assert_eq!(
+            Some(Vector2::new(1.0, 2.0)),
+            dict.get("position").unwrap().try_to_vector2()
+        );
+        assert_eq!(
+            Some(Vector3::new(1.0, 2.0, 3.0)),
+            dict.get("extra").unwrap().try_to_vector3()
+        );
+
+        let items = dict.get("items").unwrap().try_to_array().unwrap();
+        assert_eq!(Some("Key".into()), items.get(0).try_to_string());
+
+        let back: SaveData = variant_serde::from_variant(&variant).unwrap();
+        assert_eq!(save, back);
+
+        // Core types can be read from variants of their own type, or from dictionaries.
+        let position: Vector2 =
+            variant_serde::from_variant(&Vector2::new(3.0, 4.0).to_variant()).unwrap();
+        assert_eq!(Vector2::new(3.0, 4.0), position);
+
+        let fields = Dictionary::new();
+        fields.insert("x", 3.0);
+        fields.insert("y", 4.0);
+        let position: Vector2 =
+            variant_serde::from_variant(&fields.into_shared().to_variant()).unwrap();
+        assert_eq!(Vector2::new(3.0, 4.0), position);
+
+        assert_eq!(
+            Err(Error::IntegerOutOfRange),
+            variant_serde::to_variant(&u64::MAX)
+        );
+        assert_eq!(
+            Err(Error::UnsupportedType(VariantType::Object)),
+            variant_serde::from_variant::<i64>(&Reference::new().into_shared().to_variant())
+        );
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_variant_serde_round_trip failed");
+    }
+
+    ok
+}
+
+fn test_variant_serde_pass_through() -> bool {
+    println!(" -- test_variant_serde_pass_through");
+
+    let ok = std::panic::catch_unwind(|| {
+        let array = VariantArray::new();
+        array.push(1);
+        array.push(Color::rgb(1.0, 0.5, 0.0));
+        array.push(Int32Array::from_vec(vec![1, 2, 3]));
+        array.push(Variant::new());
+        array.push(NodePath::from_str("../sibling"));
+        let original = array.into_shared().to_variant();
+
+        // Variants keep their types in both directions.
+        let variant = variant_serde::to_variant(&original).unwrap();
+        let array = variant.try_to_array().expect("should be an Array");
+        assert_eq!(5, array.len());
+        assert_eq!(Some(Color::rgb(1.0, 0.5, 0.0)), array.get(1).try_to_color());
+        assert_eq!(VariantType::Int32Array, array.get(2).get_type());
+        assert_eq!(VariantType::NodePath, array.get(4).get_type());
+
+        let back: Variant = variant_serde::from_variant(&original).unwrap();
+        let back = back.try_to_array().unwrap();
+        assert_eq!(5, back.len());
+        assert_eq!(Some(1), back.get(0).try_to_i64());
+        assert_eq!(Some(Color::rgb(1.0, 0.5, 0.0)), back.get(1).try_to_color());
+        assert_eq!(
+            vec![1, 2, 3],
+            back.get(2).try_to_int32_array().unwrap().read().to_vec()
+        );
+        assert!(back.get(3).is_nil());
+        assert_eq!(
+            Some("../sibling".to_string()),
+            back.get(4).try_to_node_path().map(|path| path.to_string())
+        );
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_variant_serde_pass_through failed");
+    }
+
+    ok
+}
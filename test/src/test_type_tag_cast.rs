@@ -0,0 +1,138 @@
+use gdnative::prelude::*;
+
+pub(crate) fn run_tests() -> bool {
+    let mut status = true;
+
+    status &= test_global_type_tag_cast();
+
+    #[cfg(feature = "bench")]
+    {
+        status &= bench_cast();
+    }
+
+    status
+}
+
+pub(crate) fn register(handle: InitHandle) {
+    crate::add_derived_class::<CastTarget>(handle);
+}
+
+#[derive(NativeClass)]
+#[inherit(Node2D)]
+struct CastTarget;
+
+#[methods]
+impl CastTarget {
+    fn new(_owner: &Node2D) -> Self {
+        CastTarget
+    }
+}
+
+fn test_global_type_tag_cast() -> bool {
+    println!(" -- test_global_type_tag_cast");
+
+    let ok = std::panic::catch_unwind(|| {
+        let node = Node2D::new();
+        let node = node.upcast::<Node>();
+
+        assert!(node.as_raw().is_class::<Object>());
+        assert!(node.as_raw().is_class::<Node>());
+        assert!(node.as_raw().is_class::<CanvasItem>());
+        assert!(node.as_raw().is_class::<Node2D>());
+        assert!(!node.as_raw().is_class::<Control>());
+        assert!(!node.as_raw().is_class::<Spatial>());
+
+        assert!(node.as_raw().is_class_by_name("CanvasItem"));
+        assert!(!node.as_raw().is_class_by_name("NotAClass"));
+
+        let node = node.cast::<Node2D>().expect("should be a Node2D");
+        node.free();
+
+        // Objects with a script of this library are checked through their cache.
+        let scripted = CastTarget::new_instance().into_base();
+        let scripted = scripted.upcast::<Node>();
+
+        assert!(scripted.as_raw().is_class::<Object>());
+        assert!(scripted.as_raw().is_class::<CanvasItem>());
+        assert!(scripted.as_raw().is_class::<Node2D>());
+        assert!(!scripted.as_raw().is_class::<Control>());
+
+        let scripted = scripted.cast::<Node2D>().expect("should be a Node2D");
+        scripted.free();
+
+        let reference = Reference::new().into_shared();
+        let reference = unsafe { reference.assume_safe() };
+        assert!(reference.upcast::<Object>().cast::<Node>().is_none());
+        assert!(reference.upcast::<Object>().cast::<Reference>().is_some());
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test test_global_type_tag_cast failed");
+    }
+
+    ok
+}
+
+/// Compares casts of an object with a script of this library, which use its global type tag,
+/// with casts of an object without one, which call `Object::is_class`. Only prints the
+/// timings, since they depend on the machine. Enabled with the `bench` feature, preferably
+/// in a release build:
+///
+/// ```text
+/// cargo build --release --features=bench
+/// ```
+///
+/// Building with `bench,no_global_type_tags` makes both casts call `Object::is_class`, for
+/// comparison.
+#[cfg(feature = "bench")]
+fn bench_cast() -> bool {
+    println!(" -- bench_cast");
+
+    let ok = std::panic::catch_unwind(|| {
+        let scripted = CastTarget::new_instance().into_base().into_shared();
+        let scripted = unsafe { scripted.assume_safe() }.upcast::<Object>();
+
+        let plain = Node2D::new().into_shared();
+        let plain = unsafe { plain.assume_safe() }.upcast::<Object>();
+
+        let (scripted_casts, scripted_time) = time_casts(scripted);
+        let (plain_casts, plain_time) = time_casts(plain);
+
+        assert_eq!(scripted_casts, plain_casts);
+
+        println!(
+            "   {} casts: {:?} with a script, {:?} without",
+            scripted_casts, scripted_time, plain_time,
+        );
+
+        unsafe { scripted.cast::<Node>().unwrap().assume_unique().free() };
+        unsafe { plain.cast::<Node>().unwrap().assume_unique().free() };
+    })
+    .is_ok();
+
+    if !ok {
+        gdnative::godot_error!("   !! Test bench_cast failed");
+    }
+
+    ok
+}
+
+/// Casts `object` to a class it inherits and to one it doesn't, and returns the number of
+/// casts along with the time taken.
+#[cfg(feature = "bench")]
+fn time_casts(object: TRef<'_, Object>) -> (u32, std::time::Duration) {
+    const ITERATIONS: u32 = 100_000;
+
+    let start = std::time::Instant::now();
+    let mut succeeded = 0;
+    for _ in 0..ITERATIONS {
+        succeeded += object.cast::<CanvasItem>().is_some() as u32;
+        succeeded += object.cast::<Control>().is_some() as u32;
+    }
+    let time = start.elapsed();
+
+    assert_eq!(ITERATIONS, succeeded);
+
+    (ITERATIONS * 2, time)
+}